@@ -5,7 +5,9 @@ use std::{marker::PhantomData, ops::Deref};
 use bytemuck::Zeroable;
 #[macro_use]
 extern crate static_assertions;
+extern crate self as vst3;
 pub mod plugininterfaces;
+pub mod publicsdk;
 pub fn add(left: usize, right: usize) -> usize {
     left + right
 }
@@ -0,0 +1,247 @@
+//! Byte stream interfaces, see `pluginterfaces/base/ibstream.h`
+//!
+//! Component and controller states are read from and written to an [`IBStream`].
+//! [`IBStream::io`] wraps a stream in a [`StreamAdapter`], so the standard I/O
+//! helpers work on any stream passed by the host:
+//!
+//! ```ignore
+//! unsafe fn set_state(&self, state: &IBStream) -> Result<(), EInterface> {
+//!     let mut version = [0; 4];
+//!     state.io().read_exact(&mut version).map_err(|_| EInterface::ResultFalse)?;
+//!     ...
+//! }
+//! ```
+use super::{macros::*, pluginreexports::*, EInterface, Interface};
+use std::{
+    io::{self, SeekFrom},
+    mem::transmute_copy,
+    os::raw::c_void,
+};
+
+/// Origin of [`IBStream::seek`]
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IStreamSeekMode {
+    /// Seek from the beginning of the stream
+    Set = 0,
+    /// Seek from the current position
+    Cur = 1,
+    /// Seek from the end of the stream
+    End = 2,
+}
+impl IStreamSeekMode {
+    pub fn from_raw(mode: i32) -> Option<Self> {
+        match mode {
+            0 => Some(IStreamSeekMode::Set),
+            1 => Some(IStreamSeekMode::Cur),
+            2 => Some(IStreamSeekMode::End),
+            _ => None,
+        }
+    }
+}
+
+declare_class_iid!(IBStream, 0xC3BF_6EA2, 0x3099_4752, 0x9B6B_F990, 0x1EE3_3E9B);
+declare_interface!(IBStream);
+interface_hierarchy!(IBStream, FUnknown);
+impl IBStream {
+    /// Reads up to `buffer.len()` bytes, returns the number of bytes read
+    pub fn read(&self, buffer: &mut [u8]) -> Result<usize, EInterface> {
+        let num_bytes = i32::try_from(buffer.len()).unwrap_or(i32::MAX);
+        let read =
+            unsafe { (self.vtable().read)(transmute_copy(self), buffer.as_mut_ptr(), num_bytes) }?;
+        Ok(read.clamp(0, num_bytes) as usize)
+    }
+    /// Writes up to `buffer.len()` bytes, returns the number of bytes written
+    pub fn write(&self, buffer: &[u8]) -> Result<usize, EInterface> {
+        let num_bytes = i32::try_from(buffer.len()).unwrap_or(i32::MAX);
+        let written =
+            unsafe { (self.vtable().write)(transmute_copy(self), buffer.as_ptr(), num_bytes) }?;
+        Ok(written.clamp(0, num_bytes) as usize)
+    }
+    /// Moves the read/write position, returns the new position
+    pub fn seek(&self, pos: i64, mode: IStreamSeekMode) -> Result<i64, EInterface> {
+        unsafe { (self.vtable().seek)(transmute_copy(self), pos, mode as i32) }
+    }
+    /// Returns the current read/write position
+    pub fn tell(&self) -> Result<i64, EInterface> {
+        unsafe { (self.vtable().tell)(transmute_copy(self)) }
+    }
+    /// Adapter to use the stream with [`std::io`]
+    pub fn io(&self) -> StreamAdapter<'_> {
+        StreamAdapter::new(self)
+    }
+}
+declare_class_vtable!(
+    IBStream,
+    base FUnknown,
+    function read: (buffer: *mut u8, num_bytes: i32) -> Result<i32, EInterface>,
+    function write: (buffer: *const u8, num_bytes: i32) -> Result<i32, EInterface>,
+    function seek: (pos: i64, mode: i32) -> Result<i64, EInterface>,
+    function tell: () -> Result<i64, EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IBStreamVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IBStreamImpl,
+    {
+        unsafe fn read<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            buffer: *mut u8,
+            num_bytes: i32,
+        ) -> Result<i32, EInterface>
+        where
+            Identity: IBStreamImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IBStreamImpl::read(this, buffer, num_bytes)
+        }
+        unsafe fn write<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            buffer: *const u8,
+            num_bytes: i32,
+        ) -> Result<i32, EInterface>
+        where
+            Identity: IBStreamImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IBStreamImpl::write(this, buffer, num_bytes)
+        }
+        unsafe fn seek<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            pos: i64,
+            mode: i32,
+        ) -> Result<i64, EInterface>
+        where
+            Identity: IBStreamImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IBStreamImpl::seek(this, pos, mode)
+        }
+        unsafe fn tell<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> Result<i64, EInterface>
+        where
+            Identity: IBStreamImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IBStreamImpl::tell(this)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            read: read::<Identity, OFFSET>,
+            write: write::<Identity, OFFSET>,
+            seek: seek::<Identity, OFFSET>,
+            tell: tell::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IBStream as Interface>::iid
+    }
+}
+
+/// Adapter implementing [`io::Read`], [`io::Write`] and [`io::Seek`] over an [`IBStream`]
+#[derive(Copy, Clone)]
+pub struct StreamAdapter<'a> {
+    stream: &'a IBStream,
+}
+impl<'a> StreamAdapter<'a> {
+    pub fn new(stream: &'a IBStream) -> Self {
+        Self { stream }
+    }
+    pub fn stream(&self) -> &'a IBStream {
+        self.stream
+    }
+}
+impl io::Read for StreamAdapter<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf).map_err(io::Error::other)
+    }
+}
+impl io::Write for StreamAdapter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf).map_err(io::Error::other)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+impl io::Seek for StreamAdapter<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (pos, mode) = match pos {
+            SeekFrom::Start(pos) => (
+                i64::try_from(pos).map_err(|_| io::ErrorKind::InvalidInput)?,
+                IStreamSeekMode::Set,
+            ),
+            SeekFrom::Current(pos) => (pos, IStreamSeekMode::Cur),
+            SeekFrom::End(pos) => (pos, IStreamSeekMode::End),
+        };
+        let position = self.stream.seek(pos, mode).map_err(io::Error::other)?;
+        u64::try_from(position).map_err(|_| io::ErrorKind::InvalidData.into())
+    }
+    fn stream_position(&mut self) -> io::Result<u64> {
+        let position = self.stream.tell().map_err(io::Error::other)?;
+        u64::try_from(position).map_err(|_| io::ErrorKind::InvalidData.into())
+    }
+}
+
+declare_class_iid!(
+    ISizeableStream,
+    0x04F9_549E,
+    0xE02F_4E6E,
+    0x87E8_6A87,
+    0x47F4_E17F
+);
+declare_interface!(ISizeableStream);
+interface_hierarchy!(ISizeableStream, FUnknown);
+impl ISizeableStream {
+    /// Returns the size of the stream in bytes
+    pub fn get_stream_size(&self) -> Result<i64, EInterface> {
+        unsafe { (self.vtable().get_stream_size)(transmute_copy(self)) }
+    }
+    /// Truncates or extends the stream to `size` bytes
+    pub fn set_stream_size(&self, size: i64) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_stream_size)(transmute_copy(self), size) }
+    }
+}
+declare_class_vtable!(
+    ISizeableStream,
+    base FUnknown,
+    function get_stream_size: () -> Result<i64, EInterface>,
+    function set_stream_size: (size: i64) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl ISizeableStreamVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: ISizeableStreamImpl,
+    {
+        unsafe fn get_stream_size<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> Result<i64, EInterface>
+        where
+            Identity: ISizeableStreamImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            ISizeableStreamImpl::get_stream_size(this)
+        }
+        unsafe fn set_stream_size<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            size: i64,
+        ) -> Result<(), EInterface>
+        where
+            Identity: ISizeableStreamImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            ISizeableStreamImpl::set_stream_size(this, size)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_stream_size: get_stream_size::<Identity, OFFSET>,
+            set_stream_size: set_stream_size::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<ISizeableStream as Interface>::iid
+    }
+}
@@ -8,13 +8,18 @@ use std::{
     ptr::NonNull,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EInterface {
     BadCast,
     BadQuery,
     Pointer,
 
     InitFailed,
+
+    ResultFalse,
+    InvalidArgument,
+    NotImplemented,
+    OutOfMemory,
 }
 impl fmt::Display for EInterface {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -23,6 +28,10 @@ impl fmt::Display for EInterface {
             EInterface::BadQuery => f.write_str("Bad interface query"),
             EInterface::Pointer => f.write_str("Tried to write interface to null-pointer"),
             EInterface::InitFailed => f.write_str("IPluginBase initialization failed"),
+            EInterface::ResultFalse => f.write_str("Call returned kResultFalse"),
+            EInterface::InvalidArgument => f.write_str("Invalid argument"),
+            EInterface::NotImplemented => f.write_str("Not implemented"),
+            EInterface::OutOfMemory => f.write_str("Out of memory"),
         }
     }
 }
@@ -139,6 +148,16 @@ impl<'a, I: Interface> InterfaceRef<'a, I> {
             Self(NonNull::new_unchecked(interface.as_raw()), PhantomData)
         }
     }
+    /// Creates an `InterfaceRef` from a raw `Interface` pointer without taking ownership,
+    /// returns `None` if `raw` is null.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be null or a valid `Interface` pointer which outlives `'a`.
+    #[inline(always)]
+    pub unsafe fn from_raw(raw: *mut c_void) -> Option<Self> {
+        NonNull::new(raw).map(|raw| Self(raw, PhantomData))
+    }
     /// Calls AddRef on the underlying `Interface` and returns an "owned" (counted) reference.
     pub fn to_owned(self) -> I {
         (*self).clone()
@@ -161,7 +180,9 @@ impl<'a, I: Interface> Deref for InterfaceRef<'a, I> {
 
 pub mod coreiids;
 pub mod funknown;
+pub mod ibstream;
 pub mod ipluginbase;
+pub mod ustring;
 
 #[allow(clippy::upper_case_acronyms)]
 #[repr(C)]
@@ -0,0 +1,90 @@
+//! UTF-16 string helpers, see `pluginterfaces/base/ustring.h`
+//!
+//! The SDK passes strings as zero terminated `char16` buffers of fixed size.
+//! These helpers convert between those buffers and Rust strings.
+
+/// 16 bit code unit of an UTF-16 string
+#[allow(non_camel_case_types)]
+pub type char16 = u16;
+
+/// Copies `src` into `dest` as a zero terminated UTF-16 string.
+///
+/// The string is truncated if it does not fit, the terminating zero is always written
+/// as long as `dest` is not empty. Returns the number of code units written, excluding the zero.
+pub fn copy_str_to_utf16(src: &str, dest: &mut [char16]) -> usize {
+    let Some(capacity) = dest.len().checked_sub(1) else {
+        return 0;
+    };
+    let mut len = 0;
+    for unit in src.encode_utf16().take(capacity) {
+        dest[len] = unit;
+        len += 1;
+    }
+    // Don't leave a dangling high surrogate behind when truncating
+    if len == capacity && len > 0 && (0xD800..0xDC00).contains(&dest[len - 1]) {
+        len -= 1;
+    }
+    dest[len] = 0;
+    len
+}
+
+/// Creates a zero terminated, fixed size UTF-16 buffer from `src`.
+pub fn to_utf16_array<const N: usize>(src: &str) -> [char16; N] {
+    let mut buffer = [0; N];
+    copy_str_to_utf16(src, &mut buffer);
+    buffer
+}
+
+/// Creates a zero terminated UTF-16 string from `src`
+pub fn to_utf16_vec(src: &str) -> Vec<char16> {
+    src.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Length of a zero terminated UTF-16 string stored in `src`,
+/// or the length of the slice if no terminator is present.
+pub fn utf16_len(src: &[char16]) -> usize {
+    src.iter().position(|&unit| unit == 0).unwrap_or(src.len())
+}
+
+/// Converts a zero terminated UTF-16 buffer to a `String`,
+/// replacing invalid code units with `U+FFFD`.
+pub fn string_from_utf16(src: &[char16]) -> String {
+    String::from_utf16_lossy(&src[..utf16_len(src)])
+}
+
+/// Converts a zero terminated UTF-16 string pointer to a `String`.
+///
+/// # Safety
+///
+/// `src` must be null or point to a valid, zero terminated UTF-16 string.
+pub unsafe fn string_from_utf16_ptr(src: *const char16) -> String {
+    if src.is_null() {
+        return String::new();
+    }
+    let mut len = 0;
+    while *src.add(len) != 0 {
+        len += 1;
+    }
+    String::from_utf16_lossy(std::slice::from_raw_parts(src, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let buffer: [char16; 128] = to_utf16_array("Gain \u{1F50A}");
+        assert_eq!(string_from_utf16(&buffer), "Gain \u{1F50A}");
+        let vec = to_utf16_vec("dB");
+        assert_eq!(unsafe { string_from_utf16_ptr(vec.as_ptr()) }, "dB");
+    }
+
+    #[test]
+    fn truncates_without_splitting_surrogates() {
+        let buffer: [char16; 3] = to_utf16_array("a\u{1F50A}");
+        assert_eq!(buffer, [u16::from(b'a'), 0, 0]);
+        let buffer: [char16; 3] = to_utf16_array("abc");
+        assert_eq!(string_from_utf16(&buffer), "ab");
+    }
+}
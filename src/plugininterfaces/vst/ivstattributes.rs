@@ -0,0 +1,270 @@
+//! Attribute interfaces, see `pluginterfaces/vst/ivstattributes.h`
+//!
+//! An [`IAttributeList`] stores values of a few basic types by [`AttrID`]. Streams
+//! passed to `setState` and `getState` may implement [`IStreamAttributes`] to tell
+//! the plug-in about the context of the state, see [`PresetAttributes`].
+//!
+//! [`PresetAttributes`]: super::vstpresetkeys::PresetAttributes
+use super::vsttypes::{string128_to_string, String128, TChar};
+use crate::plugininterfaces::base::{
+    macros::*,
+    pluginreexports::*,
+    ustring::{to_utf16_vec, utf16_len},
+    EInterface, Interface, InterfaceRef,
+};
+use std::{ffi::CStr, mem::transmute_copy, os::raw::c_void};
+
+/// Identifier of an attribute
+pub type AttrID<'a> = &'a CStr;
+
+declare_class_iid!(
+    IAttributeList,
+    0x1E5F_0AEB,
+    0xCC7F_4533,
+    0xA254_4011,
+    0x38AD_5EE4
+);
+declare_interface!(IAttributeList);
+interface_hierarchy!(IAttributeList, FUnknown);
+impl IAttributeList {
+    pub fn set_int(&self, id: AttrID, value: i64) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_int)(transmute_copy(self), id, value) }
+    }
+    pub fn get_int(&self, id: AttrID) -> Result<i64, EInterface> {
+        unsafe { (self.vtable().get_int)(transmute_copy(self), id) }
+    }
+    pub fn set_float(&self, id: AttrID, value: f64) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_float)(transmute_copy(self), id, value) }
+    }
+    pub fn get_float(&self, id: AttrID) -> Result<f64, EInterface> {
+        unsafe { (self.vtable().get_float)(transmute_copy(self), id) }
+    }
+    /// Stores `string` as a zero terminated UTF-16 string
+    pub fn set_string(&self, id: AttrID, string: &str) -> Result<(), EInterface> {
+        let string = to_utf16_vec(string);
+        unsafe { (self.vtable().set_string)(transmute_copy(self), id, string.as_ptr()) }
+    }
+    /// Copies a string into `string`, truncated and zero terminated
+    pub fn get_string(&self, id: AttrID, string: &mut [TChar]) -> Result<(), EInterface> {
+        let size_in_bytes = u32::try_from(std::mem::size_of_val(string)).unwrap_or(u32::MAX);
+        unsafe {
+            (self.vtable().get_string)(transmute_copy(self), id, string.as_mut_ptr(), size_in_bytes)
+        }
+    }
+    /// Reads a string of any length, converted from UTF-16
+    pub fn get_str(&self, id: AttrID) -> Result<String, EInterface> {
+        const MAX_LENGTH: usize = 1 << 20;
+        let mut buffer: Vec<TChar> = vec![0; 128];
+        loop {
+            buffer.fill(0);
+            self.get_string(id, &mut buffer)?;
+            let len = utf16_len(&buffer);
+            // A string filling the buffer may have been truncated
+            if len + 1 < buffer.len() || buffer.len() >= MAX_LENGTH {
+                return String::from_utf16(&buffer[..len]).map_err(|_| EInterface::InvalidArgument);
+            }
+            buffer.resize(buffer.len() * 2, 0);
+        }
+    }
+    pub fn set_binary(&self, id: AttrID, data: &[u8]) -> Result<(), EInterface> {
+        let size = u32::try_from(data.len()).map_err(|_| EInterface::InvalidArgument)?;
+        unsafe {
+            (self.vtable().set_binary)(
+                transmute_copy(self),
+                id,
+                data.as_ptr() as *const c_void,
+                size,
+            )
+        }
+    }
+    /// Returns the binary data of an attribute, valid until the attribute is changed
+    pub fn get_binary(&self, id: AttrID) -> Result<&[u8], EInterface> {
+        let (data, size) = unsafe { (self.vtable().get_binary)(transmute_copy(self), id) }?;
+        if data.is_null() {
+            return Ok(&[]);
+        }
+        Ok(unsafe { std::slice::from_raw_parts(data as *const u8, size as usize) })
+    }
+}
+declare_class_vtable!(
+    IAttributeList,
+    base FUnknown,
+    function set_int: (id: &CStr, value: i64) -> Result<(), EInterface>,
+    function get_int: (id: &CStr) -> Result<i64, EInterface>,
+    function set_float: (id: &CStr, value: f64) -> Result<(), EInterface>,
+    function get_float: (id: &CStr) -> Result<f64, EInterface>,
+    function set_string: (id: &CStr, string: *const TChar) -> Result<(), EInterface>,
+    function get_string: (id: &CStr, string: *mut TChar, size_in_bytes: u32) -> Result<(), EInterface>,
+    function set_binary: (id: &CStr, data: *const c_void, size: u32) -> Result<(), EInterface>,
+    function get_binary: (id: &CStr) -> Result<(*const c_void, u32), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IAttributeListVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IAttributeListImpl,
+    {
+        unsafe fn set_int<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: &CStr,
+            value: i64,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IAttributeListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IAttributeListImpl::set_int(this, id, value)
+        }
+        unsafe fn get_int<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: &CStr,
+        ) -> Result<i64, EInterface>
+        where
+            Identity: IAttributeListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IAttributeListImpl::get_int(this, id)
+        }
+        unsafe fn set_float<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: &CStr,
+            value: f64,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IAttributeListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IAttributeListImpl::set_float(this, id, value)
+        }
+        unsafe fn get_float<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: &CStr,
+        ) -> Result<f64, EInterface>
+        where
+            Identity: IAttributeListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IAttributeListImpl::get_float(this, id)
+        }
+        unsafe fn set_string<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: &CStr,
+            string: *const TChar,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IAttributeListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IAttributeListImpl::set_string(this, id, string)
+        }
+        unsafe fn get_string<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: &CStr,
+            string: *mut TChar,
+            size_in_bytes: u32,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IAttributeListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IAttributeListImpl::get_string(this, id, string, size_in_bytes)
+        }
+        unsafe fn set_binary<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: &CStr,
+            data: *const c_void,
+            size: u32,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IAttributeListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IAttributeListImpl::set_binary(this, id, data, size)
+        }
+        unsafe fn get_binary<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: &CStr,
+        ) -> Result<(*const c_void, u32), EInterface>
+        where
+            Identity: IAttributeListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IAttributeListImpl::get_binary(this, id)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            set_int: set_int::<Identity, OFFSET>,
+            get_int: get_int::<Identity, OFFSET>,
+            set_float: set_float::<Identity, OFFSET>,
+            get_float: get_float::<Identity, OFFSET>,
+            set_string: set_string::<Identity, OFFSET>,
+            get_string: get_string::<Identity, OFFSET>,
+            set_binary: set_binary::<Identity, OFFSET>,
+            get_binary: get_binary::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IAttributeList as Interface>::iid
+    }
+}
+
+declare_class_iid!(
+    IStreamAttributes,
+    0xD6CE_2FFC,
+    0xEFAF_4B8C,
+    0x9E74_F1BB,
+    0x12DA_44B4
+);
+declare_interface!(IStreamAttributes);
+interface_hierarchy!(IStreamAttributes, FUnknown);
+impl IStreamAttributes {
+    /// Returns the file name of the stream without extension
+    pub fn get_file_name(&self) -> Result<String, EInterface> {
+        unsafe { (self.vtable().get_file_name)(transmute_copy(self)) }
+            .map(|name| string128_to_string(&name))
+    }
+    /// Returns the attributes of the stream, owned by the stream
+    pub fn get_attributes(&self) -> Option<InterfaceRef<'_, IAttributeList>> {
+        unsafe { InterfaceRef::from_raw((self.vtable().get_attributes)(transmute_copy(self))) }
+    }
+}
+declare_class_vtable!(
+    IStreamAttributes,
+    base FUnknown,
+    function get_file_name: () -> Result<String128, EInterface>,
+    function get_attributes: () -> *mut c_void
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IStreamAttributesVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IStreamAttributesImpl,
+    {
+        unsafe fn get_file_name<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> Result<String128, EInterface>
+        where
+            Identity: IStreamAttributesImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IStreamAttributesImpl::get_file_name(this)
+        }
+        unsafe fn get_attributes<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> *mut c_void
+        where
+            Identity: IStreamAttributesImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IStreamAttributesImpl::get_attributes(this)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_file_name: get_file_name::<Identity, OFFSET>,
+            get_attributes: get_attributes::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IStreamAttributes as Interface>::iid
+    }
+}
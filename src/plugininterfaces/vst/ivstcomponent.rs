@@ -0,0 +1,358 @@
+//! Component interfaces, see `pluginterfaces/vst/ivstcomponent.h`
+use super::vsttypes::{string128_from_str, string128_to_string, String128};
+use crate::plugininterfaces::base::{
+    ibstream::IBStream, ipluginbase::IPluginBaseImpl, macros::*, pluginreexports::*, EInterface,
+    Interface, TUID,
+};
+use std::{mem::transmute_copy, ops, os::raw::c_void};
+
+/// Media type of a bus, one of [`MediaTypes`]
+pub type MediaType = i32;
+/// Direction of a bus, one of [`BusDirections`]
+pub type BusDirection = i32;
+
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MediaTypes {
+    /// Audio bus
+    Audio = 0,
+    /// Event bus
+    Event = 1,
+}
+
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BusDirections {
+    /// Input bus
+    Input = 0,
+    /// Output bus
+    Output = 1,
+}
+
+/// Type of a bus, one of [`BusTypes`]
+pub type BusType = i32;
+
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BusTypes {
+    /// Main bus
+    Main = 0,
+    /// Auxiliary bus, e.g. a side-chain
+    Aux = 1,
+}
+
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BusFlags {
+    NoFlags,
+
+    /// The bus should be activated by the host by default
+    DefaultActive = 1 << 0,
+
+    /// The bus carries control voltage instead of audio
+    IsControlVoltage = 1 << 1,
+}
+impl ops::BitOr<BusFlags> for u32 {
+    type Output = u32;
+    fn bitor(self, rhs: BusFlags) -> Self::Output {
+        self | rhs as u32
+    }
+}
+impl ops::BitOr for BusFlags {
+    type Output = u32;
+    fn bitor(self, rhs: BusFlags) -> Self::Output {
+        self as u32 | rhs as u32
+    }
+}
+
+/// Bus description
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct BusInfo {
+    /// Media type of the bus, one of [`MediaTypes`]
+    pub media_type: MediaType,
+    /// Direction of the bus, one of [`BusDirections`]
+    pub direction: BusDirection,
+    /// Number of channels, for event buses the number of MIDI channels
+    pub channel_count: i32,
+    /// Name of the bus
+    pub name: String128,
+    /// Main or auxiliary bus, one of [`BusTypes`]
+    pub bus_type: BusType,
+    /// Combination of [`BusFlags`]
+    pub flags: u32,
+}
+impl BusInfo {
+    pub fn new(
+        media_type: MediaTypes,
+        direction: BusDirections,
+        channel_count: i32,
+        name: &str,
+        bus_type: BusTypes,
+        flags: u32,
+    ) -> Self {
+        Self {
+            media_type: media_type as MediaType,
+            direction: direction as BusDirection,
+            channel_count,
+            name: string128_from_str(name),
+            bus_type: bus_type as BusType,
+            flags,
+        }
+    }
+    pub fn name(&self) -> String {
+        string128_to_string(&self.name)
+    }
+    pub fn set_name(&mut self, name: &str) {
+        self.name = string128_from_str(name);
+    }
+    pub fn has_flag(&self, flag: BusFlags) -> bool {
+        self.flags & flag as u32 != 0
+    }
+}
+impl Default for BusInfo {
+    fn default() -> Self {
+        Self {
+            media_type: MediaTypes::Audio as MediaType,
+            direction: BusDirections::Input as BusDirection,
+            channel_count: 0,
+            name: [0; 128],
+            bus_type: BusTypes::Main as BusType,
+            flags: BusFlags::NoFlags as u32,
+        }
+    }
+}
+
+/// I/O mode of a component, one of [`IoModes`]
+pub type IoMode = i32;
+
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IoModes {
+    /// 1:1 input/output, the default
+    Simple = 0,
+    /// Inputs and outputs are routed independently
+    Advanced = 1,
+    /// Offline processing, e.g. for bouncing
+    OfflineProcessing = 2,
+}
+
+/// Routing of an event bus channel to an output, see [`IComponent::get_routing_info`]
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RoutingInfo {
+    /// Media type of the bus, one of [`MediaTypes`]
+    pub media_type: MediaType,
+    /// Index of the bus
+    pub bus_index: i32,
+    /// Channel of the bus, -1 for all channels
+    pub channel: i32,
+}
+
+declare_class_iid!(
+    IComponent,
+    0xE831_FF31,
+    0xF2D5_4301,
+    0x928E_BBEE,
+    0x2569_7802
+);
+declare_interface!(IComponent);
+interface_hierarchy!(IComponent, IPluginBase, FUnknown);
+impl IComponent {
+    /// Returns the class ID of the edit controller of the component
+    pub fn get_controller_class_id(&self) -> Result<FUID, EInterface> {
+        unsafe { (self.vtable().get_controller_class_id)(transmute_copy(self)) }.map(FUID::from)
+    }
+    /// Sets the I/O mode before the component is activated, see [`IoModes`]
+    pub fn set_io_mode(&self, mode: IoModes) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_io_mode)(transmute_copy(self), mode as IoMode) }
+    }
+    pub fn get_bus_count(&self, type_: MediaTypes, dir: BusDirections) -> i32 {
+        unsafe {
+            (self.vtable().get_bus_count)(
+                transmute_copy(self),
+                type_ as MediaType,
+                dir as BusDirection,
+            )
+        }
+    }
+    pub fn get_bus_info(
+        &self,
+        type_: MediaTypes,
+        dir: BusDirections,
+        index: i32,
+    ) -> Result<BusInfo, EInterface> {
+        unsafe {
+            (self.vtable().get_bus_info)(
+                transmute_copy(self),
+                type_ as MediaType,
+                dir as BusDirection,
+                index,
+            )
+        }
+    }
+    /// Returns the output routed from the event bus channel `in_info`
+    pub fn get_routing_info(&self, in_info: &RoutingInfo) -> Result<RoutingInfo, EInterface> {
+        unsafe { (self.vtable().get_routing_info)(transmute_copy(self), in_info) }
+    }
+    pub fn activate_bus(
+        &self,
+        type_: MediaTypes,
+        dir: BusDirections,
+        index: i32,
+        state: bool,
+    ) -> Result<(), EInterface> {
+        unsafe {
+            (self.vtable().activate_bus)(
+                transmute_copy(self),
+                type_ as MediaType,
+                dir as BusDirection,
+                index,
+                state,
+            )
+        }
+    }
+    pub fn set_active(&self, state: bool) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_active)(transmute_copy(self), state) }
+    }
+    /// Sets the component state
+    pub fn set_state(&self, state: &IBStream) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_state)(transmute_copy(self), state) }
+    }
+    /// Gets the component state
+    pub fn get_state(&self, state: &IBStream) -> Result<(), EInterface> {
+        unsafe { (self.vtable().get_state)(transmute_copy(self), state) }
+    }
+}
+declare_class_vtable!(
+    IComponent,
+    base IPluginBase,
+    bound IPluginBase,
+    function get_controller_class_id: () -> Result<TUID, EInterface>,
+    function set_io_mode: (mode: IoMode) -> Result<(), EInterface>,
+    function get_bus_count: (type_: MediaType, dir: BusDirection) -> i32,
+    function get_bus_info: (type_: MediaType, dir: BusDirection, index: i32) -> Result<BusInfo, EInterface>,
+    function get_routing_info: (in_info: &RoutingInfo) -> Result<RoutingInfo, EInterface>,
+    function activate_bus: (type_: MediaType, dir: BusDirection, index: i32, state: bool) -> Result<(), EInterface>,
+    function set_active: (state: bool) -> Result<(), EInterface>,
+    function set_state: (state: &IBStream) -> Result<(), EInterface>,
+    function get_state: (state: &IBStream) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IComponentVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IComponentImpl,
+    {
+        unsafe fn get_controller_class_id<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> Result<TUID, EInterface>
+        where
+            Identity: IComponentImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentImpl::get_controller_class_id(this)
+        }
+        unsafe fn set_io_mode<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            mode: IoMode,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IComponentImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentImpl::set_io_mode(this, mode)
+        }
+        unsafe fn get_bus_count<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            type_: MediaType,
+            dir: BusDirection,
+        ) -> i32
+        where
+            Identity: IComponentImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentImpl::get_bus_count(this, type_, dir)
+        }
+        unsafe fn get_bus_info<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            type_: MediaType,
+            dir: BusDirection,
+            index: i32,
+        ) -> Result<BusInfo, EInterface>
+        where
+            Identity: IComponentImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentImpl::get_bus_info(this, type_, dir, index)
+        }
+        unsafe fn get_routing_info<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            in_info: &RoutingInfo,
+        ) -> Result<RoutingInfo, EInterface>
+        where
+            Identity: IComponentImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentImpl::get_routing_info(this, in_info)
+        }
+        unsafe fn activate_bus<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            type_: MediaType,
+            dir: BusDirection,
+            index: i32,
+            state: bool,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IComponentImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentImpl::activate_bus(this, type_, dir, index, state)
+        }
+        unsafe fn set_active<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            state: bool,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IComponentImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentImpl::set_active(this, state)
+        }
+        unsafe fn set_state<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            state: &IBStream,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IComponentImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentImpl::set_state(this, state)
+        }
+        unsafe fn get_state<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            state: &IBStream,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IComponentImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentImpl::get_state(this, state)
+        }
+        Self {
+            base: IPluginBaseVtable::new::<Identity, OFFSET>(),
+            get_controller_class_id: get_controller_class_id::<Identity, OFFSET>,
+            set_io_mode: set_io_mode::<Identity, OFFSET>,
+            get_bus_count: get_bus_count::<Identity, OFFSET>,
+            get_bus_info: get_bus_info::<Identity, OFFSET>,
+            get_routing_info: get_routing_info::<Identity, OFFSET>,
+            activate_bus: activate_bus::<Identity, OFFSET>,
+            set_active: set_active::<Identity, OFFSET>,
+            set_state: set_state::<Identity, OFFSET>,
+            get_state: get_state::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IComponent as Interface>::iid || iid == &<IPluginBase as Interface>::iid
+    }
+}
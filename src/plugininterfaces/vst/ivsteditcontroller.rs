@@ -0,0 +1,542 @@
+//! Edit controller interfaces, see `pluginterfaces/vst/ivsteditcontroller.h`
+use super::vsttypes::{
+    kRootUnitId, string128_from_str, string128_to_string, ParamID, ParamValue, String128, TChar,
+    UnitID,
+};
+use crate::plugininterfaces::base::{
+    ibstream::IBStream, ipluginbase::IPluginBaseImpl, macros::*, pluginreexports::*,
+    ustring::to_utf16_vec, EInterface, Interface,
+};
+use std::{ffi::CStr, mem::transmute_copy, ops, os::raw::c_void};
+
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParameterFlags {
+    NoFlags,
+
+    /// Parameter can be automated
+    CanAutomate = 1 << 0,
+
+    /// Parameter cannot be changed from outside the plug-in
+    IsReadOnly = 1 << 1,
+
+    /// Attempts to set the parameter past its limits wrap around
+    IsWrapAround = 1 << 2,
+
+    /// Parameter should be displayed as a list in generic editors
+    IsList = 1 << 3,
+
+    /// Parameter should not be displayed and cannot be changed from outside the plug-in
+    IsHidden = 1 << 4,
+
+    /// Parameter is a program change, its unit should be a program list
+    IsProgramChange = 1 << 15,
+
+    /// Special bypass parameter, at most one per plug-in
+    IsBypass = 1 << 16,
+}
+impl ops::BitOr<ParameterFlags> for i32 {
+    type Output = i32;
+    fn bitor(self, rhs: ParameterFlags) -> Self::Output {
+        self | rhs as i32
+    }
+}
+impl ops::BitOr for ParameterFlags {
+    type Output = i32;
+    fn bitor(self, rhs: ParameterFlags) -> Self::Output {
+        self as i32 | rhs as i32
+    }
+}
+impl ops::BitOrAssign<ParameterFlags> for i32 {
+    fn bitor_assign(&mut self, rhs: ParameterFlags) {
+        *self = *self | rhs;
+    }
+}
+
+/// Converts a discrete value to its normalized representation
+pub fn to_normalized(discrete_value: f64, step_count: i32) -> ParamValue {
+    if step_count <= 0 {
+        return discrete_value;
+    }
+    discrete_value / step_count as f64
+}
+
+/// Converts a normalized value to a discrete value in `0..=step_count`
+pub fn from_normalized(value_normalized: ParamValue, step_count: i32) -> i32 {
+    (value_normalized * (step_count as f64 + 1.0)).min(step_count as f64) as i32
+}
+
+/// Controller parameter info
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ParameterInfo {
+    /// Unique identifier of this parameter
+    pub id: ParamID,
+    /// Parameter title (e.g. "Volume")
+    pub title: String128,
+    /// Parameter short title (e.g. "Vol")
+    pub short_title: String128,
+    /// Parameter unit (e.g. "dB")
+    pub units: String128,
+    /// Number of discrete steps, 0 for a continuous parameter
+    pub step_count: i32,
+    /// Default normalized value [0, 1]
+    pub default_normalized_value: ParamValue,
+    /// Identifier of the unit this parameter belongs to
+    pub unit_id: UnitID,
+    /// Combination of [`ParameterFlags`]
+    pub flags: i32,
+}
+impl ParameterInfo {
+    pub fn new(id: ParamID, title: &str, units: &str) -> Self {
+        Self {
+            id,
+            title: string128_from_str(title),
+            units: string128_from_str(units),
+            ..Default::default()
+        }
+    }
+    pub fn title(&self) -> String {
+        string128_to_string(&self.title)
+    }
+    pub fn set_title(&mut self, title: &str) {
+        self.title = string128_from_str(title);
+    }
+    pub fn short_title(&self) -> String {
+        string128_to_string(&self.short_title)
+    }
+    pub fn set_short_title(&mut self, short_title: &str) {
+        self.short_title = string128_from_str(short_title);
+    }
+    pub fn units(&self) -> String {
+        string128_to_string(&self.units)
+    }
+    pub fn set_units(&mut self, units: &str) {
+        self.units = string128_from_str(units);
+    }
+    pub fn has_flag(&self, flag: ParameterFlags) -> bool {
+        self.flags & flag as i32 != 0
+    }
+}
+impl Default for ParameterInfo {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            title: [0; 128],
+            short_title: [0; 128],
+            units: [0; 128],
+            step_count: 0,
+            default_normalized_value: 0.0,
+            unit_id: kRootUnitId,
+            flags: ParameterFlags::NoFlags as i32,
+        }
+    }
+}
+
+/// Flags used by [`IComponentHandler::restart_component`]
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RestartFlags {
+    /// The component should be reloaded
+    ReloadComponent = 1 << 0,
+    /// Input/output bus configuration has changed
+    IoChanged = 1 << 1,
+    /// Multiple parameter values have changed
+    ParamValuesChanged = 1 << 2,
+    /// Latency has changed
+    LatencyChanged = 1 << 3,
+    /// Parameter titles, default values or flags have changed
+    ParamTitlesChanged = 1 << 4,
+    /// MIDI controller assignments have changed
+    MidiCCAssignmentChanged = 1 << 5,
+    /// Note expression has changed
+    NoteExpressionChanged = 1 << 6,
+    /// Input/output bus titles have changed
+    IoTitlesChanged = 1 << 7,
+    /// Prefetch support has changed
+    PrefetchableSupportChanged = 1 << 8,
+    /// Routing info has changed
+    RoutingInfoChanged = 1 << 9,
+    /// Key switches have changed
+    KeyswitchChanged = 1 << 10,
+    /// Parameter IDs have changed
+    ParamIDMappingChanged = 1 << 11,
+}
+impl ops::BitOr<RestartFlags> for i32 {
+    type Output = i32;
+    fn bitor(self, rhs: RestartFlags) -> Self::Output {
+        self | rhs as i32
+    }
+}
+
+declare_class_iid!(
+    IComponentHandler,
+    0x93A0_BEA3,
+    0x0BD0_45DB,
+    0x8E89_0B0C,
+    0xC1E4_6AC6
+);
+declare_interface!(IComponentHandler);
+interface_hierarchy!(IComponentHandler, FUnknown);
+impl IComponentHandler {
+    /// Called before a [`IComponentHandler::perform_edit`], e.g. on mouse-click-down event
+    pub fn begin_edit(&self, id: ParamID) -> Result<(), EInterface> {
+        unsafe { (self.vtable().begin_edit)(transmute_copy(self), id) }
+    }
+    /// Called between [`IComponentHandler::begin_edit`] and [`IComponentHandler::end_edit`]
+    /// to inform the host about a new normalized value
+    pub fn perform_edit(
+        &self,
+        id: ParamID,
+        value_normalized: ParamValue,
+    ) -> Result<(), EInterface> {
+        unsafe { (self.vtable().perform_edit)(transmute_copy(self), id, value_normalized) }
+    }
+    /// Called after a [`IComponentHandler::perform_edit`], e.g. on mouse-click-up event
+    pub fn end_edit(&self, id: ParamID) -> Result<(), EInterface> {
+        unsafe { (self.vtable().end_edit)(transmute_copy(self), id) }
+    }
+    /// Instructs the host to restart the component, `flags` is a combination of [`RestartFlags`]
+    pub fn restart_component(&self, flags: i32) -> Result<(), EInterface> {
+        unsafe { (self.vtable().restart_component)(transmute_copy(self), flags) }
+    }
+}
+declare_class_vtable!(
+    IComponentHandler,
+    base FUnknown,
+    function begin_edit: (id: ParamID) -> Result<(), EInterface>,
+    function perform_edit: (id: ParamID, value_normalized: ParamValue) -> Result<(), EInterface>,
+    function end_edit: (id: ParamID) -> Result<(), EInterface>,
+    function restart_component: (flags: i32) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IComponentHandlerVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IComponentHandlerImpl,
+    {
+        unsafe fn begin_edit<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: ParamID,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IComponentHandlerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentHandlerImpl::begin_edit(this, id)
+        }
+        unsafe fn perform_edit<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: ParamID,
+            value_normalized: ParamValue,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IComponentHandlerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentHandlerImpl::perform_edit(this, id, value_normalized)
+        }
+        unsafe fn end_edit<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: ParamID,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IComponentHandlerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentHandlerImpl::end_edit(this, id)
+        }
+        unsafe fn restart_component<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            flags: i32,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IComponentHandlerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IComponentHandlerImpl::restart_component(this, flags)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            begin_edit: begin_edit::<Identity, OFFSET>,
+            perform_edit: perform_edit::<Identity, OFFSET>,
+            end_edit: end_edit::<Identity, OFFSET>,
+            restart_component: restart_component::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IComponentHandler as Interface>::iid
+    }
+}
+
+/// Name of the editor view passed to [`IEditController::create_view`]
+pub struct ViewType;
+impl ViewType {
+    pub const EDITOR: &'static CStr = c"editor";
+}
+
+declare_class_iid!(
+    IEditController,
+    0xDCD7_BBE3,
+    0x7742_448D,
+    0xA874_AACC,
+    0x979C_759E
+);
+declare_interface!(IEditController);
+interface_hierarchy!(IEditController, IPluginBase, FUnknown);
+impl IEditController {
+    /// Receives the component state
+    pub fn set_component_state(&self, state: &IBStream) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_component_state)(transmute_copy(self), state) }
+    }
+    /// Sets the controller state
+    pub fn set_state(&self, state: &IBStream) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_state)(transmute_copy(self), state) }
+    }
+    /// Gets the controller state
+    pub fn get_state(&self, state: &IBStream) -> Result<(), EInterface> {
+        unsafe { (self.vtable().get_state)(transmute_copy(self), state) }
+    }
+    /// Returns the number of parameters exported
+    pub fn get_parameter_count(&self) -> i32 {
+        unsafe { (self.vtable().get_parameter_count)(transmute_copy(self)) }
+    }
+    /// Gets the info of the parameter at `param_index`
+    pub fn get_parameter_info(&self, param_index: i32) -> Result<ParameterInfo, EInterface> {
+        unsafe { (self.vtable().get_parameter_info)(transmute_copy(self), param_index) }
+    }
+    /// Gets the string representation of a normalized parameter value
+    pub fn get_param_string_by_value(
+        &self,
+        id: ParamID,
+        value_normalized: ParamValue,
+    ) -> Result<String, EInterface> {
+        let string = unsafe {
+            (self.vtable().get_param_string_by_value)(transmute_copy(self), id, value_normalized)
+        }?;
+        Ok(string128_to_string(&string))
+    }
+    /// Parses `string` into a normalized parameter value
+    pub fn get_param_value_by_string(
+        &self,
+        id: ParamID,
+        string: &str,
+    ) -> Result<ParamValue, EInterface> {
+        let string = to_utf16_vec(string);
+        unsafe {
+            (self.vtable().get_param_value_by_string)(transmute_copy(self), id, string.as_ptr())
+        }
+    }
+    /// Converts a normalized value to its plain representation (e.g. 90 for 90db)
+    pub fn normalized_param_to_plain(
+        &self,
+        id: ParamID,
+        value_normalized: ParamValue,
+    ) -> ParamValue {
+        unsafe {
+            (self.vtable().normalized_param_to_plain)(transmute_copy(self), id, value_normalized)
+        }
+    }
+    /// Converts a plain value to its normalized representation
+    pub fn plain_param_to_normalized(&self, id: ParamID, plain_value: ParamValue) -> ParamValue {
+        unsafe { (self.vtable().plain_param_to_normalized)(transmute_copy(self), id, plain_value) }
+    }
+    /// Returns the normalized value of a parameter
+    pub fn get_param_normalized(&self, id: ParamID) -> ParamValue {
+        unsafe { (self.vtable().get_param_normalized)(transmute_copy(self), id) }
+    }
+    /// Sets the normalized value of a parameter
+    pub fn set_param_normalized(&self, id: ParamID, value: ParamValue) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_param_normalized)(transmute_copy(self), id, value) }
+    }
+    /// Gets from the host a handler which allows the plug-in to communicate parameter changes
+    pub fn set_component_handler(
+        &self,
+        handler: Option<&IComponentHandler>,
+    ) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_component_handler)(transmute_copy(self), handler) }
+    }
+    /// Creates the editor view of the plug-in, see [`ViewType`]
+    pub fn create_view(&self, name: &CStr) -> Option<FUnknown> {
+        unsafe { (self.vtable().create_view)(transmute_copy(self), name) }
+    }
+}
+declare_class_vtable!(
+    IEditController,
+    base IPluginBase,
+    bound IPluginBase,
+    function set_component_state: (state: &IBStream) -> Result<(), EInterface>,
+    function set_state: (state: &IBStream) -> Result<(), EInterface>,
+    function get_state: (state: &IBStream) -> Result<(), EInterface>,
+    function get_parameter_count: () -> i32,
+    function get_parameter_info: (param_index: i32) -> Result<ParameterInfo, EInterface>,
+    function get_param_string_by_value: (id: ParamID, value_normalized: ParamValue) -> Result<String128, EInterface>,
+    function get_param_value_by_string: (id: ParamID, string: *const TChar) -> Result<ParamValue, EInterface>,
+    function normalized_param_to_plain: (id: ParamID, value_normalized: ParamValue) -> ParamValue,
+    function plain_param_to_normalized: (id: ParamID, plain_value: ParamValue) -> ParamValue,
+    function get_param_normalized: (id: ParamID) -> ParamValue,
+    function set_param_normalized: (id: ParamID, value: ParamValue) -> Result<(), EInterface>,
+    function set_component_handler: (handler: Option<&IComponentHandler>) -> Result<(), EInterface>,
+    function create_view: (name: &CStr) -> Option<FUnknown>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IEditControllerVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IEditControllerImpl,
+    {
+        unsafe fn set_component_state<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            state: &IBStream,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::set_component_state(this, state)
+        }
+        unsafe fn set_state<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            state: &IBStream,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::set_state(this, state)
+        }
+        unsafe fn get_state<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            state: &IBStream,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::get_state(this, state)
+        }
+        unsafe fn get_parameter_count<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> i32
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::get_parameter_count(this)
+        }
+        unsafe fn get_parameter_info<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            param_index: i32,
+        ) -> Result<ParameterInfo, EInterface>
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::get_parameter_info(this, param_index)
+        }
+        unsafe fn get_param_string_by_value<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: ParamID,
+            value_normalized: ParamValue,
+        ) -> Result<String128, EInterface>
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::get_param_string_by_value(this, id, value_normalized)
+        }
+        unsafe fn get_param_value_by_string<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: ParamID,
+            string: *const TChar,
+        ) -> Result<ParamValue, EInterface>
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::get_param_value_by_string(this, id, string)
+        }
+        unsafe fn normalized_param_to_plain<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: ParamID,
+            value_normalized: ParamValue,
+        ) -> ParamValue
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::normalized_param_to_plain(this, id, value_normalized)
+        }
+        unsafe fn plain_param_to_normalized<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: ParamID,
+            plain_value: ParamValue,
+        ) -> ParamValue
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::plain_param_to_normalized(this, id, plain_value)
+        }
+        unsafe fn get_param_normalized<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: ParamID,
+        ) -> ParamValue
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::get_param_normalized(this, id)
+        }
+        unsafe fn set_param_normalized<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: ParamID,
+            value: ParamValue,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::set_param_normalized(this, id, value)
+        }
+        unsafe fn set_component_handler<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            handler: Option<&IComponentHandler>,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::set_component_handler(this, handler)
+        }
+        unsafe fn create_view<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            name: &CStr,
+        ) -> Option<FUnknown>
+        where
+            Identity: IEditControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEditControllerImpl::create_view(this, name)
+        }
+        Self {
+            base: IPluginBaseVtable::new::<Identity, OFFSET>(),
+            set_component_state: set_component_state::<Identity, OFFSET>,
+            set_state: set_state::<Identity, OFFSET>,
+            get_state: get_state::<Identity, OFFSET>,
+            get_parameter_count: get_parameter_count::<Identity, OFFSET>,
+            get_parameter_info: get_parameter_info::<Identity, OFFSET>,
+            get_param_string_by_value: get_param_string_by_value::<Identity, OFFSET>,
+            get_param_value_by_string: get_param_value_by_string::<Identity, OFFSET>,
+            normalized_param_to_plain: normalized_param_to_plain::<Identity, OFFSET>,
+            plain_param_to_normalized: plain_param_to_normalized::<Identity, OFFSET>,
+            get_param_normalized: get_param_normalized::<Identity, OFFSET>,
+            set_param_normalized: set_param_normalized::<Identity, OFFSET>,
+            set_component_handler: set_component_handler::<Identity, OFFSET>,
+            create_view: create_view::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IEditController as Interface>::iid || iid == &<IPluginBase as Interface>::iid
+    }
+}
@@ -0,0 +1,644 @@
+//! Event interfaces, see `pluginterfaces/vst/ivstevents.h`
+//!
+//! [`Event`] is the `#[repr(C)]` binding of the SDK's tagged union, [`EventKind`]
+//! is its safe counterpart. Converting between both is lossless: `Event::new` /
+//! [`Event::set_kind`] store an [`EventKind`], [`Event::kind`] reads it back.
+use super::{
+    ivstnoteexpression::{NoteExpressionTextEvent, NoteExpressionTypeID, NoteExpressionValueEvent},
+    vsttypes::{TChar, TQuarterNotes},
+};
+use crate::plugininterfaces::base::{macros::*, pluginreexports::*, EInterface, Interface};
+use std::{iter::FusedIterator, mem::transmute_copy, ops, os::raw::c_void};
+
+/// Note-on event specific data
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NoteOnEvent {
+    /// Channel index in event bus
+    pub channel: i16,
+    /// Range [0, 127] = [C-2, G8] with A3=440Hz (12-TET)
+    pub pitch: i16,
+    /// Offset from the pitch in cents, 1.0 = +1 cent
+    pub tuning: f32,
+    /// Range [0.0, 1.0]
+    pub velocity: f32,
+    /// In sample frames, 0 if unknown
+    pub length: i32,
+    /// Note identifier, -1 if not available
+    pub note_id: i32,
+}
+
+/// Note-off event specific data
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NoteOffEvent {
+    /// Channel index in event bus
+    pub channel: i16,
+    /// Range [0, 127] = [C-2, G8] with A3=440Hz (12-TET)
+    pub pitch: i16,
+    /// Range [0.0, 1.0]
+    pub velocity: f32,
+    /// Identifier of the note to release, -1 if not available
+    pub note_id: i32,
+    /// Offset from the pitch in cents, 1.0 = +1 cent
+    pub tuning: f32,
+}
+
+/// Data event specific data, the data is only valid during the processing call
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DataEvent {
+    /// Size in bytes of `bytes`
+    pub size: u32,
+    /// One of [`DataTypes`]
+    pub type_: u32,
+    /// Pointer to the data
+    pub bytes: *const u8,
+}
+
+/// Types of [`DataEvent`]
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DataTypes {
+    /// MIDI system exclusive message
+    MidiSysEx = 0,
+}
+
+/// Polyphonic pressure event specific data
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PolyPressureEvent {
+    /// Channel index in event bus
+    pub channel: i16,
+    /// Range [0, 127] = [C-2, G8] with A3=440Hz
+    pub pitch: i16,
+    /// Range [0.0, 1.0]
+    pub pressure: f32,
+    /// Identifier of the note this event applies to, -1 if not available
+    pub note_id: i32,
+}
+
+/// Chord event specific data
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChordEvent {
+    /// Range [0, 127] = [C-2, G8] with A3=440Hz
+    pub root: i16,
+    /// Range [0, 127] = [C-2, G8] with A3=440Hz
+    pub bass_note: i16,
+    /// Root is bit 0
+    pub mask: i16,
+    /// Number of characters of `text`, excluding the terminating zero
+    pub text_len: u16,
+    /// Zero terminated UTF-16 name of the chord
+    pub text: *const TChar,
+}
+
+/// Scale event specific data
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScaleEvent {
+    /// Range [0, 127] = root note/transpose factor
+    pub root: i16,
+    /// Bit 0 = C, bit 1 = C#, ... (0x5ab5 = major scale)
+    pub mask: i16,
+    /// Number of characters of `text`, excluding the terminating zero
+    pub text_len: u16,
+    /// Zero terminated UTF-16 name of the scale
+    pub text: *const TChar,
+}
+
+/// Legacy MIDI CC out event, only used by plug-ins to send MIDI controllers to the host
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LegacyMIDICCOutEvent {
+    /// See `ControllerNumbers` [0, 255]
+    pub control_number: u8,
+    /// Channel index in event bus [0, 15]
+    pub channel: i8,
+    /// Value of the controller [0, 127]
+    pub value: i8,
+    /// Second value, used by pitch bend (MSB) and poly pressure (pressure)
+    pub value2: i8,
+}
+
+/// Event flags, combined in [`Event::flags`]
+#[repr(u16)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EventFlags {
+    /// The event is played live, directly from a keyboard
+    IsLive = 1 << 0,
+    /// Reserved for user (for internal use)
+    UserReserved1 = 1 << 14,
+    /// Reserved for user (for internal use)
+    UserReserved2 = 1 << 15,
+}
+impl ops::BitOr<EventFlags> for u16 {
+    type Output = u16;
+    fn bitor(self, rhs: EventFlags) -> Self::Output {
+        self | rhs as u16
+    }
+}
+impl ops::BitOr for EventFlags {
+    type Output = u16;
+    fn bitor(self, rhs: EventFlags) -> Self::Output {
+        self as u16 | rhs as u16
+    }
+}
+
+/// Event types, stored in [`Event::type_`]
+#[repr(u16)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EventTypes {
+    NoteOnEvent = 0,
+    NoteOffEvent = 1,
+    DataEvent = 2,
+    PolyPressureEvent = 3,
+    NoteExpressionValueEvent = 4,
+    NoteExpressionTextEvent = 5,
+    ChordEvent = 6,
+    ScaleEvent = 7,
+    LegacyMIDICCOutEvent = 65535,
+}
+impl EventTypes {
+    pub fn from_raw(type_: u16) -> Option<Self> {
+        Some(match type_ {
+            0 => Self::NoteOnEvent,
+            1 => Self::NoteOffEvent,
+            2 => Self::DataEvent,
+            3 => Self::PolyPressureEvent,
+            4 => Self::NoteExpressionValueEvent,
+            5 => Self::NoteExpressionTextEvent,
+            6 => Self::ChordEvent,
+            7 => Self::ScaleEvent,
+            65535 => Self::LegacyMIDICCOutEvent,
+            _ => return None,
+        })
+    }
+}
+
+/// Type specific data of an [`Event`], selected by [`Event::type_`]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union EventData {
+    pub note_on: NoteOnEvent,
+    pub note_off: NoteOffEvent,
+    pub data: DataEvent,
+    pub poly_pressure: PolyPressureEvent,
+    pub note_expression_value: NoteExpressionValueEvent,
+    pub note_expression_text: NoteExpressionTextEvent,
+    pub chord: ChordEvent,
+    pub scale: ScaleEvent,
+    pub midi_cc_out: LegacyMIDICCOutEvent,
+}
+
+/// Event, passed through an [`IEventList`]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Event {
+    /// Event bus index
+    pub bus_index: i32,
+    /// Sample frames related to the current block start sample position
+    pub sample_offset: i32,
+    /// Position in project
+    pub ppq_position: TQuarterNotes,
+    /// Combination of [`EventFlags`]
+    pub flags: u16,
+    /// One of [`EventTypes`]
+    pub type_: u16,
+    pub data: EventData,
+}
+impl Event {
+    /// Creates an event on `bus_index` at `sample_offset`, referencing the payload of `kind`
+    pub fn new(bus_index: i32, sample_offset: i32, kind: EventKind<'_>) -> Self {
+        let mut event = Self {
+            bus_index,
+            sample_offset,
+            ppq_position: 0.0,
+            flags: 0,
+            // Zeroes the whole union, `NoteExpressionTextEvent` is its largest member
+            type_: EventTypes::NoteExpressionTextEvent as u16,
+            data: EventData {
+                note_expression_text: NoteExpressionTextEvent {
+                    type_id: 0,
+                    note_id: 0,
+                    text_len: 0,
+                    text: std::ptr::null(),
+                },
+            },
+        };
+        event.set_kind(kind);
+        event
+    }
+    /// Replaces type and data of the event, text and data of `kind` are referenced, not copied
+    pub fn set_kind(&mut self, kind: EventKind<'_>) {
+        let (type_, data) = match kind {
+            EventKind::NoteOn(note_on) => (EventTypes::NoteOnEvent, EventData { note_on }),
+            EventKind::NoteOff(note_off) => (EventTypes::NoteOffEvent, EventData { note_off }),
+            EventKind::Data { type_, bytes } => (
+                EventTypes::DataEvent,
+                EventData {
+                    data: DataEvent {
+                        size: bytes.len() as u32,
+                        type_,
+                        bytes: bytes.as_ptr(),
+                    },
+                },
+            ),
+            EventKind::PolyPressure(poly_pressure) => {
+                (EventTypes::PolyPressureEvent, EventData { poly_pressure })
+            }
+            EventKind::NoteExpressionValue(note_expression_value) => (
+                EventTypes::NoteExpressionValueEvent,
+                EventData {
+                    note_expression_value,
+                },
+            ),
+            EventKind::NoteExpressionText {
+                type_id,
+                note_id,
+                text,
+            } => (
+                EventTypes::NoteExpressionTextEvent,
+                EventData {
+                    note_expression_text: NoteExpressionTextEvent {
+                        type_id,
+                        note_id,
+                        text_len: text.len() as u32,
+                        text: text.as_ptr(),
+                    },
+                },
+            ),
+            EventKind::Chord {
+                root,
+                bass_note,
+                mask,
+                text,
+            } => (
+                EventTypes::ChordEvent,
+                EventData {
+                    chord: ChordEvent {
+                        root,
+                        bass_note,
+                        mask,
+                        text_len: text.len() as u16,
+                        text: text.as_ptr(),
+                    },
+                },
+            ),
+            EventKind::Scale { root, mask, text } => (
+                EventTypes::ScaleEvent,
+                EventData {
+                    scale: ScaleEvent {
+                        root,
+                        mask,
+                        text_len: text.len() as u16,
+                        text: text.as_ptr(),
+                    },
+                },
+            ),
+            EventKind::LegacyMIDICCOut(midi_cc_out) => {
+                (EventTypes::LegacyMIDICCOutEvent, EventData { midi_cc_out })
+            }
+        };
+        self.type_ = type_ as u16;
+        self.data = data;
+    }
+    /// Returns the type of the event, `None` for types unknown to this SDK version
+    pub fn event_type(&self) -> Option<EventTypes> {
+        EventTypes::from_raw(self.type_)
+    }
+    pub fn has_flag(&self, flag: EventFlags) -> bool {
+        self.flags & flag as u16 != 0
+    }
+    /// Reads the type specific data, `None` for types unknown to this SDK version
+    ///
+    /// # Safety
+    ///
+    /// The data and text pointers of the event must be valid for the lifetime of the borrow,
+    /// which is the case for events of an [`IEventList`] during the processing call.
+    pub unsafe fn kind(&self) -> Option<EventKind<'_>> {
+        self.kind_unbounded()
+    }
+    /// Same as [`Event::kind`], the lifetime is chosen by the caller
+    unsafe fn kind_unbounded<'a>(&self) -> Option<EventKind<'a>> {
+        Some(match self.event_type()? {
+            EventTypes::NoteOnEvent => EventKind::NoteOn(self.data.note_on),
+            EventTypes::NoteOffEvent => EventKind::NoteOff(self.data.note_off),
+            EventTypes::DataEvent => EventKind::Data {
+                type_: self.data.data.type_,
+                bytes: slice_from_raw(self.data.data.bytes, self.data.data.size as usize),
+            },
+            EventTypes::PolyPressureEvent => EventKind::PolyPressure(self.data.poly_pressure),
+            EventTypes::NoteExpressionValueEvent => {
+                EventKind::NoteExpressionValue(self.data.note_expression_value)
+            }
+            EventTypes::NoteExpressionTextEvent => {
+                let text_event = self.data.note_expression_text;
+                EventKind::NoteExpressionText {
+                    type_id: text_event.type_id,
+                    note_id: text_event.note_id,
+                    text: slice_from_raw(text_event.text, text_event.text_len as usize),
+                }
+            }
+            EventTypes::ChordEvent => {
+                let chord = self.data.chord;
+                EventKind::Chord {
+                    root: chord.root,
+                    bass_note: chord.bass_note,
+                    mask: chord.mask,
+                    text: slice_from_raw(chord.text, chord.text_len as usize),
+                }
+            }
+            EventTypes::ScaleEvent => {
+                let scale = self.data.scale;
+                EventKind::Scale {
+                    root: scale.root,
+                    mask: scale.mask,
+                    text: slice_from_raw(scale.text, scale.text_len as usize),
+                }
+            }
+            EventTypes::LegacyMIDICCOutEvent => EventKind::LegacyMIDICCOut(self.data.midi_cc_out),
+        })
+    }
+}
+
+unsafe fn slice_from_raw<'a, T>(data: *const T, len: usize) -> &'a [T] {
+    if data.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    }
+}
+
+/// Safe view of the type specific data of an [`Event`]
+///
+/// Texts are UTF-16 without the terminating zero. When an [`Event`] is created
+/// from a text, the slice should be followed by a zero in memory since
+/// plug-ins may rely on it, the host [`EventList`] takes care of this.
+///
+/// [`EventList`]: crate::publicsdk::vst::hosting::eventlist::EventList
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EventKind<'a> {
+    NoteOn(NoteOnEvent),
+    NoteOff(NoteOffEvent),
+    /// Data event, `type_` is one of [`DataTypes`]
+    Data {
+        type_: u32,
+        bytes: &'a [u8],
+    },
+    PolyPressure(PolyPressureEvent),
+    NoteExpressionValue(NoteExpressionValueEvent),
+    NoteExpressionText {
+        type_id: NoteExpressionTypeID,
+        note_id: i32,
+        text: &'a [TChar],
+    },
+    Chord {
+        root: i16,
+        bass_note: i16,
+        mask: i16,
+        text: &'a [TChar],
+    },
+    Scale {
+        root: i16,
+        mask: i16,
+        text: &'a [TChar],
+    },
+    LegacyMIDICCOut(LegacyMIDICCOutEvent),
+}
+impl<'a> EventKind<'a> {
+    /// System exclusive data event
+    pub fn sysex(bytes: &'a [u8]) -> Self {
+        EventKind::Data {
+            type_: DataTypes::MidiSysEx as u32,
+            bytes,
+        }
+    }
+    /// Identifier of the note the event applies to, -1 if not available or not a note event
+    pub fn note_id(&self) -> i32 {
+        match self {
+            EventKind::NoteOn(event) => event.note_id,
+            EventKind::NoteOff(event) => event.note_id,
+            EventKind::PolyPressure(event) => event.note_id,
+            EventKind::NoteExpressionValue(event) => event.note_id,
+            EventKind::NoteExpressionText { note_id, .. } => *note_id,
+            _ => -1,
+        }
+    }
+}
+
+declare_class_iid!(
+    IEventList,
+    0x3A2C_4214,
+    0x3463_49FE,
+    0xB2C4_F397,
+    0xB969_5A44
+);
+declare_interface!(IEventList);
+interface_hierarchy!(IEventList, FUnknown);
+impl IEventList {
+    /// Returns the count of events
+    pub fn get_event_count(&self) -> i32 {
+        unsafe { (self.vtable().get_event_count)(transmute_copy(self)) }
+    }
+    /// Gets the event at `index`
+    pub fn get_event(&self, index: i32) -> Result<Event, EInterface> {
+        let mut event = Event::new(0, 0, EventKind::sysex(&[]));
+        unsafe { (self.vtable().get_event)(transmute_copy(self), index, &mut event)? };
+        Ok(event)
+    }
+    /// Adds a new event, the host copies its data
+    pub fn add_event(&self, event: &Event) -> Result<(), EInterface> {
+        let mut event = *event;
+        unsafe { (self.vtable().add_event)(transmute_copy(self), &mut event) }
+    }
+    /// Adds `kind` on `bus_index` at `sample_offset`
+    pub fn add(
+        &self,
+        bus_index: i32,
+        sample_offset: i32,
+        kind: EventKind<'_>,
+    ) -> Result<(), EInterface> {
+        self.add_event(&Event::new(bus_index, sample_offset, kind))
+    }
+    /// Iterates over the events of the list with their type specific data,
+    /// events of unknown types are skipped
+    pub fn events(&self) -> Events<'_> {
+        Events {
+            list: self,
+            index: 0,
+            count: self.get_event_count().max(0),
+        }
+    }
+}
+declare_class_vtable!(
+    IEventList,
+    base FUnknown,
+    function get_event_count: () -> i32,
+    function get_event: (index: i32, event: *mut Event) -> Result<(), EInterface>,
+    function add_event: (event: *mut Event) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IEventListVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IEventListImpl,
+    {
+        unsafe fn get_event_count<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> i32
+        where
+            Identity: IEventListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEventListImpl::get_event_count(this)
+        }
+        unsafe fn get_event<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            index: i32,
+            event: *mut Event,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IEventListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEventListImpl::get_event(this, index, event)
+        }
+        unsafe fn add_event<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            event: *mut Event,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IEventListImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IEventListImpl::add_event(this, event)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_event_count: get_event_count::<Identity, OFFSET>,
+            get_event: get_event::<Identity, OFFSET>,
+            add_event: add_event::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IEventList as Interface>::iid
+    }
+}
+
+/// Iterator over the events of an [`IEventList`], see [`IEventList::events`]
+///
+/// Yields each raw [`Event`] with its [`EventKind`], the data of the events
+/// is valid as long as the list is borrowed.
+pub struct Events<'a> {
+    list: &'a IEventList,
+    index: i32,
+    count: i32,
+}
+impl<'a> Iterator for Events<'a> {
+    type Item = (Event, EventKind<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.count {
+            let event = self.list.get_event(self.index);
+            self.index += 1;
+            let Ok(event) = event else {
+                continue;
+            };
+            // The host keeps the data of its events alive during the processing call
+            if let Some(kind) = unsafe { event.kind_unbounded() } {
+                return Some((event, kind));
+            }
+        }
+        None
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some((self.count - self.index) as usize))
+    }
+}
+impl FusedIterator for Events<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugininterfaces::base::ustring::to_utf16_vec;
+
+    #[test]
+    fn layout() {
+        assert_eq!(std::mem::size_of::<EventData>(), 24);
+        assert_eq!(std::mem::size_of::<Event>(), 48);
+        assert_eq!(std::mem::offset_of!(Event, data), 24);
+    }
+
+    #[test]
+    fn kind_round_trip() {
+        let text = to_utf16_vec("Cmaj7");
+        let text = &text[..text.len() - 1];
+        let sysex = [0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7];
+        let kinds = [
+            EventKind::NoteOn(NoteOnEvent {
+                channel: 1,
+                pitch: 60,
+                tuning: -12.5,
+                velocity: 0.75,
+                length: 480,
+                note_id: 7,
+            }),
+            EventKind::NoteOff(NoteOffEvent {
+                channel: 1,
+                pitch: 60,
+                velocity: 0.5,
+                note_id: 7,
+                tuning: 3.0,
+            }),
+            EventKind::sysex(&sysex),
+            EventKind::PolyPressure(PolyPressureEvent {
+                channel: 2,
+                pitch: 64,
+                pressure: 0.25,
+                note_id: -1,
+            }),
+            EventKind::NoteExpressionValue(NoteExpressionValueEvent {
+                type_id: 2,
+                note_id: 7,
+                value: 0.125,
+            }),
+            EventKind::NoteExpressionText {
+                type_id: 5,
+                note_id: 7,
+                text,
+            },
+            EventKind::Chord {
+                root: 60,
+                bass_note: 48,
+                mask: 0x891,
+                text,
+            },
+            EventKind::Scale {
+                root: 0,
+                mask: 0x5ab5,
+                text,
+            },
+            EventKind::LegacyMIDICCOut(LegacyMIDICCOutEvent {
+                control_number: 129,
+                channel: 3,
+                value: 0,
+                value2: 64,
+            }),
+        ];
+        for kind in kinds {
+            let mut event = Event::new(1, 32, kind);
+            event.ppq_position = 4.5;
+            event.flags = EventFlags::IsLive as u16;
+            let read = unsafe { event.kind() }.unwrap();
+            assert_eq!(read, kind);
+
+            let mut rebuilt = event;
+            rebuilt.set_kind(read);
+            assert_eq!(rebuilt.type_, event.type_);
+            assert_eq!(unsafe { rebuilt.kind() }, Some(kind));
+            assert!(rebuilt.has_flag(EventFlags::IsLive));
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Host context handed to `IPluginBase::initialize`, see
+//! `pluginterfaces/vst/ivsthostapplication.h`
+use super::{
+    ivstmessage::IMessage,
+    vsttypes::{string128_to_string, String128},
+};
+use crate::plugininterfaces::base::{macros::*, pluginreexports::*, EInterface, Interface, TUID};
+use std::{
+    mem::{transmute_copy, MaybeUninit},
+    os::raw::c_void,
+};
+
+declare_class_iid!(
+    IHostApplication,
+    0x58E5_95CC,
+    0xDB2D_4969,
+    0x8B6A_AF8C,
+    0x36A6_64E5
+);
+declare_interface!(IHostApplication);
+interface_hierarchy!(IHostApplication, FUnknown);
+impl IHostApplication {
+    pub fn get_name(&self) -> Result<String, EInterface> {
+        unsafe { (self.vtable().get_name)(transmute_copy(self)) }
+            .map(|name| string128_to_string(&name))
+    }
+    /// Creates a host object of class `cid`, queried for `iid`
+    ///
+    /// # Safety
+    ///
+    /// `obj` must be valid for writing an interface pointer, which the caller owns afterwards.
+    pub unsafe fn create_instance_raw(
+        &self,
+        cid: &FUID,
+        iid: &FUID,
+        obj: *mut *mut c_void,
+    ) -> Result<(), EInterface> {
+        (self.vtable().create_instance)(transmute_copy(self), cid.as_ref(), iid.as_ref(), obj)
+    }
+    /// Creates a host object implementing `T`, e.g. an [`IMessage`] or an `IAttributeList`
+    pub fn create_instance<T: Interface>(&self) -> Result<T, EInterface> {
+        unsafe {
+            let mut result = MaybeUninit::<Option<T>>::zeroed();
+            self.create_instance_raw(&T::iid, &T::iid, result.as_mut_ptr() as _)?;
+            result.assume_init().ok_or(EInterface::Pointer)
+        }
+    }
+    /// Creates a message to send to a connected `IConnectionPoint`
+    pub fn allocate_message(&self) -> Result<IMessage, EInterface> {
+        self.create_instance::<IMessage>()
+    }
+}
+declare_class_vtable!(
+    IHostApplication,
+    base FUnknown,
+    function get_name: () -> Result<String128, EInterface>,
+    function create_instance: (cid: &TUID, iid: &TUID, obj: *mut *mut c_void) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IHostApplicationVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IHostApplicationImpl,
+    {
+        unsafe fn get_name<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> Result<String128, EInterface>
+        where
+            Identity: IHostApplicationImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IHostApplicationImpl::get_name(this)
+        }
+        unsafe fn create_instance<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            cid: &TUID,
+            iid: &TUID,
+            obj: *mut *mut c_void,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IHostApplicationImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IHostApplicationImpl::create_instance(this, cid, iid, obj)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_name: get_name::<Identity, OFFSET>,
+            create_instance: create_instance::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IHostApplication as Interface>::iid
+    }
+}
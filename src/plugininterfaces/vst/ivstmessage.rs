@@ -0,0 +1,174 @@
+//! Messages between the processor and the edit controller, see
+//! `pluginterfaces/vst/ivstmessage.h`
+//!
+//! The host connects the [`IConnectionPoint`]s of the component and the controller,
+//! which then send each other [`IMessage`]s allocated through
+//! [`IHostApplication::allocate_message`]:
+//!
+//! ```ignore
+//! let message = host.allocate_message()?;
+//! message.set_message_id(c"Spectrum");
+//! if let Some(attributes) = message.get_attributes() {
+//!     attributes.set_binary(c"Bins", &bins)?;
+//! }
+//! peer.notify(&message)?;
+//! ```
+//!
+//! [`IHostApplication::allocate_message`]: super::ivsthostapplication::IHostApplication::allocate_message
+use super::ivstattributes::IAttributeList;
+use crate::plugininterfaces::base::{
+    macros::*, pluginreexports::*, EInterface, Interface, InterfaceRef,
+};
+use std::{
+    ffi::{c_char, CStr},
+    mem::transmute_copy,
+    os::raw::c_void,
+};
+
+declare_class_iid!(IMessage, 0x936F_033B, 0xC6C0_47DB, 0xBB08_82F8, 0x13C1_E613);
+declare_interface!(IMessage);
+interface_hierarchy!(IMessage, FUnknown);
+impl IMessage {
+    pub fn get_message_id(&self) -> Option<&CStr> {
+        let id = unsafe { (self.vtable().get_message_id)(transmute_copy(self)) };
+        if id.is_null() {
+            return None;
+        }
+        Some(unsafe { CStr::from_ptr(id) })
+    }
+    pub fn set_message_id(&self, id: &CStr) {
+        unsafe { (self.vtable().set_message_id)(transmute_copy(self), id) }
+    }
+    /// Attributes carrying the content of the message, owned by the message
+    pub fn get_attributes(&self) -> Option<InterfaceRef<'_, IAttributeList>> {
+        unsafe { InterfaceRef::from_raw((self.vtable().get_attributes)(transmute_copy(self))) }
+    }
+}
+declare_class_vtable!(
+    IMessage,
+    base FUnknown,
+    function get_message_id: () -> *const c_char,
+    function set_message_id: (id: &CStr),
+    function get_attributes: () -> *mut c_void
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IMessageVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IMessageImpl,
+    {
+        unsafe fn get_message_id<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> *const c_char
+        where
+            Identity: IMessageImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IMessageImpl::get_message_id(this)
+        }
+        unsafe fn set_message_id<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: &CStr,
+        ) where
+            Identity: IMessageImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IMessageImpl::set_message_id(this, id)
+        }
+        unsafe fn get_attributes<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> *mut c_void
+        where
+            Identity: IMessageImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IMessageImpl::get_attributes(this)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_message_id: get_message_id::<Identity, OFFSET>,
+            set_message_id: set_message_id::<Identity, OFFSET>,
+            get_attributes: get_attributes::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IMessage as Interface>::iid
+    }
+}
+
+declare_class_iid!(
+    IConnectionPoint,
+    0x70A4_156F,
+    0x6E6E_4026,
+    0x9891_48BF,
+    0xAA60_D8D1
+);
+declare_interface!(IConnectionPoint);
+interface_hierarchy!(IConnectionPoint, FUnknown);
+impl IConnectionPoint {
+    /// Connects to `other`, called by the host on both sides
+    pub fn connect(&self, other: &IConnectionPoint) -> Result<(), EInterface> {
+        unsafe { (self.vtable().connect)(transmute_copy(self), other) }
+    }
+    pub fn disconnect(&self, other: &IConnectionPoint) -> Result<(), EInterface> {
+        unsafe { (self.vtable().disconnect)(transmute_copy(self), other) }
+    }
+    /// Delivers `message` from the connected peer
+    pub fn notify(&self, message: &IMessage) -> Result<(), EInterface> {
+        unsafe { (self.vtable().notify)(transmute_copy(self), message) }
+    }
+}
+declare_class_vtable!(
+    IConnectionPoint,
+    base FUnknown,
+    function connect: (other: &IConnectionPoint) -> Result<(), EInterface>,
+    function disconnect: (other: &IConnectionPoint) -> Result<(), EInterface>,
+    function notify: (message: &IMessage) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IConnectionPointVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IConnectionPointImpl,
+    {
+        unsafe fn connect<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            other: &IConnectionPoint,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IConnectionPointImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IConnectionPointImpl::connect(this, other)
+        }
+        unsafe fn disconnect<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            other: &IConnectionPoint,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IConnectionPointImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IConnectionPointImpl::disconnect(this, other)
+        }
+        unsafe fn notify<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            message: &IMessage,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IConnectionPointImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IConnectionPointImpl::notify(this, message)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            connect: connect::<Identity, OFFSET>,
+            disconnect: disconnect::<Identity, OFFSET>,
+            notify: notify::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IConnectionPoint as Interface>::iid
+    }
+}
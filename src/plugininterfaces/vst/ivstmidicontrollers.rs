@@ -0,0 +1,155 @@
+//! MIDI controller numbers and MIDI mapping, see `pluginterfaces/vst/ivstmidicontrollers.h`
+//! and `pluginterfaces/vst/ivsteditcontroller.h`
+#![allow(non_upper_case_globals)]
+
+use super::vsttypes::ParamID;
+use crate::plugininterfaces::base::{macros::*, pluginreexports::*, EInterface, Interface};
+use std::{mem::transmute_copy, os::raw::c_void};
+
+/// MIDI controller number, a MIDI CC number or one of the values past 127 below
+pub type CtrlNumber = i16;
+
+pub const kCtrlBankSelectMSB: CtrlNumber = 0;
+pub const kCtrlModWheel: CtrlNumber = 1;
+pub const kCtrlBreath: CtrlNumber = 2;
+pub const kCtrlFoot: CtrlNumber = 4;
+pub const kCtrlPortaTime: CtrlNumber = 5;
+pub const kCtrlDataEntryMSB: CtrlNumber = 6;
+pub const kCtrlVolume: CtrlNumber = 7;
+pub const kCtrlBalance: CtrlNumber = 8;
+pub const kCtrlPan: CtrlNumber = 10;
+pub const kCtrlExpression: CtrlNumber = 11;
+pub const kCtrlEffect1: CtrlNumber = 12;
+pub const kCtrlEffect2: CtrlNumber = 13;
+pub const kCtrlGPC1: CtrlNumber = 16;
+pub const kCtrlGPC2: CtrlNumber = 17;
+pub const kCtrlGPC3: CtrlNumber = 18;
+pub const kCtrlGPC4: CtrlNumber = 19;
+pub const kCtrlBankSelectLSB: CtrlNumber = 32;
+pub const kCtrlDataEntryLSB: CtrlNumber = 38;
+pub const kCtrlSustainOnOff: CtrlNumber = 64;
+pub const kCtrlPortaOnOff: CtrlNumber = 65;
+pub const kCtrlSustenutoOnOff: CtrlNumber = 66;
+pub const kCtrlSoftPedalOnOff: CtrlNumber = 67;
+pub const kCtrlLegatoFootSwOnOff: CtrlNumber = 68;
+pub const kCtrlHold2OnOff: CtrlNumber = 69;
+pub const kCtrlSoundVariation: CtrlNumber = 70;
+pub const kCtrlFilterCutoff: CtrlNumber = 71;
+pub const kCtrlReleaseTime: CtrlNumber = 72;
+pub const kCtrlAttackTime: CtrlNumber = 73;
+pub const kCtrlFilterResonance: CtrlNumber = 74;
+pub const kCtrlDecayTime: CtrlNumber = 75;
+pub const kCtrlVibratoRate: CtrlNumber = 76;
+pub const kCtrlVibratoDepth: CtrlNumber = 77;
+pub const kCtrlVibratoDelay: CtrlNumber = 78;
+pub const kCtrlSoundCtrler10: CtrlNumber = 79;
+pub const kCtrlGPC5: CtrlNumber = 80;
+pub const kCtrlGPC6: CtrlNumber = 81;
+pub const kCtrlGPC7: CtrlNumber = 82;
+pub const kCtrlGPC8: CtrlNumber = 83;
+pub const kCtrlPortaControl: CtrlNumber = 84;
+pub const kCtrlEff1Depth: CtrlNumber = 91;
+pub const kCtrlEff2Depth: CtrlNumber = 92;
+pub const kCtrlEff3Depth: CtrlNumber = 93;
+pub const kCtrlEff4Depth: CtrlNumber = 94;
+pub const kCtrlEff5Depth: CtrlNumber = 95;
+pub const kCtrlDataIncrement: CtrlNumber = 96;
+pub const kCtrlDataDecrement: CtrlNumber = 97;
+pub const kCtrlNRPNSelectLSB: CtrlNumber = 98;
+pub const kCtrlNRPNSelectMSB: CtrlNumber = 99;
+pub const kCtrlRPNSelectLSB: CtrlNumber = 100;
+pub const kCtrlRPNSelectMSB: CtrlNumber = 101;
+pub const kCtrlAllSoundsOff: CtrlNumber = 120;
+pub const kCtrlResetAllCtrlers: CtrlNumber = 121;
+pub const kCtrlLocalCtrlOnOff: CtrlNumber = 122;
+pub const kCtrlAllNotesOff: CtrlNumber = 123;
+pub const kCtrlOmniModeOff: CtrlNumber = 124;
+pub const kCtrlOmniModeOn: CtrlNumber = 125;
+pub const kCtrlPolyModeOnOff: CtrlNumber = 126;
+pub const kCtrlPolyModeOn: CtrlNumber = 127;
+
+/// Channel pressure (aftertouch)
+pub const kAfterTouch: CtrlNumber = 128;
+/// Pitch bend change
+pub const kPitchBend: CtrlNumber = 129;
+/// Count of controllers which can be mapped to parameters
+pub const kCountCtrlNumber: CtrlNumber = 130;
+
+/// Program change, only used in [`LegacyMIDICCOutEvent`]
+///
+/// [`LegacyMIDICCOutEvent`]: super::ivstevents::LegacyMIDICCOutEvent
+pub const kCtrlProgramChange: CtrlNumber = 130;
+/// Polyphonic key pressure, only used in [`LegacyMIDICCOutEvent`]
+///
+/// [`LegacyMIDICCOutEvent`]: super::ivstevents::LegacyMIDICCOutEvent
+pub const kCtrlPolyPressure: CtrlNumber = 131;
+/// Quarter frame, only used in [`LegacyMIDICCOutEvent`]
+///
+/// [`LegacyMIDICCOutEvent`]: super::ivstevents::LegacyMIDICCOutEvent
+pub const kCtrlQuarterFrame: CtrlNumber = 132;
+
+declare_class_iid!(
+    IMidiMapping,
+    0xDF0F_F9F7,
+    0x49B7_4669,
+    0xB63A_B732,
+    0x7ADB_F5E5
+);
+declare_interface!(IMidiMapping);
+interface_hierarchy!(IMidiMapping, FUnknown);
+impl IMidiMapping {
+    /// Gets the parameter assigned to the MIDI controller `midi_controller_number` of the
+    /// given bus and channel, fails with [`EInterface::ResultFalse`] if there is none
+    pub fn get_midi_controller_assignment(
+        &self,
+        bus_index: i32,
+        channel: i16,
+        midi_controller_number: CtrlNumber,
+    ) -> Result<ParamID, EInterface> {
+        unsafe {
+            (self.vtable().get_midi_controller_assignment)(
+                transmute_copy(self),
+                bus_index,
+                channel,
+                midi_controller_number,
+            )
+        }
+    }
+}
+declare_class_vtable!(
+    IMidiMapping,
+    base FUnknown,
+    function get_midi_controller_assignment: (bus_index: i32, channel: i16, midi_controller_number: CtrlNumber) -> Result<ParamID, EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IMidiMappingVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IMidiMappingImpl,
+    {
+        unsafe fn get_midi_controller_assignment<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            bus_index: i32,
+            channel: i16,
+            midi_controller_number: CtrlNumber,
+        ) -> Result<ParamID, EInterface>
+        where
+            Identity: IMidiMappingImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IMidiMappingImpl::get_midi_controller_assignment(
+                this,
+                bus_index,
+                channel,
+                midi_controller_number,
+            )
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_midi_controller_assignment: get_midi_controller_assignment::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IMidiMapping as Interface>::iid
+    }
+}
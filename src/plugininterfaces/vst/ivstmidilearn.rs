@@ -0,0 +1,65 @@
+//! MIDI learn, see `pluginterfaces/vst/ivstmidilearn.h`
+use super::ivstmidicontrollers::CtrlNumber;
+use crate::plugininterfaces::base::{macros::*, pluginreexports::*, EInterface, Interface};
+use std::{mem::transmute_copy, os::raw::c_void};
+
+declare_class_iid!(
+    IMidiLearn,
+    0x6B24_49CC,
+    0x4197_40B5,
+    0xAB3C_79DA,
+    0xC5FE_5C86
+);
+declare_interface!(IMidiLearn);
+interface_hierarchy!(IMidiLearn, FUnknown);
+impl IMidiLearn {
+    /// Called by the host on the edit controller for live MIDI controller input,
+    /// so the plug-in can assign it to the parameter being learned
+    pub fn on_live_midi_controller_input(
+        &self,
+        bus_index: i32,
+        channel: i16,
+        midi_cc: CtrlNumber,
+    ) -> Result<(), EInterface> {
+        unsafe {
+            (self.vtable().on_live_midi_controller_input)(
+                transmute_copy(self),
+                bus_index,
+                channel,
+                midi_cc,
+            )
+        }
+    }
+}
+declare_class_vtable!(
+    IMidiLearn,
+    base FUnknown,
+    function on_live_midi_controller_input: (bus_index: i32, channel: i16, midi_cc: CtrlNumber) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IMidiLearnVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IMidiLearnImpl,
+    {
+        unsafe fn on_live_midi_controller_input<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            bus_index: i32,
+            channel: i16,
+            midi_cc: CtrlNumber,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IMidiLearnImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IMidiLearnImpl::on_live_midi_controller_input(this, bus_index, channel, midi_cc)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            on_live_midi_controller_input: on_live_midi_controller_input::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IMidiLearn as Interface>::iid
+    }
+}
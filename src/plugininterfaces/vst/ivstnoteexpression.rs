@@ -0,0 +1,718 @@
+//! Note expression types, see `pluginterfaces/vst/ivstnoteexpression.h`
+#![allow(non_upper_case_globals)]
+
+use super::vsttypes::{
+    kNoParamId, kRootUnitId, string128_from_str, string128_to_string, ParamID, String128, TChar,
+    UnitID,
+};
+use crate::plugininterfaces::base::{
+    macros::*, pluginreexports::*, ustring::to_utf16_vec, EInterface, Interface,
+};
+use std::{mem::transmute_copy, ops, os::raw::c_void};
+
+/// Note expression type identifier
+pub type NoteExpressionTypeID = u32;
+/// Note expression value, normalized to [0, 1]
+pub type NoteExpressionValue = f64;
+
+/// Volume, plain = 20 * log(4 * norm) dB: 0.0 = -oo, 0.25 = 0dB, 1.0 = +12dB
+pub const kVolumeTypeID: NoteExpressionTypeID = 0;
+/// Panning, 0.0 = left, 0.5 = center, 1.0 = right
+pub const kPanTypeID: NoteExpressionTypeID = 1;
+/// Tuning, plain = 240 * (norm - 0.5) semitones: 0.0 = -120, 0.5 = none, 1.0 = +120
+pub const kTuningTypeID: NoteExpressionTypeID = 2;
+/// Vibrato, 0.0 = none, 1.0 = full
+pub const kVibratoTypeID: NoteExpressionTypeID = 3;
+/// Expression, 0.0 = none, 1.0 = full
+pub const kExpressionTypeID: NoteExpressionTypeID = 4;
+/// Brightness, 0.0 = darker, 0.5 = none, 1.0 = brighter
+pub const kBrightnessTypeID: NoteExpressionTypeID = 5;
+/// Text, sent as [`NoteExpressionTextEvent`]
+pub const kTextTypeID: NoteExpressionTypeID = 6;
+/// Phoneme, sent as [`NoteExpressionTextEvent`]
+pub const kPhonemeTypeID: NoteExpressionTypeID = 7;
+/// Start of the range of plug-in specific note expression types
+pub const kCustomStart: NoteExpressionTypeID = 100000;
+/// End of the range of plug-in specific note expression types
+pub const kCustomEnd: NoteExpressionTypeID = 200000;
+/// Invalid note expression type
+pub const kInvalidTypeID: NoteExpressionTypeID = 0xFFFF_FFFF;
+
+/// Note expression value event, changes the expression `type_id` of the note `note_id`
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NoteExpressionValueEvent {
+    /// Identifier of the note expression type
+    pub type_id: NoteExpressionTypeID,
+    /// Identifier of the note this event applies to
+    pub note_id: i32,
+    /// Normalized value of the note expression
+    pub value: NoteExpressionValue,
+}
+
+/// Note expression text event, used for the text and phoneme note expression types
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NoteExpressionTextEvent {
+    /// Identifier of the note expression type
+    pub type_id: NoteExpressionTypeID,
+    /// Identifier of the note this event applies to
+    pub note_id: i32,
+    /// Number of characters of `text`, excluding the terminating zero
+    pub text_len: u32,
+    /// Zero terminated UTF-16 text
+    pub text: *const TChar,
+}
+
+/// Value range and default of a note expression type, all values are normalized
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NoteExpressionValueDescription {
+    /// Default normalized value [0, 1]
+    pub default_value: NoteExpressionValue,
+    /// Minimum normalized value [0, 1]
+    pub minimum: NoteExpressionValue,
+    /// Maximum normalized value [0, 1]
+    pub maximum: NoteExpressionValue,
+    /// Number of discrete steps, 0 for a continuous note expression
+    pub step_count: i32,
+}
+impl Default for NoteExpressionValueDescription {
+    fn default() -> Self {
+        Self {
+            default_value: 0.0,
+            minimum: 0.0,
+            maximum: 1.0,
+            step_count: 0,
+        }
+    }
+}
+
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NoteExpressionTypeFlags {
+    NoFlags,
+
+    /// Event is bipolar (e.g. pan), otherwise unipolar
+    IsBipolar = 1 << 0,
+
+    /// Event occurs only once for each note
+    IsOneShot = 1 << 1,
+
+    /// Absolute value of the note expression, otherwise relative to the parameter
+    IsAbsolute = 1 << 2,
+
+    /// `associated_parameter_id` is valid
+    AssociatedParameterIDValid = 1 << 3,
+}
+impl ops::BitOr<NoteExpressionTypeFlags> for i32 {
+    type Output = i32;
+    fn bitor(self, rhs: NoteExpressionTypeFlags) -> Self::Output {
+        self | rhs as i32
+    }
+}
+impl ops::BitOr for NoteExpressionTypeFlags {
+    type Output = i32;
+    fn bitor(self, rhs: NoteExpressionTypeFlags) -> Self::Output {
+        self as i32 | rhs as i32
+    }
+}
+
+/// Description of a note expression type supported by a plug-in
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct NoteExpressionTypeInfo {
+    /// Unique identifier of this note expression type
+    pub type_id: NoteExpressionTypeID,
+    /// Note expression type title (e.g. "Volume")
+    pub title: String128,
+    /// Note expression type short title (e.g. "Vol")
+    pub short_title: String128,
+    /// Note expression type unit (e.g. "dB")
+    pub units: String128,
+    /// Identifier of the unit this note expression belongs to
+    pub unit_id: UnitID,
+    /// Value range and default
+    pub value_desc: NoteExpressionValueDescription,
+    /// Parameter this note expression is applied to, if flagged as valid
+    pub associated_parameter_id: ParamID,
+    /// Combination of [`NoteExpressionTypeFlags`]
+    pub flags: i32,
+}
+impl NoteExpressionTypeInfo {
+    pub fn new(type_id: NoteExpressionTypeID, title: &str, units: &str) -> Self {
+        Self {
+            type_id,
+            title: string128_from_str(title),
+            units: string128_from_str(units),
+            ..Default::default()
+        }
+    }
+    pub fn title(&self) -> String {
+        string128_to_string(&self.title)
+    }
+    pub fn set_title(&mut self, title: &str) {
+        self.title = string128_from_str(title);
+    }
+    pub fn short_title(&self) -> String {
+        string128_to_string(&self.short_title)
+    }
+    pub fn set_short_title(&mut self, short_title: &str) {
+        self.short_title = string128_from_str(short_title);
+    }
+    pub fn units(&self) -> String {
+        string128_to_string(&self.units)
+    }
+    pub fn set_units(&mut self, units: &str) {
+        self.units = string128_from_str(units);
+    }
+    pub fn has_flag(&self, flag: NoteExpressionTypeFlags) -> bool {
+        self.flags & flag as i32 != 0
+    }
+    /// Parameter this note expression is applied to, if any
+    pub fn associated_parameter(&self) -> Option<ParamID> {
+        self.has_flag(NoteExpressionTypeFlags::AssociatedParameterIDValid)
+            .then_some(self.associated_parameter_id)
+    }
+}
+impl Default for NoteExpressionTypeInfo {
+    fn default() -> Self {
+        Self {
+            type_id: kInvalidTypeID,
+            title: [0; 128],
+            short_title: [0; 128],
+            units: [0; 128],
+            unit_id: kRootUnitId,
+            value_desc: NoteExpressionValueDescription::default(),
+            associated_parameter_id: kNoParamId,
+            flags: NoteExpressionTypeFlags::NoFlags as i32,
+        }
+    }
+}
+
+declare_class_iid!(
+    INoteExpressionController,
+    0xB7F8_F859,
+    0x4123_4872,
+    0x9116_9581,
+    0x4F37_21A3
+);
+declare_interface!(INoteExpressionController);
+interface_hierarchy!(INoteExpressionController, FUnknown);
+impl INoteExpressionController {
+    /// Returns the number of note expression types supported on the given bus and channel
+    pub fn get_note_expression_count(&self, bus_index: i32, channel: i16) -> i32 {
+        unsafe {
+            (self.vtable().get_note_expression_count)(transmute_copy(self), bus_index, channel)
+        }
+    }
+    /// Gets the info of the note expression type at `note_expression_index`
+    pub fn get_note_expression_info(
+        &self,
+        bus_index: i32,
+        channel: i16,
+        note_expression_index: i32,
+    ) -> Result<NoteExpressionTypeInfo, EInterface> {
+        unsafe {
+            (self.vtable().get_note_expression_info)(
+                transmute_copy(self),
+                bus_index,
+                channel,
+                note_expression_index,
+            )
+        }
+    }
+    /// Gets the string representation of a normalized note expression value
+    pub fn get_note_expression_string_by_value(
+        &self,
+        bus_index: i32,
+        channel: i16,
+        id: NoteExpressionTypeID,
+        value_normalized: NoteExpressionValue,
+    ) -> Result<String, EInterface> {
+        let string = unsafe {
+            (self.vtable().get_note_expression_string_by_value)(
+                transmute_copy(self),
+                bus_index,
+                channel,
+                id,
+                value_normalized,
+            )
+        }?;
+        Ok(string128_to_string(&string))
+    }
+    /// Parses `string` into a normalized note expression value
+    pub fn get_note_expression_value_by_string(
+        &self,
+        bus_index: i32,
+        channel: i16,
+        id: NoteExpressionTypeID,
+        string: &str,
+    ) -> Result<NoteExpressionValue, EInterface> {
+        let string = to_utf16_vec(string);
+        unsafe {
+            (self.vtable().get_note_expression_value_by_string)(
+                transmute_copy(self),
+                bus_index,
+                channel,
+                id,
+                string.as_ptr(),
+            )
+        }
+    }
+}
+declare_class_vtable!(
+    INoteExpressionController,
+    base FUnknown,
+    function get_note_expression_count: (bus_index: i32, channel: i16) -> i32,
+    function get_note_expression_info: (bus_index: i32, channel: i16, note_expression_index: i32) -> Result<NoteExpressionTypeInfo, EInterface>,
+    function get_note_expression_string_by_value: (bus_index: i32, channel: i16, id: NoteExpressionTypeID, value_normalized: NoteExpressionValue) -> Result<String128, EInterface>,
+    function get_note_expression_value_by_string: (bus_index: i32, channel: i16, id: NoteExpressionTypeID, string: *const TChar) -> Result<NoteExpressionValue, EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl INoteExpressionControllerVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: INoteExpressionControllerImpl,
+    {
+        unsafe fn get_note_expression_count<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            bus_index: i32,
+            channel: i16,
+        ) -> i32
+        where
+            Identity: INoteExpressionControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            INoteExpressionControllerImpl::get_note_expression_count(this, bus_index, channel)
+        }
+        unsafe fn get_note_expression_info<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            bus_index: i32,
+            channel: i16,
+            note_expression_index: i32,
+        ) -> Result<NoteExpressionTypeInfo, EInterface>
+        where
+            Identity: INoteExpressionControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            INoteExpressionControllerImpl::get_note_expression_info(
+                this,
+                bus_index,
+                channel,
+                note_expression_index,
+            )
+        }
+        unsafe fn get_note_expression_string_by_value<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            bus_index: i32,
+            channel: i16,
+            id: NoteExpressionTypeID,
+            value_normalized: NoteExpressionValue,
+        ) -> Result<String128, EInterface>
+        where
+            Identity: INoteExpressionControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            INoteExpressionControllerImpl::get_note_expression_string_by_value(
+                this,
+                bus_index,
+                channel,
+                id,
+                value_normalized,
+            )
+        }
+        unsafe fn get_note_expression_value_by_string<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            bus_index: i32,
+            channel: i16,
+            id: NoteExpressionTypeID,
+            string: *const TChar,
+        ) -> Result<NoteExpressionValue, EInterface>
+        where
+            Identity: INoteExpressionControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            INoteExpressionControllerImpl::get_note_expression_value_by_string(
+                this, bus_index, channel, id, string,
+            )
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_note_expression_count: get_note_expression_count::<Identity, OFFSET>,
+            get_note_expression_info: get_note_expression_info::<Identity, OFFSET>,
+            get_note_expression_string_by_value: get_note_expression_string_by_value::<
+                Identity,
+                OFFSET,
+            >,
+            get_note_expression_value_by_string: get_note_expression_value_by_string::<
+                Identity,
+                OFFSET,
+            >,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<INoteExpressionController as Interface>::iid
+    }
+}
+
+/// Physical UI type identifier
+pub type PhysicalUITypeID = u32;
+
+/// Horizontal movement on the physical UI, 0.0 = left, 1.0 = right
+pub const kPUIXMovement: PhysicalUITypeID = 0;
+/// Vertical movement on the physical UI, 0.0 = bottom, 1.0 = top
+pub const kPUIYMovement: PhysicalUITypeID = 1;
+/// Pressure on the physical UI, 0.0 = none, 1.0 = full
+pub const kPUIPressure: PhysicalUITypeID = 2;
+/// Count of the predefined physical UI types
+pub const kPUITypeCount: PhysicalUITypeID = 3;
+/// Invalid physical UI type
+pub const kInvalidPUITypeID: PhysicalUITypeID = 0xFFFF_FFFF;
+
+/// Association of a physical UI type with a note expression type
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PhysicalUIMap {
+    /// Physical UI type, filled in by the host
+    pub physical_ui_type_id: PhysicalUITypeID,
+    /// Note expression type, filled in by the plug-in or [`kInvalidTypeID`]
+    pub note_expression_type_id: NoteExpressionTypeID,
+}
+impl PhysicalUIMap {
+    /// Entry for the host to query, not yet mapped
+    pub fn new(physical_ui_type_id: PhysicalUITypeID) -> Self {
+        Self {
+            physical_ui_type_id,
+            note_expression_type_id: kInvalidTypeID,
+        }
+    }
+}
+
+/// Array of [`PhysicalUIMap`] allocated by the host
+#[repr(C)]
+#[derive(Debug)]
+pub struct PhysicalUIMapList {
+    /// Number of entries of `map`
+    pub count: u32,
+    pub map: *mut PhysicalUIMap,
+}
+impl PhysicalUIMapList {
+    /// Entries of the list
+    ///
+    /// # Safety
+    ///
+    /// `map` must be null or point to `count` valid entries.
+    pub unsafe fn as_mut_slice(&mut self) -> &mut [PhysicalUIMap] {
+        if self.map.is_null() {
+            return &mut [];
+        }
+        std::slice::from_raw_parts_mut(self.map, self.count as usize)
+    }
+}
+
+declare_class_iid!(
+    INoteExpressionPhysicalUIMapping,
+    0xB030_78FF,
+    0x94D2_4AC8,
+    0x90CC_D303,
+    0xD413_3324
+);
+declare_interface!(INoteExpressionPhysicalUIMapping);
+interface_hierarchy!(INoteExpressionPhysicalUIMapping, FUnknown);
+impl INoteExpressionPhysicalUIMapping {
+    /// Fills the note expression type of each physical UI type in `map`,
+    /// entries the plug-in doesn't map are set to [`kInvalidTypeID`]
+    pub fn get_physical_ui_mapping(
+        &self,
+        bus_index: i32,
+        channel: i16,
+        map: &mut [PhysicalUIMap],
+    ) -> Result<(), EInterface> {
+        let mut list = PhysicalUIMapList {
+            count: u32::try_from(map.len()).map_err(|_| EInterface::InvalidArgument)?,
+            map: map.as_mut_ptr(),
+        };
+        unsafe {
+            (self.vtable().get_physical_ui_mapping)(
+                transmute_copy(self),
+                bus_index,
+                channel,
+                &mut list,
+            )
+        }
+    }
+}
+declare_class_vtable!(
+    INoteExpressionPhysicalUIMapping,
+    base FUnknown,
+    function get_physical_ui_mapping: (bus_index: i32, channel: i16, list: *mut PhysicalUIMapList) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl INoteExpressionPhysicalUIMappingVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: INoteExpressionPhysicalUIMappingImpl,
+    {
+        unsafe fn get_physical_ui_mapping<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            bus_index: i32,
+            channel: i16,
+            list: *mut PhysicalUIMapList,
+        ) -> Result<(), EInterface>
+        where
+            Identity: INoteExpressionPhysicalUIMappingImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            INoteExpressionPhysicalUIMappingImpl::get_physical_ui_mapping(
+                this, bus_index, channel, list,
+            )
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_physical_ui_mapping: get_physical_ui_mapping::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<INoteExpressionPhysicalUIMapping as Interface>::iid
+    }
+}
+
+declare_class_iid!(
+    IVst3WrapperMPESupport,
+    0x4414_9067,
+    0x42CF_4BF9,
+    0x8800_B750,
+    0xF735_9FE3
+);
+declare_interface!(IVst3WrapperMPESupport);
+interface_hierarchy!(IVst3WrapperMPESupport, FUnknown);
+impl IVst3WrapperMPESupport {
+    /// Enables or disables the MPE processing of the wrapper, the wrapper then converts
+    /// MPE input to note expression events
+    pub fn enable_mpe_input_processing(&self, state: bool) -> Result<(), EInterface> {
+        unsafe { (self.vtable().enable_mpe_input_processing)(transmute_copy(self), state) }
+    }
+    /// Sets the MPE zone: the master channel and the range of member channels
+    pub fn set_mpe_input_device_settings(
+        &self,
+        master_channel: i32,
+        member_begin_channel: i32,
+        member_end_channel: i32,
+    ) -> Result<(), EInterface> {
+        unsafe {
+            (self.vtable().set_mpe_input_device_settings)(
+                transmute_copy(self),
+                master_channel,
+                member_begin_channel,
+                member_end_channel,
+            )
+        }
+    }
+}
+declare_class_vtable!(
+    IVst3WrapperMPESupport,
+    base FUnknown,
+    function enable_mpe_input_processing: (state: bool) -> Result<(), EInterface>,
+    function set_mpe_input_device_settings: (master_channel: i32, member_begin_channel: i32, member_end_channel: i32) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IVst3WrapperMPESupportVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IVst3WrapperMPESupportImpl,
+    {
+        unsafe fn enable_mpe_input_processing<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            state: bool,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IVst3WrapperMPESupportImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IVst3WrapperMPESupportImpl::enable_mpe_input_processing(this, state)
+        }
+        unsafe fn set_mpe_input_device_settings<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            master_channel: i32,
+            member_begin_channel: i32,
+            member_end_channel: i32,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IVst3WrapperMPESupportImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IVst3WrapperMPESupportImpl::set_mpe_input_device_settings(
+                this,
+                master_channel,
+                member_begin_channel,
+                member_end_channel,
+            )
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            enable_mpe_input_processing: enable_mpe_input_processing::<Identity, OFFSET>,
+            set_mpe_input_device_settings: set_mpe_input_device_settings::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IVst3WrapperMPESupport as Interface>::iid
+    }
+}
+
+/// Key switch type identifier
+pub type KeyswitchTypeID = u32;
+
+/// Key switch activated by a note-on
+pub const kNoteOnKeyswitchTypeID: KeyswitchTypeID = 0;
+/// Key switch activated by a note-on while another note is held
+pub const kOnTheFlyKeyswitchTypeID: KeyswitchTypeID = 1;
+/// Key switch activated by a note-off
+pub const kOnReleaseKeyswitchTypeID: KeyswitchTypeID = 2;
+/// Range of keys playing the same articulation, not a key switch
+pub const kKeyRangeTypeID: KeyswitchTypeID = 3;
+
+/// Description of a key switch or key range of a plug-in
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct KeyswitchInfo {
+    /// One of the key switch type constants
+    pub type_id: KeyswitchTypeID,
+    /// Key switch title (e.g. "Accentuation")
+    pub title: String128,
+    /// Key switch short title (e.g. "Acc")
+    pub short_title: String128,
+    /// Lowest associated pitch
+    pub keyswitch_min: i32,
+    /// Highest associated pitch
+    pub keyswitch_max: i32,
+    /// Pitch the plug-in plays instead of the key switch, -1 if none
+    pub key_remapped: i32,
+    /// Identifier of the unit this key switch belongs to, -1 if none
+    pub unit_id: i32,
+    /// Reserved, 0
+    pub flags: i32,
+}
+impl KeyswitchInfo {
+    pub fn new(
+        type_id: KeyswitchTypeID,
+        title: &str,
+        keyswitch_min: i32,
+        keyswitch_max: i32,
+    ) -> Self {
+        Self {
+            type_id,
+            title: string128_from_str(title),
+            keyswitch_min,
+            keyswitch_max,
+            ..Default::default()
+        }
+    }
+    pub fn title(&self) -> String {
+        string128_to_string(&self.title)
+    }
+    pub fn set_title(&mut self, title: &str) {
+        self.title = string128_from_str(title);
+    }
+    pub fn short_title(&self) -> String {
+        string128_to_string(&self.short_title)
+    }
+    pub fn set_short_title(&mut self, short_title: &str) {
+        self.short_title = string128_from_str(short_title);
+    }
+    /// Whether `pitch` is in the range of this key switch
+    pub fn contains(&self, pitch: i32) -> bool {
+        (self.keyswitch_min..=self.keyswitch_max).contains(&pitch)
+    }
+}
+impl Default for KeyswitchInfo {
+    fn default() -> Self {
+        Self {
+            type_id: kNoteOnKeyswitchTypeID,
+            title: [0; 128],
+            short_title: [0; 128],
+            keyswitch_min: 0,
+            keyswitch_max: 0,
+            key_remapped: -1,
+            unit_id: -1,
+            flags: 0,
+        }
+    }
+}
+
+declare_class_iid!(
+    IKeyswitchController,
+    0x1F2F_76D3,
+    0xBFFB_4B96,
+    0xB995_27A5,
+    0x5EBC_CEF4
+);
+declare_interface!(IKeyswitchController);
+interface_hierarchy!(IKeyswitchController, FUnknown);
+impl IKeyswitchController {
+    /// Returns the number of key switches supported on the given bus and channel
+    pub fn get_keyswitch_count(&self, bus_index: i32, channel: i16) -> i32 {
+        unsafe { (self.vtable().get_keyswitch_count)(transmute_copy(self), bus_index, channel) }
+    }
+    /// Gets the info of the key switch at `keyswitch_index`
+    pub fn get_keyswitch_info(
+        &self,
+        bus_index: i32,
+        channel: i16,
+        keyswitch_index: i32,
+    ) -> Result<KeyswitchInfo, EInterface> {
+        unsafe {
+            (self.vtable().get_keyswitch_info)(
+                transmute_copy(self),
+                bus_index,
+                channel,
+                keyswitch_index,
+            )
+        }
+    }
+}
+declare_class_vtable!(
+    IKeyswitchController,
+    base FUnknown,
+    function get_keyswitch_count: (bus_index: i32, channel: i16) -> i32,
+    function get_keyswitch_info: (bus_index: i32, channel: i16, keyswitch_index: i32) -> Result<KeyswitchInfo, EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IKeyswitchControllerVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IKeyswitchControllerImpl,
+    {
+        unsafe fn get_keyswitch_count<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            bus_index: i32,
+            channel: i16,
+        ) -> i32
+        where
+            Identity: IKeyswitchControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IKeyswitchControllerImpl::get_keyswitch_count(this, bus_index, channel)
+        }
+        unsafe fn get_keyswitch_info<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            bus_index: i32,
+            channel: i16,
+            keyswitch_index: i32,
+        ) -> Result<KeyswitchInfo, EInterface>
+        where
+            Identity: IKeyswitchControllerImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IKeyswitchControllerImpl::get_keyswitch_info(this, bus_index, channel, keyswitch_index)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_keyswitch_count: get_keyswitch_count::<Identity, OFFSET>,
+            get_keyswitch_info: get_keyswitch_info::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IKeyswitchController as Interface>::iid
+    }
+}
@@ -0,0 +1,279 @@
+//! Parameter change interfaces, see `pluginterfaces/vst/ivstparameterchanges.h`
+use super::vsttypes::{ParamID, ParamValue};
+use crate::plugininterfaces::base::{
+    macros::*, pluginreexports::*, EInterface, Interface, InterfaceRef,
+};
+use std::{iter::FusedIterator, mem::transmute_copy, os::raw::c_void};
+
+declare_class_iid!(
+    IParamValueQueue,
+    0x0126_3A18,
+    0xED07_4F6F,
+    0x98C9_D356,
+    0x4686_F9BA
+);
+declare_interface!(IParamValueQueue);
+interface_hierarchy!(IParamValueQueue, FUnknown);
+impl IParamValueQueue {
+    /// Returns its associated ID
+    pub fn get_parameter_id(&self) -> ParamID {
+        unsafe { (self.vtable().get_parameter_id)(transmute_copy(self)) }
+    }
+    /// Returns the count of points in the queue
+    pub fn get_point_count(&self) -> i32 {
+        unsafe { (self.vtable().get_point_count)(transmute_copy(self)) }
+    }
+    /// Gets the sample offset and value of the point at `index`
+    pub fn get_point(&self, index: i32) -> Result<(i32, ParamValue), EInterface> {
+        unsafe { (self.vtable().get_point)(transmute_copy(self), index) }
+    }
+    /// Adds a new value at the end of the queue, its index is returned
+    pub fn add_point(&self, sample_offset: i32, value: ParamValue) -> Result<i32, EInterface> {
+        unsafe { (self.vtable().add_point)(transmute_copy(self), sample_offset, value) }
+    }
+    /// Iterates over the `(sample_offset, value)` points of the queue
+    pub fn points(&self) -> ParamValuePoints<'_> {
+        ParamValuePoints {
+            queue: self,
+            index: 0,
+            count: self.get_point_count().max(0),
+        }
+    }
+}
+declare_class_vtable!(
+    IParamValueQueue,
+    base FUnknown,
+    function get_parameter_id: () -> ParamID,
+    function get_point_count: () -> i32,
+    function get_point: (index: i32) -> Result<(i32, ParamValue), EInterface>,
+    function add_point: (sample_offset: i32, value: ParamValue) -> Result<i32, EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IParamValueQueueVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IParamValueQueueImpl,
+    {
+        unsafe fn get_parameter_id<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> ParamID
+        where
+            Identity: IParamValueQueueImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IParamValueQueueImpl::get_parameter_id(this)
+        }
+        unsafe fn get_point_count<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> i32
+        where
+            Identity: IParamValueQueueImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IParamValueQueueImpl::get_point_count(this)
+        }
+        unsafe fn get_point<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            index: i32,
+        ) -> Result<(i32, ParamValue), EInterface>
+        where
+            Identity: IParamValueQueueImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IParamValueQueueImpl::get_point(this, index)
+        }
+        unsafe fn add_point<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            sample_offset: i32,
+            value: ParamValue,
+        ) -> Result<i32, EInterface>
+        where
+            Identity: IParamValueQueueImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IParamValueQueueImpl::add_point(this, sample_offset, value)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_parameter_id: get_parameter_id::<Identity, OFFSET>,
+            get_point_count: get_point_count::<Identity, OFFSET>,
+            get_point: get_point::<Identity, OFFSET>,
+            add_point: add_point::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IParamValueQueue as Interface>::iid
+    }
+}
+
+/// Iterator over the `(sample_offset, value)` points of an [`IParamValueQueue`]
+pub struct ParamValuePoints<'a> {
+    queue: &'a IParamValueQueue,
+    index: i32,
+    count: i32,
+}
+impl Iterator for ParamValuePoints<'_> {
+    type Item = (i32, ParamValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.count {
+            let point = self.queue.get_point(self.index);
+            self.index += 1;
+            if let Ok(point) = point {
+                return Some(point);
+            }
+        }
+        None
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some((self.count - self.index) as usize))
+    }
+}
+impl FusedIterator for ParamValuePoints<'_> {}
+
+declare_class_iid!(
+    IParameterChanges,
+    0xA477_9663,
+    0x0BB6_4A56,
+    0xB443_84A8,
+    0x466F_EB9D
+);
+declare_interface!(IParameterChanges);
+interface_hierarchy!(IParameterChanges, FUnknown);
+impl IParameterChanges {
+    /// Returns count of parameter changes in the list
+    pub fn get_parameter_count(&self) -> i32 {
+        unsafe { (self.vtable().get_parameter_count)(transmute_copy(self)) }
+    }
+    /// Returns the queue at `index`
+    pub fn get_parameter_data(&self, index: i32) -> Option<InterfaceRef<'_, IParamValueQueue>> {
+        unsafe {
+            InterfaceRef::from_raw((self.vtable().get_parameter_data)(
+                transmute_copy(self),
+                index,
+            ))
+        }
+    }
+    /// Adds a new parameter queue with the given ID at the end of the list,
+    /// returns its index and the new queue (or the existing queue for `id`)
+    pub fn add_parameter_data(
+        &self,
+        id: ParamID,
+    ) -> Result<(i32, InterfaceRef<'_, IParamValueQueue>), EInterface> {
+        unsafe {
+            let (index, queue) = (self.vtable().add_parameter_data)(transmute_copy(self), id)?;
+            let queue = InterfaceRef::from_raw(queue).ok_or(EInterface::Pointer)?;
+            Ok((index, queue))
+        }
+    }
+    /// Iterates over the queues of the list
+    pub fn queues(&self) -> impl Iterator<Item = InterfaceRef<'_, IParamValueQueue>> + '_ {
+        (0..self.get_parameter_count().max(0)).filter_map(|index| self.get_parameter_data(index))
+    }
+    /// Returns the queue of the parameter `id`, if it changed in this block
+    pub fn queue(&self, id: ParamID) -> Option<InterfaceRef<'_, IParamValueQueue>> {
+        self.queues().find(|queue| queue.get_parameter_id() == id)
+    }
+    /// Iterates over all `(id, sample_offset, value)` points, queue by queue
+    pub fn points(&self) -> ParameterChangePoints<'_> {
+        ParameterChangePoints {
+            changes: self,
+            queue: None,
+            queue_index: 0,
+            point_index: 0,
+            queue_count: self.get_parameter_count().max(0),
+        }
+    }
+}
+declare_class_vtable!(
+    IParameterChanges,
+    base FUnknown,
+    function get_parameter_count: () -> i32,
+    function get_parameter_data: (index: i32) -> *mut c_void,
+    function add_parameter_data: (id: ParamID) -> Result<(i32, *mut c_void), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IParameterChangesVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IParameterChangesImpl,
+    {
+        unsafe fn get_parameter_count<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> i32
+        where
+            Identity: IParameterChangesImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IParameterChangesImpl::get_parameter_count(this)
+        }
+        unsafe fn get_parameter_data<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            index: i32,
+        ) -> *mut c_void
+        where
+            Identity: IParameterChangesImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IParameterChangesImpl::get_parameter_data(this, index)
+        }
+        unsafe fn add_parameter_data<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            id: ParamID,
+        ) -> Result<(i32, *mut c_void), EInterface>
+        where
+            Identity: IParameterChangesImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IParameterChangesImpl::add_parameter_data(this, id)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_parameter_count: get_parameter_count::<Identity, OFFSET>,
+            get_parameter_data: get_parameter_data::<Identity, OFFSET>,
+            add_parameter_data: add_parameter_data::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IParameterChanges as Interface>::iid
+    }
+}
+
+/// Iterator over the `(id, sample_offset, value)` points of an [`IParameterChanges`]
+pub struct ParameterChangePoints<'a> {
+    changes: &'a IParameterChanges,
+    queue: Option<(ParamID, InterfaceRef<'a, IParamValueQueue>, i32)>,
+    queue_index: i32,
+    point_index: i32,
+    queue_count: i32,
+}
+impl Iterator for ParameterChangePoints<'_> {
+    type Item = (ParamID, i32, ParamValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((id, queue, point_count)) = &self.queue {
+                while self.point_index < *point_count {
+                    let point = queue.get_point(self.point_index);
+                    self.point_index += 1;
+                    if let Ok((sample_offset, value)) = point {
+                        return Some((*id, sample_offset, value));
+                    }
+                }
+                self.queue = None;
+            }
+            if self.queue_index >= self.queue_count {
+                return None;
+            }
+            let queue = self.changes.get_parameter_data(self.queue_index);
+            self.queue_index += 1;
+            self.point_index = 0;
+            self.queue = queue.map(|queue| {
+                let id = queue.get_parameter_id();
+                let point_count = queue.get_point_count();
+                (id, queue, point_count)
+            });
+        }
+    }
+}
+impl FusedIterator for ParameterChangePoints<'_> {}
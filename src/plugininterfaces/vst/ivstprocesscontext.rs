@@ -0,0 +1,604 @@
+//! Transport and musical time of a processing block, see `pluginterfaces/vst/ivstprocesscontext.h`
+//!
+//! Most fields of a [`ProcessContext`] are only meaningful when the host sets the
+//! matching [`StatesAndFlags`] bit, the accessors returning an `Option` check them.
+//! The free functions convert between samples, seconds, quarter notes, bars and SMPTE
+//! time at a constant tempo.
+use super::vsttypes::{TQuarterNotes, TSamples};
+use crate::plugininterfaces::base::{macros::*, pluginreexports::*, Interface};
+use std::{mem::transmute_copy, ops, os::raw::c_void};
+
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameRateFlags {
+    NoFlags,
+
+    /// 0.1% slower than `frames_per_second` (e.g. 29.97 fps)
+    PullDownRate = 1 << 0,
+
+    /// Drop frame timecode
+    DropRate = 1 << 1,
+}
+impl ops::BitOr for FrameRateFlags {
+    type Output = u32;
+    fn bitor(self, rhs: FrameRateFlags) -> Self::Output {
+        self as u32 | rhs as u32
+    }
+}
+
+/// SMPTE frame rate
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameRate {
+    /// Nominal frame rate (e.g. 30)
+    pub frames_per_second: u32,
+    /// Combination of [`FrameRateFlags`]
+    pub flags: u32,
+}
+impl FrameRate {
+    pub fn new(frames_per_second: u32, flags: u32) -> Self {
+        Self {
+            frames_per_second,
+            flags,
+        }
+    }
+    pub fn has_flag(&self, flag: FrameRateFlags) -> bool {
+        self.flags & flag as u32 != 0
+    }
+    /// Actual number of frames per second, taking the pull down into account
+    pub fn frames_per_second_exact(&self) -> f64 {
+        let frames_per_second = self.frames_per_second as f64;
+        if self.has_flag(FrameRateFlags::PullDownRate) {
+            frames_per_second * 1000.0 / 1001.0
+        } else {
+            frames_per_second
+        }
+    }
+    /// Frame numbers skipped at the start of each minute not divisible by 10
+    fn dropped_frames(&self) -> i64 {
+        if self.has_flag(FrameRateFlags::DropRate) {
+            (self.frames_per_second as i64 / 15).max(1)
+        } else {
+            0
+        }
+    }
+}
+
+/// Chord played at the current position
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Chord {
+    /// Key note of the chord [0, 11], 0 = C
+    pub key_note: u8,
+    /// Lowest note of the chord [0, 11], 0 = C
+    pub root_note: u8,
+    /// Notes of the chord relative to the key note, bit 0 = key note, bit 1 = second ...
+    pub chord_mask: i16,
+}
+impl Chord {
+    /// Bits of `chord_mask` holding the notes
+    pub const CHORD_MASK: i16 = 0x0FFF;
+    /// Reserved bits of `chord_mask`
+    pub const RESERVED_MASK: i16 = 0xF000u16 as i16;
+
+    /// Whether the note `interval` semitones above the key note is part of the chord
+    pub fn contains(&self, interval: u8) -> bool {
+        interval < 12 && self.chord_mask & Self::CHORD_MASK & (1 << interval) != 0
+    }
+}
+
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StatesAndFlags {
+    NoFlags,
+
+    /// Transport is playing
+    Playing = 1 << 1,
+
+    /// Cycle is active
+    CycleActive = 1 << 2,
+
+    /// Transport is recording
+    Recording = 1 << 3,
+
+    /// `system_time` is valid
+    SystemTimeValid = 1 << 8,
+
+    /// `project_time_music` is valid
+    ProjectTimeMusicValid = 1 << 9,
+
+    /// `tempo` is valid
+    TempoValid = 1 << 10,
+
+    /// `bar_position_music` is valid
+    BarPositionValid = 1 << 11,
+
+    /// `cycle_start_music` and `cycle_end_music` are valid
+    CycleValid = 1 << 12,
+
+    /// `time_sig_numerator` and `time_sig_denominator` are valid
+    TimeSigValid = 1 << 13,
+
+    /// `smpte_offset_subframes` and `frame_rate` are valid
+    SmpteValid = 1 << 14,
+
+    /// `samples_to_next_clock` is valid
+    ClockValid = 1 << 15,
+
+    /// `continous_time_samples` is valid
+    ContTimeValid = 1 << 17,
+
+    /// `chord` is valid
+    ChordValid = 1 << 18,
+}
+impl ops::BitOr<StatesAndFlags> for u32 {
+    type Output = u32;
+    fn bitor(self, rhs: StatesAndFlags) -> Self::Output {
+        self | rhs as u32
+    }
+}
+impl ops::BitOr for StatesAndFlags {
+    type Output = u32;
+    fn bitor(self, rhs: StatesAndFlags) -> Self::Output {
+        self as u32 | rhs as u32
+    }
+}
+impl ops::BitOrAssign<StatesAndFlags> for u32 {
+    fn bitor_assign(&mut self, rhs: StatesAndFlags) {
+        *self = *self | rhs;
+    }
+}
+
+/// Transport state and musical position of the first sample of a processing block
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ProcessContext {
+    /// Combination of [`StatesAndFlags`]
+    pub state: u32,
+    /// Current sample rate
+    pub sample_rate: f64,
+    /// Project time in samples, always valid
+    pub project_time_samples: TSamples,
+    /// System time in nanoseconds
+    pub system_time: i64,
+    /// Time in samples that keeps running while the transport is stopped or cycling
+    pub continous_time_samples: TSamples,
+    /// Musical position in quarter notes
+    pub project_time_music: TQuarterNotes,
+    /// Position of the last bar start in quarter notes
+    pub bar_position_music: TQuarterNotes,
+    /// Cycle start in quarter notes
+    pub cycle_start_music: TQuarterNotes,
+    /// Cycle end in quarter notes
+    pub cycle_end_music: TQuarterNotes,
+    /// Tempo in quarter notes per minute
+    pub tempo: f64,
+    /// Time signature numerator (e.g. 3 for 3/4)
+    pub time_sig_numerator: i32,
+    /// Time signature denominator (e.g. 4 for 3/4)
+    pub time_sig_denominator: i32,
+    /// Musical info
+    pub chord: Chord,
+    /// SMPTE (sync) offset in subframes, 1/80 of a frame
+    pub smpte_offset_subframes: i32,
+    /// Frame rate
+    pub frame_rate: FrameRate,
+    /// MIDI clock resolution (24 per quarter note), can be negative
+    pub samples_to_next_clock: i32,
+}
+impl Default for ProcessContext {
+    fn default() -> Self {
+        Self {
+            state: StatesAndFlags::NoFlags as u32,
+            sample_rate: 44100.0,
+            project_time_samples: 0,
+            system_time: 0,
+            continous_time_samples: 0,
+            project_time_music: 0.0,
+            bar_position_music: 0.0,
+            cycle_start_music: 0.0,
+            cycle_end_music: 0.0,
+            tempo: 120.0,
+            time_sig_numerator: 4,
+            time_sig_denominator: 4,
+            chord: Chord::default(),
+            smpte_offset_subframes: 0,
+            frame_rate: FrameRate::default(),
+            samples_to_next_clock: 0,
+        }
+    }
+}
+impl ProcessContext {
+    pub fn has_state(&self, flag: StatesAndFlags) -> bool {
+        self.state & flag as u32 != 0
+    }
+    pub fn is_playing(&self) -> bool {
+        self.has_state(StatesAndFlags::Playing)
+    }
+    pub fn is_recording(&self) -> bool {
+        self.has_state(StatesAndFlags::Recording)
+    }
+    pub fn is_cycle_active(&self) -> bool {
+        self.has_state(StatesAndFlags::CycleActive)
+    }
+    fn valid<T>(&self, flag: StatesAndFlags, value: T) -> Option<T> {
+        self.has_state(flag).then_some(value)
+    }
+    pub fn system_time(&self) -> Option<i64> {
+        self.valid(StatesAndFlags::SystemTimeValid, self.system_time)
+    }
+    pub fn continuous_time_samples(&self) -> Option<TSamples> {
+        self.valid(StatesAndFlags::ContTimeValid, self.continous_time_samples)
+    }
+    pub fn project_time_music(&self) -> Option<TQuarterNotes> {
+        self.valid(
+            StatesAndFlags::ProjectTimeMusicValid,
+            self.project_time_music,
+        )
+    }
+    pub fn bar_position_music(&self) -> Option<TQuarterNotes> {
+        self.valid(StatesAndFlags::BarPositionValid, self.bar_position_music)
+    }
+    /// Cycle start and end in quarter notes
+    pub fn cycle(&self) -> Option<(TQuarterNotes, TQuarterNotes)> {
+        self.valid(
+            StatesAndFlags::CycleValid,
+            (self.cycle_start_music, self.cycle_end_music),
+        )
+    }
+    pub fn tempo(&self) -> Option<f64> {
+        self.valid(StatesAndFlags::TempoValid, self.tempo)
+            .filter(|&tempo| tempo > 0.0)
+    }
+    /// Numerator and denominator of the time signature
+    pub fn time_signature(&self) -> Option<(i32, i32)> {
+        self.valid(
+            StatesAndFlags::TimeSigValid,
+            (self.time_sig_numerator, self.time_sig_denominator),
+        )
+        .filter(|&(numerator, denominator)| numerator > 0 && denominator > 0)
+    }
+    pub fn chord(&self) -> Option<Chord> {
+        self.valid(StatesAndFlags::ChordValid, self.chord)
+    }
+    /// SMPTE offset in subframes and frame rate
+    pub fn smpte(&self) -> Option<(i32, FrameRate)> {
+        self.valid(
+            StatesAndFlags::SmpteValid,
+            (self.smpte_offset_subframes, self.frame_rate),
+        )
+    }
+    pub fn samples_to_next_clock(&self) -> Option<i32> {
+        self.valid(StatesAndFlags::ClockValid, self.samples_to_next_clock)
+    }
+
+    /// Project time in seconds
+    pub fn project_time_seconds(&self) -> f64 {
+        samples_to_seconds(self.project_time_samples as f64, self.sample_rate)
+    }
+    /// Number of samples per quarter note at the current tempo
+    pub fn samples_per_quarter(&self) -> Option<f64> {
+        Some(quarter_notes_to_samples(
+            1.0,
+            self.tempo()?,
+            self.sample_rate,
+        ))
+    }
+    /// Length of a bar in quarter notes
+    pub fn bar_length(&self) -> Option<TQuarterNotes> {
+        let (numerator, denominator) = self.time_signature()?;
+        Some(quarter_notes_per_bar(numerator, denominator))
+    }
+    /// Musical position of the sample `sample_offset` of the block
+    pub fn project_time_music_at(&self, sample_offset: i32) -> Option<TQuarterNotes> {
+        Some(
+            self.project_time_music()?
+                + samples_to_quarter_notes(sample_offset as f64, self.tempo()?, self.sample_rate),
+        )
+    }
+    /// Position inside the current bar in quarter notes
+    pub fn position_in_bar(&self) -> Option<TQuarterNotes> {
+        let bar_length = self.bar_length()?;
+        let position = self.project_time_music()? - self.bar_position_music()?;
+        Some(position.rem_euclid(bar_length))
+    }
+    /// Start of the next bar at or after the current position, in quarter notes
+    pub fn next_bar_position(&self) -> Option<TQuarterNotes> {
+        let position = self.position_in_bar()?;
+        let project_time_music = self.project_time_music()?;
+        if position == 0.0 {
+            Some(project_time_music)
+        } else {
+            Some(project_time_music - position + self.bar_length()?)
+        }
+    }
+    /// Next position at or after the current one that is a multiple of `interval`
+    /// quarter notes counted from the bar start, e.g. 0.5 for eighth notes
+    pub fn next_aligned_position(&self, interval: TQuarterNotes) -> Option<TQuarterNotes> {
+        if interval <= 0.0 {
+            return None;
+        }
+        let project_time_music = self.project_time_music()?;
+        let bar_start = project_time_music - self.position_in_bar()?;
+        let steps = ((project_time_music - bar_start) / interval).ceil();
+        Some(bar_start + steps * interval)
+    }
+    /// Offset in samples from the start of the block to the musical position `position`
+    pub fn samples_until(&self, position: TQuarterNotes) -> Option<f64> {
+        let quarter_notes = position - self.project_time_music()?;
+        Some(quarter_notes_to_samples(
+            quarter_notes,
+            self.tempo()?,
+            self.sample_rate,
+        ))
+    }
+    /// SMPTE time of the project position, including the SMPTE offset
+    pub fn smpte_time(&self) -> Option<SmpteTime> {
+        let (offset_subframes, frame_rate) = self.smpte()?;
+        let time = SmpteTime::from_samples(
+            self.project_time_samples as f64,
+            self.sample_rate,
+            frame_rate,
+        )?;
+        SmpteTime::from_subframes(
+            time.to_subframes(frame_rate) + offset_subframes as i64,
+            frame_rate,
+        )
+    }
+}
+
+/// Converts samples to seconds
+pub fn samples_to_seconds(samples: f64, sample_rate: f64) -> f64 {
+    samples / sample_rate
+}
+
+/// Converts seconds to samples
+pub fn seconds_to_samples(seconds: f64, sample_rate: f64) -> f64 {
+    seconds * sample_rate
+}
+
+/// Converts samples to quarter notes at a constant `tempo`
+pub fn samples_to_quarter_notes(samples: f64, tempo: f64, sample_rate: f64) -> TQuarterNotes {
+    samples / sample_rate * tempo / 60.0
+}
+
+/// Converts quarter notes to samples at a constant `tempo`
+pub fn quarter_notes_to_samples(quarter_notes: TQuarterNotes, tempo: f64, sample_rate: f64) -> f64 {
+    quarter_notes * 60.0 / tempo * sample_rate
+}
+
+/// Length of a bar in quarter notes, e.g. 3 for 3/4 and 3.5 for 7/8
+pub fn quarter_notes_per_bar(numerator: i32, denominator: i32) -> TQuarterNotes {
+    numerator as f64 * 4.0 / denominator as f64
+}
+
+/// Splits a musical position into a zero based bar index and the position inside that bar,
+/// for a time signature that is constant since the position 0
+pub fn quarter_notes_to_bars(
+    quarter_notes: TQuarterNotes,
+    numerator: i32,
+    denominator: i32,
+) -> (i64, TQuarterNotes) {
+    let bar_length = quarter_notes_per_bar(numerator, denominator);
+    let bar = (quarter_notes / bar_length).floor();
+    (bar as i64, quarter_notes - bar * bar_length)
+}
+
+/// Musical position of the start of the zero based bar `bar`,
+/// for a time signature that is constant since the position 0
+pub fn bars_to_quarter_notes(bar: i64, numerator: i32, denominator: i32) -> TQuarterNotes {
+    bar as f64 * quarter_notes_per_bar(numerator, denominator)
+}
+
+/// Number of subframes per SMPTE frame
+pub const SUBFRAMES_PER_FRAME: i64 = 80;
+
+/// SMPTE time code, labeled according to the frame rate of the conversion
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SmpteTime {
+    /// Whether the time is before zero
+    pub negative: bool,
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+    /// 1/80 of a frame
+    pub subframes: u32,
+}
+impl SmpteTime {
+    /// Time code of a count of subframes since zero, `None` for a zero frame rate
+    pub fn from_subframes(subframes: i64, frame_rate: FrameRate) -> Option<Self> {
+        let frames_per_second = frame_rate.frames_per_second as u64;
+        if frames_per_second == 0 {
+            return None;
+        }
+        let negative = subframes < 0;
+        // Unsigned, `i64::MIN` has no positive counterpart
+        let subframes = subframes.unsigned_abs();
+        let mut frame = subframes / SUBFRAMES_PER_FRAME as u64;
+        // Skip the dropped frame numbers
+        let dropped = frame_rate.dropped_frames() as u64;
+        if dropped > 0 {
+            let frames_per_minute = frames_per_second * 60 - dropped;
+            let frames_per_ten_minutes = frames_per_minute * 10 + dropped;
+            let ten_minutes = frame / frames_per_ten_minutes;
+            let remainder = frame % frames_per_ten_minutes;
+            frame += dropped * 9 * ten_minutes;
+            if remainder > dropped {
+                frame += dropped * ((remainder - dropped) / frames_per_minute);
+            }
+        }
+        let total_seconds = frame / frames_per_second;
+        Some(Self {
+            negative,
+            hours: (total_seconds / 3600) as u32,
+            minutes: (total_seconds / 60 % 60) as u32,
+            seconds: (total_seconds % 60) as u32,
+            frames: (frame % frames_per_second) as u32,
+            subframes: (subframes % SUBFRAMES_PER_FRAME as u64) as u32,
+        })
+    }
+    /// Count of subframes since zero
+    pub fn to_subframes(&self, frame_rate: FrameRate) -> i64 {
+        let frames_per_second = frame_rate.frames_per_second as i64;
+        let total_minutes = self.hours as i64 * 60 + self.minutes as i64;
+        let mut frame =
+            (total_minutes * 60 + self.seconds as i64) * frames_per_second + self.frames as i64;
+        frame -= frame_rate.dropped_frames() * (total_minutes - total_minutes / 10);
+        let subframes = frame * SUBFRAMES_PER_FRAME + self.subframes as i64;
+        if self.negative {
+            -subframes
+        } else {
+            subframes
+        }
+    }
+    /// Time code of a sample position, rounded to the nearest subframe
+    pub fn from_samples(samples: f64, sample_rate: f64, frame_rate: FrameRate) -> Option<Self> {
+        let frames = samples / sample_rate * frame_rate.frames_per_second_exact();
+        let subframes = (frames * SUBFRAMES_PER_FRAME as f64).round() as i64;
+        Self::from_subframes(subframes, frame_rate)
+    }
+    /// Sample position of the time code
+    pub fn to_samples(&self, sample_rate: f64, frame_rate: FrameRate) -> f64 {
+        let frames = self.to_subframes(frame_rate) as f64 / SUBFRAMES_PER_FRAME as f64;
+        frames / frame_rate.frames_per_second_exact() * sample_rate
+    }
+}
+
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProcessContextRequirementFlags {
+    NoFlags,
+    NeedSystemTime = 1 << 0,
+    NeedContinousTimeSamples = 1 << 1,
+    NeedProjectTimeMusic = 1 << 2,
+    NeedBarPositionMusic = 1 << 3,
+    NeedCycleMusic = 1 << 4,
+    NeedSamplesToNextClock = 1 << 5,
+    NeedTempo = 1 << 6,
+    NeedTimeSignature = 1 << 7,
+    NeedChord = 1 << 8,
+    NeedFrameRate = 1 << 9,
+    NeedTransportState = 1 << 10,
+}
+impl ops::BitOr<ProcessContextRequirementFlags> for u32 {
+    type Output = u32;
+    fn bitor(self, rhs: ProcessContextRequirementFlags) -> Self::Output {
+        self | rhs as u32
+    }
+}
+impl ops::BitOr for ProcessContextRequirementFlags {
+    type Output = u32;
+    fn bitor(self, rhs: ProcessContextRequirementFlags) -> Self::Output {
+        self as u32 | rhs as u32
+    }
+}
+
+declare_class_iid!(
+    IProcessContextRequirements,
+    0x2A65_4303,
+    0xEF76_4E3D,
+    0x95B5_FE83,
+    0x730E_F6D0
+);
+declare_interface!(IProcessContextRequirements);
+interface_hierarchy!(IProcessContextRequirements, FUnknown);
+impl IProcessContextRequirements {
+    /// Returns the [`ProcessContext`] fields the plug-in needs,
+    /// as a combination of [`ProcessContextRequirementFlags`]
+    pub fn get_process_context_requirements(&self) -> u32 {
+        unsafe { (self.vtable().get_process_context_requirements)(transmute_copy(self)) }
+    }
+}
+declare_class_vtable!(
+    IProcessContextRequirements,
+    base FUnknown,
+    function get_process_context_requirements: () -> u32
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IProcessContextRequirementsVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IProcessContextRequirementsImpl,
+    {
+        unsafe fn get_process_context_requirements<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> u32
+        where
+            Identity: IProcessContextRequirementsImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IProcessContextRequirementsImpl::get_process_context_requirements(this)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_process_context_requirements: get_process_context_requirements::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IProcessContextRequirements as Interface>::iid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout() {
+        assert_eq!(std::mem::size_of::<ProcessContext>(), 112);
+        assert_eq!(std::mem::offset_of!(ProcessContext, tempo), 72);
+        assert_eq!(std::mem::offset_of!(ProcessContext, frame_rate), 96);
+    }
+
+    #[test]
+    fn musical_positions() {
+        let context = ProcessContext {
+            state: StatesAndFlags::Playing
+                | StatesAndFlags::TempoValid
+                | StatesAndFlags::TimeSigValid
+                | StatesAndFlags::ProjectTimeMusicValid
+                | StatesAndFlags::BarPositionValid,
+            sample_rate: 48000.0,
+            project_time_music: 7.25,
+            bar_position_music: 6.0,
+            tempo: 120.0,
+            time_sig_numerator: 3,
+            time_sig_denominator: 4,
+            ..Default::default()
+        };
+        assert!(context.is_playing());
+        assert_eq!(context.cycle(), None);
+        assert_eq!(context.samples_per_quarter(), Some(24000.0));
+        assert_eq!(context.position_in_bar(), Some(1.25));
+        assert_eq!(context.next_bar_position(), Some(9.0));
+        assert_eq!(context.next_aligned_position(0.5), Some(7.5));
+        assert_eq!(context.samples_until(7.5), Some(6000.0));
+        assert_eq!(context.project_time_music_at(12000), Some(7.75));
+        assert_eq!(quarter_notes_to_bars(7.25, 7, 8), (2, 0.25));
+        assert_eq!(bars_to_quarter_notes(2, 7, 8), 7.0);
+    }
+
+    #[test]
+    fn smpte_conversions() {
+        let rate_25 = FrameRate::new(25, 0);
+        let time = SmpteTime::from_samples(48000.0 * 3661.5, 48000.0, rate_25).unwrap();
+        assert_eq!((time.hours, time.minutes, time.seconds), (1, 1, 1));
+        assert_eq!((time.frames, time.subframes), (12, 40));
+        assert_eq!(time.to_samples(48000.0, rate_25), 48000.0 * 3661.5);
+
+        // 29.97 drop frame: frames 0 and 1 are skipped after 00:00:59;29
+        let drop = FrameRate::new(30, FrameRateFlags::PullDownRate | FrameRateFlags::DropRate);
+        let time = SmpteTime::from_subframes(1800 * SUBFRAMES_PER_FRAME, drop).unwrap();
+        assert_eq!((time.minutes, time.seconds, time.frames), (1, 0, 2));
+        assert_eq!(time.to_subframes(drop), 1800 * SUBFRAMES_PER_FRAME);
+        let ten_minutes = SmpteTime::from_subframes(17982 * SUBFRAMES_PER_FRAME, drop).unwrap();
+        assert_eq!((ten_minutes.minutes, ten_minutes.frames), (10, 0));
+        assert_eq!(ten_minutes.to_subframes(drop), 17982 * SUBFRAMES_PER_FRAME);
+
+        // A zero sample rate saturates to the most negative subframe count
+        let time = SmpteTime::from_samples(-1.0, 0.0, rate_25).unwrap();
+        assert!(time.negative);
+        assert!(SmpteTime::from_subframes(i64::MIN, drop).unwrap().negative);
+    }
+}
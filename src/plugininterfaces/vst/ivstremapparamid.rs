@@ -0,0 +1,64 @@
+//! Parameter ID remapping for plug-ins replacing another plug-in, see
+//! `pluginterfaces/vst/ivstremapparamid.h`
+use super::vsttypes::ParamID;
+use crate::plugininterfaces::base::{macros::*, pluginreexports::*, EInterface, Interface, TUID};
+use std::{mem::transmute_copy, os::raw::c_void};
+
+declare_class_iid!(
+    IRemapParamID,
+    0x2B88_021E,
+    0x6286_B646,
+    0xB49D_F76A,
+    0x5663_061C
+);
+declare_interface!(IRemapParamID);
+interface_hierarchy!(IRemapParamID, FUnknown);
+impl IRemapParamID {
+    /// Asked by the host on the edit controller of a plug-in which replaces the plug-in
+    /// `plugin_to_replace`, e.g. its VST 2 version, for the ID of the parameter which
+    /// was `old_param_id` there. Fails with `ResultFalse` if there is no such parameter.
+    pub fn get_compatible_param_id(
+        &self,
+        plugin_to_replace: &FUID,
+        old_param_id: ParamID,
+    ) -> Result<ParamID, EInterface> {
+        unsafe {
+            (self.vtable().get_compatible_param_id)(
+                transmute_copy(self),
+                plugin_to_replace.as_ref(),
+                old_param_id,
+            )
+        }
+    }
+}
+declare_class_vtable!(
+    IRemapParamID,
+    base FUnknown,
+    function get_compatible_param_id: (plugin_to_replace_uid: &TUID, old_param_id: ParamID) -> Result<ParamID, EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IRemapParamIDVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IRemapParamIDImpl,
+    {
+        unsafe fn get_compatible_param_id<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            plugin_to_replace_uid: &TUID,
+            old_param_id: ParamID,
+        ) -> Result<ParamID, EInterface>
+        where
+            Identity: IRemapParamIDImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IRemapParamIDImpl::get_compatible_param_id(this, plugin_to_replace_uid, old_param_id)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_compatible_param_id: get_compatible_param_id::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IRemapParamID as Interface>::iid
+    }
+}
@@ -0,0 +1,548 @@
+//! Unit and program list interfaces, see `pluginterfaces/vst/ivstunits.h`
+//!
+//! Units structure the parameters of a plug-in into a tree, e.g. one unit per part
+//! of a multi-timbral instrument. A unit can have a program list, whose programs
+//! are selected through a program change parameter.
+#![allow(non_upper_case_globals)]
+
+use super::{
+    ivstcomponent::{BusDirection, MediaType},
+    vsttypes::{string128_from_str, string128_to_string, String128, UnitID},
+};
+use crate::plugininterfaces::base::{
+    ibstream::IBStream, macros::*, pluginreexports::*, EInterface, Interface,
+};
+use std::{ffi::CStr, mem::transmute_copy, os::raw::c_void};
+
+/// Parent of the root unit
+pub const kNoParentUnitId: UnitID = -1;
+
+/// Program list identifier
+pub type ProgramListID = i32;
+/// Unit without program list
+pub const kNoProgramListId: ProgramListID = -1;
+/// Program index passed to `IUnitHandler::notifyProgramListChange` when all programs changed
+pub const kAllProgramInvalid: i32 = -1;
+
+/// Basic unit description
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct UnitInfo {
+    /// Unit identifier
+    pub id: UnitID,
+    /// Identifier of the parent unit, [`kNoParentUnitId`] for the root unit
+    pub parent_unit_id: UnitID,
+    /// Name of the unit
+    pub name: String128,
+    /// Program list of the unit, [`kNoProgramListId`] if none
+    pub program_list_id: ProgramListID,
+}
+impl UnitInfo {
+    pub fn new(id: UnitID, parent_unit_id: UnitID, name: &str) -> Self {
+        Self {
+            id,
+            parent_unit_id,
+            name: string128_from_str(name),
+            program_list_id: kNoProgramListId,
+        }
+    }
+    pub fn name(&self) -> String {
+        string128_to_string(&self.name)
+    }
+    pub fn set_name(&mut self, name: &str) {
+        self.name = string128_from_str(name);
+    }
+}
+
+/// Basic program list description
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ProgramListInfo {
+    /// Program list identifier
+    pub id: ProgramListID,
+    /// Name of the program list
+    pub name: String128,
+    /// Number of programs in the list
+    pub program_count: i32,
+}
+impl ProgramListInfo {
+    pub fn new(id: ProgramListID, name: &str, program_count: i32) -> Self {
+        Self {
+            id,
+            name: string128_from_str(name),
+            program_count,
+        }
+    }
+    pub fn name(&self) -> String {
+        string128_to_string(&self.name)
+    }
+    pub fn set_name(&mut self, name: &str) {
+        self.name = string128_from_str(name);
+    }
+}
+
+declare_class_iid!(
+    IUnitInfo,
+    0x3D4B_D6B5,
+    0x913A_4FD2,
+    0xA886_E768,
+    0xA5EB_92C1
+);
+declare_interface!(IUnitInfo);
+interface_hierarchy!(IUnitInfo, FUnknown);
+impl IUnitInfo {
+    /// Returns the number of units
+    pub fn get_unit_count(&self) -> i32 {
+        unsafe { (self.vtable().get_unit_count)(transmute_copy(self)) }
+    }
+    /// Gets the info of the unit at `unit_index`
+    pub fn get_unit_info(&self, unit_index: i32) -> Result<UnitInfo, EInterface> {
+        unsafe { (self.vtable().get_unit_info)(transmute_copy(self), unit_index) }
+    }
+    /// Returns the number of program lists
+    pub fn get_program_list_count(&self) -> i32 {
+        unsafe { (self.vtable().get_program_list_count)(transmute_copy(self)) }
+    }
+    /// Gets the info of the program list at `list_index`
+    pub fn get_program_list_info(&self, list_index: i32) -> Result<ProgramListInfo, EInterface> {
+        unsafe { (self.vtable().get_program_list_info)(transmute_copy(self), list_index) }
+    }
+    /// Gets the name of a program
+    pub fn get_program_name(
+        &self,
+        list_id: ProgramListID,
+        program_index: i32,
+    ) -> Result<String, EInterface> {
+        let name = unsafe {
+            (self.vtable().get_program_name)(transmute_copy(self), list_id, program_index)
+        }?;
+        Ok(string128_to_string(&name))
+    }
+    /// Gets an attribute of a program, `attribute_id` is one of the preset attribute keys
+    pub fn get_program_info(
+        &self,
+        list_id: ProgramListID,
+        program_index: i32,
+        attribute_id: &CStr,
+    ) -> Result<String, EInterface> {
+        let value = unsafe {
+            (self.vtable().get_program_info)(
+                transmute_copy(self),
+                list_id,
+                program_index,
+                attribute_id,
+            )
+        }?;
+        Ok(string128_to_string(&value))
+    }
+    /// Checks whether a program has pitch names, fails with [`EInterface::ResultFalse`] if not
+    pub fn has_program_pitch_names(
+        &self,
+        list_id: ProgramListID,
+        program_index: i32,
+    ) -> Result<(), EInterface> {
+        unsafe {
+            (self.vtable().has_program_pitch_names)(transmute_copy(self), list_id, program_index)
+        }
+    }
+    /// Gets the name of the MIDI pitch `midi_pitch` in a program (e.g. "Snare" for a drum kit)
+    pub fn get_program_pitch_name(
+        &self,
+        list_id: ProgramListID,
+        program_index: i32,
+        midi_pitch: i16,
+    ) -> Result<String, EInterface> {
+        let name = unsafe {
+            (self.vtable().get_program_pitch_name)(
+                transmute_copy(self),
+                list_id,
+                program_index,
+                midi_pitch,
+            )
+        }?;
+        Ok(string128_to_string(&name))
+    }
+    /// Returns the unit selected in the plug-in editor
+    pub fn get_selected_unit(&self) -> UnitID {
+        unsafe { (self.vtable().get_selected_unit)(transmute_copy(self)) }
+    }
+    /// Selects a unit in the plug-in editor
+    pub fn select_unit(&self, unit_id: UnitID) -> Result<(), EInterface> {
+        unsafe { (self.vtable().select_unit)(transmute_copy(self), unit_id) }
+    }
+    /// Gets the unit a channel of a bus is routed to
+    pub fn get_unit_by_bus(
+        &self,
+        type_: MediaType,
+        dir: BusDirection,
+        bus_index: i32,
+        channel: i32,
+    ) -> Result<UnitID, EInterface> {
+        unsafe {
+            (self.vtable().get_unit_by_bus)(transmute_copy(self), type_, dir, bus_index, channel)
+        }
+    }
+    /// Loads the data of a program into a unit or a program list
+    pub fn set_unit_program_data(
+        &self,
+        list_or_unit_id: i32,
+        program_index: i32,
+        data: &IBStream,
+    ) -> Result<(), EInterface> {
+        unsafe {
+            (self.vtable().set_unit_program_data)(
+                transmute_copy(self),
+                list_or_unit_id,
+                program_index,
+                data,
+            )
+        }
+    }
+}
+declare_class_vtable!(
+    IUnitInfo,
+    base FUnknown,
+    function get_unit_count: () -> i32,
+    function get_unit_info: (unit_index: i32) -> Result<UnitInfo, EInterface>,
+    function get_program_list_count: () -> i32,
+    function get_program_list_info: (list_index: i32) -> Result<ProgramListInfo, EInterface>,
+    function get_program_name: (list_id: ProgramListID, program_index: i32) -> Result<String128, EInterface>,
+    function get_program_info: (list_id: ProgramListID, program_index: i32, attribute_id: &CStr) -> Result<String128, EInterface>,
+    function has_program_pitch_names: (list_id: ProgramListID, program_index: i32) -> Result<(), EInterface>,
+    function get_program_pitch_name: (list_id: ProgramListID, program_index: i32, midi_pitch: i16) -> Result<String128, EInterface>,
+    function get_selected_unit: () -> UnitID,
+    function select_unit: (unit_id: UnitID) -> Result<(), EInterface>,
+    function get_unit_by_bus: (type_: MediaType, dir: BusDirection, bus_index: i32, channel: i32) -> Result<UnitID, EInterface>,
+    function set_unit_program_data: (list_or_unit_id: i32, program_index: i32, data: &IBStream) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IUnitInfoVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IUnitInfoImpl,
+    {
+        unsafe fn get_unit_count<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> i32
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::get_unit_count(this)
+        }
+        unsafe fn get_unit_info<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            unit_index: i32,
+        ) -> Result<UnitInfo, EInterface>
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::get_unit_info(this, unit_index)
+        }
+        unsafe fn get_program_list_count<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> i32
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::get_program_list_count(this)
+        }
+        unsafe fn get_program_list_info<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            list_index: i32,
+        ) -> Result<ProgramListInfo, EInterface>
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::get_program_list_info(this, list_index)
+        }
+        unsafe fn get_program_name<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            list_id: ProgramListID,
+            program_index: i32,
+        ) -> Result<String128, EInterface>
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::get_program_name(this, list_id, program_index)
+        }
+        unsafe fn get_program_info<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            list_id: ProgramListID,
+            program_index: i32,
+            attribute_id: &CStr,
+        ) -> Result<String128, EInterface>
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::get_program_info(this, list_id, program_index, attribute_id)
+        }
+        unsafe fn has_program_pitch_names<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            list_id: ProgramListID,
+            program_index: i32,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::has_program_pitch_names(this, list_id, program_index)
+        }
+        unsafe fn get_program_pitch_name<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            list_id: ProgramListID,
+            program_index: i32,
+            midi_pitch: i16,
+        ) -> Result<String128, EInterface>
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::get_program_pitch_name(this, list_id, program_index, midi_pitch)
+        }
+        unsafe fn get_selected_unit<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+        ) -> UnitID
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::get_selected_unit(this)
+        }
+        unsafe fn select_unit<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            unit_id: UnitID,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::select_unit(this, unit_id)
+        }
+        unsafe fn get_unit_by_bus<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            type_: MediaType,
+            dir: BusDirection,
+            bus_index: i32,
+            channel: i32,
+        ) -> Result<UnitID, EInterface>
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::get_unit_by_bus(this, type_, dir, bus_index, channel)
+        }
+        unsafe fn set_unit_program_data<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            list_or_unit_id: i32,
+            program_index: i32,
+            data: &IBStream,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IUnitInfoImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitInfoImpl::set_unit_program_data(this, list_or_unit_id, program_index, data)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            get_unit_count: get_unit_count::<Identity, OFFSET>,
+            get_unit_info: get_unit_info::<Identity, OFFSET>,
+            get_program_list_count: get_program_list_count::<Identity, OFFSET>,
+            get_program_list_info: get_program_list_info::<Identity, OFFSET>,
+            get_program_name: get_program_name::<Identity, OFFSET>,
+            get_program_info: get_program_info::<Identity, OFFSET>,
+            has_program_pitch_names: has_program_pitch_names::<Identity, OFFSET>,
+            get_program_pitch_name: get_program_pitch_name::<Identity, OFFSET>,
+            get_selected_unit: get_selected_unit::<Identity, OFFSET>,
+            select_unit: select_unit::<Identity, OFFSET>,
+            get_unit_by_bus: get_unit_by_bus::<Identity, OFFSET>,
+            set_unit_program_data: set_unit_program_data::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IUnitInfo as Interface>::iid
+    }
+}
+
+declare_class_iid!(
+    IProgramListData,
+    0x8683_B01F,
+    0x7B35_4F70,
+    0xA265_1DEC,
+    0x353A_F4FF
+);
+declare_interface!(IProgramListData);
+interface_hierarchy!(IProgramListData, FUnknown);
+impl IProgramListData {
+    /// Checks whether the program list supports program data,
+    /// fails with [`EInterface::ResultFalse`] if not
+    pub fn program_data_supported(&self, list_id: ProgramListID) -> Result<(), EInterface> {
+        unsafe { (self.vtable().program_data_supported)(transmute_copy(self), list_id) }
+    }
+    /// Writes the data of a program to `data`
+    pub fn get_program_data(
+        &self,
+        list_id: ProgramListID,
+        program_index: i32,
+        data: &IBStream,
+    ) -> Result<(), EInterface> {
+        unsafe {
+            (self.vtable().get_program_data)(transmute_copy(self), list_id, program_index, data)
+        }
+    }
+    /// Reads the data of a program from `data`
+    pub fn set_program_data(
+        &self,
+        list_id: ProgramListID,
+        program_index: i32,
+        data: &IBStream,
+    ) -> Result<(), EInterface> {
+        unsafe {
+            (self.vtable().set_program_data)(transmute_copy(self), list_id, program_index, data)
+        }
+    }
+}
+declare_class_vtable!(
+    IProgramListData,
+    base FUnknown,
+    function program_data_supported: (list_id: ProgramListID) -> Result<(), EInterface>,
+    function get_program_data: (list_id: ProgramListID, program_index: i32, data: &IBStream) -> Result<(), EInterface>,
+    function set_program_data: (list_id: ProgramListID, program_index: i32, data: &IBStream) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IProgramListDataVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IProgramListDataImpl,
+    {
+        unsafe fn program_data_supported<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            list_id: ProgramListID,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IProgramListDataImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IProgramListDataImpl::program_data_supported(this, list_id)
+        }
+        unsafe fn get_program_data<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            list_id: ProgramListID,
+            program_index: i32,
+            data: &IBStream,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IProgramListDataImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IProgramListDataImpl::get_program_data(this, list_id, program_index, data)
+        }
+        unsafe fn set_program_data<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            list_id: ProgramListID,
+            program_index: i32,
+            data: &IBStream,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IProgramListDataImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IProgramListDataImpl::set_program_data(this, list_id, program_index, data)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            program_data_supported: program_data_supported::<Identity, OFFSET>,
+            get_program_data: get_program_data::<Identity, OFFSET>,
+            set_program_data: set_program_data::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IProgramListData as Interface>::iid
+    }
+}
+
+declare_class_iid!(
+    IUnitData,
+    0x6C38_9611,
+    0xD391_455D,
+    0xB870_B833,
+    0x94A0_EFDD
+);
+declare_interface!(IUnitData);
+interface_hierarchy!(IUnitData, FUnknown);
+impl IUnitData {
+    /// Checks whether the unit supports unit data, fails with [`EInterface::ResultFalse`] if not
+    pub fn unit_data_supported(&self, unit_id: UnitID) -> Result<(), EInterface> {
+        unsafe { (self.vtable().unit_data_supported)(transmute_copy(self), unit_id) }
+    }
+    /// Writes the data of a unit to `data`
+    pub fn get_unit_data(&self, unit_id: UnitID, data: &IBStream) -> Result<(), EInterface> {
+        unsafe { (self.vtable().get_unit_data)(transmute_copy(self), unit_id, data) }
+    }
+    /// Reads the data of a unit from `data`
+    pub fn set_unit_data(&self, unit_id: UnitID, data: &IBStream) -> Result<(), EInterface> {
+        unsafe { (self.vtable().set_unit_data)(transmute_copy(self), unit_id, data) }
+    }
+}
+declare_class_vtable!(
+    IUnitData,
+    base FUnknown,
+    function unit_data_supported: (unit_id: UnitID) -> Result<(), EInterface>,
+    function get_unit_data: (unit_id: UnitID, data: &IBStream) -> Result<(), EInterface>,
+    function set_unit_data: (unit_id: UnitID, data: &IBStream) -> Result<(), EInterface>
+);
+#[allow(clippy::multiple_bound_locations)]
+impl IUnitDataVtable {
+    pub const fn new<Identity: FUnknownImpl, const OFFSET: isize>() -> Self
+    where
+        Identity: IUnitDataImpl,
+    {
+        unsafe fn unit_data_supported<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            unit_id: UnitID,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IUnitDataImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitDataImpl::unit_data_supported(this, unit_id)
+        }
+        unsafe fn get_unit_data<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            unit_id: UnitID,
+            data: &IBStream,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IUnitDataImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitDataImpl::get_unit_data(this, unit_id, data)
+        }
+        unsafe fn set_unit_data<Identity: FUnknownImpl, const OFFSET: isize>(
+            this: *mut c_void,
+            unit_id: UnitID,
+            data: &IBStream,
+        ) -> Result<(), EInterface>
+        where
+            Identity: IUnitDataImpl,
+        {
+            let this: &Identity = &*((this as *mut *mut c_void).offset(OFFSET) as *const Identity);
+            IUnitDataImpl::set_unit_data(this, unit_id, data)
+        }
+        Self {
+            base: FUnknownVtable::new::<Identity, OFFSET>(),
+            unit_data_supported: unit_data_supported::<Identity, OFFSET>,
+            get_unit_data: get_unit_data::<Identity, OFFSET>,
+            set_unit_data: set_unit_data::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &FUID) -> bool {
+        iid == &<IUnitData as Interface>::iid
+    }
+}
@@ -1 +1,16 @@
+pub mod ivstattributes;
+pub mod ivstcomponent;
+pub mod ivsteditcontroller;
+pub mod ivstevents;
+pub mod ivsthostapplication;
+pub mod ivstmessage;
+pub mod ivstmidicontrollers;
+pub mod ivstmidilearn;
+pub mod ivstnoteexpression;
+pub mod ivstparameterchanges;
+pub mod ivstprocesscontext;
+pub mod ivstremapparamid;
+pub mod ivstunits;
 pub mod vstaudioprocessor;
+pub mod vstpresetkeys;
+pub mod vsttypes;
@@ -0,0 +1,41 @@
+//! Keys of preset attributes, see `pluginterfaces/vst/vstpresetkeys.h`
+use std::ffi::CStr;
+
+/// Data-less struct acting as namespace
+///
+/// Holds the ids of the attributes of a preset, used in the attribute list of
+/// [`IStreamAttributes`] and in the meta info of preset files
+///
+/// [`IStreamAttributes`]: super::ivstattributes::IStreamAttributes
+pub struct PresetAttributes;
+impl PresetAttributes {
+    /// Plug-in name
+    pub const PLUGIN_NAME: &'static CStr = c"PlugInName";
+    /// Plug-in category, see `PlugTypeName`
+    pub const PLUGIN_CATEGORY: &'static CStr = c"PlugInCategory";
+    /// Musical instrument type
+    pub const INSTRUMENT: &'static CStr = c"MusicalInstrument";
+    /// Musical style
+    pub const STYLE: &'static CStr = c"MusicalStyle";
+    /// Musical character
+    pub const CHARACTER: &'static CStr = c"MusicalCharacter";
+    /// Context of the state, one of [`StateType`]
+    pub const STATE_TYPE: &'static CStr = c"StateType";
+    /// Full path of the preset file
+    pub const FILE_PATH_STRING_TYPE: &'static CStr = c"FilePathString";
+    /// Preset name
+    pub const NAME: &'static CStr = c"Name";
+    /// File name of the preset, without path and extension
+    pub const FILE_NAME: &'static CStr = c"FileName";
+}
+
+/// Data-less struct acting as namespace
+///
+/// Holds the values of the [`PresetAttributes::STATE_TYPE`] attribute
+pub struct StateType;
+impl StateType {
+    /// The state is stored in or loaded from a project
+    pub const PROJECT: &'static str = "Project";
+    /// The state is the default preset of the plug-in
+    pub const DEFAULT: &'static str = "Default";
+}
@@ -0,0 +1,35 @@
+//! Basic VST types, see `pluginterfaces/vst/vsttypes.h`
+#![allow(non_upper_case_globals)]
+
+use crate::plugininterfaces::base::ustring::{self, char16};
+
+/// UTF-16 character
+pub type TChar = char16;
+/// 128 character UTF-16 string
+pub type String128 = [TChar; 128];
+
+/// Parameter identifier
+pub type ParamID = u32;
+/// Parameter value type, normalized values are in the range [0, 1]
+pub type ParamValue = f64;
+/// Unit identifier
+pub type UnitID = i32;
+/// Time expressed in samples
+pub type TSamples = i64;
+/// Musical position in quarter notes (1.0 equals one quarter note)
+pub type TQuarterNotes = f64;
+
+/// Invalid parameter identifier
+pub const kNoParamId: ParamID = 0xFFFF_FFFF;
+/// Identifier of the root unit, which every parameter belongs to by default
+pub const kRootUnitId: UnitID = 0;
+
+/// Creates a [`String128`] from `src`, truncating it if needed.
+pub fn string128_from_str(src: &str) -> String128 {
+    ustring::to_utf16_array(src)
+}
+
+/// Converts a [`String128`] to a `String`.
+pub fn string128_to_string(src: &String128) -> String {
+    ustring::string_from_utf16(src)
+}
@@ -0,0 +1,392 @@
+//! Typed reading and writing of streams, see `base/source/fstreamer.h`
+//!
+//! [`StreamReader`] and [`StreamWriter`] encode numbers in a fixed byte order,
+//! little-endian by default like the SDK's `IBStreamer`, so that states written
+//! on one platform can be read on any other:
+//!
+//! ```ignore
+//! unsafe fn set_state(&self, state: &IBStream) -> Result<(), EInterface> {
+//!     let mut reader = StreamReader::new(state);
+//!     let gain = reader.read_f64().map_err(|_| EInterface::ResultFalse)?;
+//!     let bypass = reader.read_bool().map_err(|_| EInterface::ResultFalse)?;
+//!     ...
+//! }
+//! ```
+use crate::plugininterfaces::base::{
+    ibstream::{IBStream, IStreamSeekMode},
+    EInterface,
+};
+use std::fmt;
+
+/// Byte order of the numbers in a stream
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    #[default]
+    LittleEndian,
+    BigEndian,
+}
+impl ByteOrder {
+    /// Byte order of the target platform
+    pub const NATIVE: ByteOrder = if cfg!(target_endian = "big") {
+        ByteOrder::BigEndian
+    } else {
+        ByteOrder::LittleEndian
+    };
+}
+
+/// Error while reading or writing a stream, with the stream offset of the failed value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamError {
+    /// The stream ended after `available` of `expected` bytes
+    UnexpectedEnd {
+        offset: i64,
+        expected: usize,
+        available: usize,
+    },
+    /// The stream accepted fewer bytes than written
+    ShortWrite { offset: i64 },
+    /// The string isn't valid UTF-8 or UTF-16
+    InvalidString { offset: i64 },
+    /// The stream failed
+    Stream { offset: i64, error: EInterface },
+}
+impl StreamError {
+    /// Stream offset of the value which couldn't be read or written
+    pub fn offset(&self) -> i64 {
+        match *self {
+            StreamError::UnexpectedEnd { offset, .. }
+            | StreamError::ShortWrite { offset }
+            | StreamError::InvalidString { offset }
+            | StreamError::Stream { offset, .. } => offset,
+        }
+    }
+}
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::UnexpectedEnd {
+                offset,
+                expected,
+                available,
+            } => write!(
+                f,
+                "Unexpected end of stream at offset {offset}, read {available} of {expected} bytes"
+            ),
+            StreamError::ShortWrite { offset } => {
+                write!(f, "Stream is full at offset {offset}")
+            }
+            StreamError::InvalidString { offset } => {
+                write!(f, "Invalid string at offset {offset}")
+            }
+            StreamError::Stream { offset, error } => {
+                write!(f, "Stream failed at offset {offset}: {error}")
+            }
+        }
+    }
+}
+impl std::error::Error for StreamError {}
+
+macro_rules! read_numbers {
+    ($($name:ident: $type:ty),*) => {
+        $(
+            pub fn $name(&mut self) -> Result<$type, StreamError> {
+                let mut bytes = [0; std::mem::size_of::<$type>()];
+                self.read_raw(&mut bytes)?;
+                Ok(match self.byte_order {
+                    ByteOrder::LittleEndian => <$type>::from_le_bytes(bytes),
+                    ByteOrder::BigEndian => <$type>::from_be_bytes(bytes),
+                })
+            }
+        )*
+    };
+}
+
+macro_rules! write_numbers {
+    ($($name:ident: $type:ty),*) => {
+        $(
+            pub fn $name(&mut self, value: $type) -> Result<(), StreamError> {
+                match self.byte_order {
+                    ByteOrder::LittleEndian => self.write_raw(&value.to_le_bytes()),
+                    ByteOrder::BigEndian => self.write_raw(&value.to_be_bytes()),
+                }
+            }
+        )*
+    };
+}
+
+/// Current position of `stream`, 0 if the stream can't tell
+fn start_position(stream: &IBStream) -> i64 {
+    stream.tell().unwrap_or(0)
+}
+
+/// Reads typed values from an [`IBStream`]
+pub struct StreamReader<'a> {
+    stream: &'a IBStream,
+    byte_order: ByteOrder,
+    position: i64,
+}
+impl<'a> StreamReader<'a> {
+    /// Little-endian reader at the current position of `stream`
+    pub fn new(stream: &'a IBStream) -> Self {
+        Self::with_byte_order(stream, ByteOrder::LittleEndian)
+    }
+    pub fn with_byte_order(stream: &'a IBStream, byte_order: ByteOrder) -> Self {
+        Self {
+            stream,
+            byte_order,
+            position: start_position(stream),
+        }
+    }
+    pub fn stream(&self) -> &'a IBStream {
+        self.stream
+    }
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+    pub fn set_byte_order(&mut self, byte_order: ByteOrder) {
+        self.byte_order = byte_order;
+    }
+    /// Stream offset of the next value
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+    /// Fills `buffer`, fails if the stream ends before
+    pub fn read_raw(&mut self, buffer: &mut [u8]) -> Result<(), StreamError> {
+        let offset = self.position;
+        let mut available = 0;
+        while available < buffer.len() {
+            let read = self
+                .stream
+                .read(&mut buffer[available..])
+                .map_err(|error| StreamError::Stream { offset, error })?;
+            if read == 0 {
+                break;
+            }
+            available += read;
+        }
+        self.position += available as i64;
+        if available < buffer.len() {
+            return Err(StreamError::UnexpectedEnd {
+                offset,
+                expected: buffer.len(),
+                available,
+            });
+        }
+        Ok(())
+    }
+    /// Reads a block of `size` bytes
+    ///
+    /// The block grows while it is read, so a corrupt size fails with
+    /// [`StreamError::UnexpectedEnd`] instead of allocating `size` bytes upfront.
+    pub fn read_block(&mut self, size: usize) -> Result<Vec<u8>, StreamError> {
+        const CHUNK_SIZE: usize = 64 * 1024;
+        let offset = self.position;
+        let mut block = Vec::with_capacity(size.min(CHUNK_SIZE));
+        while block.len() < size {
+            let start = block.len();
+            block.resize(start + (size - start).min(CHUNK_SIZE), 0);
+            if let Err(error) = self.read_raw(&mut block[start..]) {
+                return Err(match error {
+                    StreamError::UnexpectedEnd { available, .. } => StreamError::UnexpectedEnd {
+                        offset,
+                        expected: size,
+                        available: start + available,
+                    },
+                    error => error,
+                });
+            }
+        }
+        Ok(block)
+    }
+    read_numbers!(
+        read_i8: i8, read_u8: u8, read_i16: i16, read_u16: u16, read_i32: i32,
+        read_u32: u32, read_i64: i64, read_u64: u64, read_f32: f32, read_f64: f64
+    );
+    /// Reads a bool stored as a 16 bit integer
+    pub fn read_bool(&mut self) -> Result<bool, StreamError> {
+        Ok(self.read_i16()? != 0)
+    }
+    /// Reads a UTF-8 string written by [`StreamWriter::write_str8`]
+    pub fn read_str8(&mut self) -> Result<String, StreamError> {
+        let offset = self.position;
+        let size =
+            usize::try_from(self.read_i32()?).map_err(|_| StreamError::InvalidString { offset })?;
+        let mut bytes = self.read_block(size)?;
+        if bytes.pop().is_some_and(|terminator| terminator != 0) {
+            return Err(StreamError::InvalidString { offset });
+        }
+        String::from_utf8(bytes).map_err(|_| StreamError::InvalidString { offset })
+    }
+    /// Reads a zero terminated UTF-16 string written by [`StreamWriter::write_str16`]
+    pub fn read_str16(&mut self) -> Result<String, StreamError> {
+        let offset = self.position;
+        let mut units = Vec::new();
+        loop {
+            match self.read_u16()? {
+                0 => break,
+                unit => units.push(unit),
+            }
+        }
+        String::from_utf16(&units).map_err(|_| StreamError::InvalidString { offset })
+    }
+    /// Moves to the absolute stream offset `position`
+    pub fn seek(&mut self, position: i64) -> Result<(), StreamError> {
+        let offset = self.position;
+        self.position = self
+            .stream
+            .seek(position, IStreamSeekMode::Set)
+            .map_err(|error| StreamError::Stream { offset, error })?;
+        Ok(())
+    }
+    /// Skips `size` bytes
+    pub fn skip(&mut self, size: i64) -> Result<(), StreamError> {
+        let offset = self.position;
+        self.position = self
+            .stream
+            .seek(size, IStreamSeekMode::Cur)
+            .map_err(|error| StreamError::Stream { offset, error })?;
+        Ok(())
+    }
+}
+
+/// Writes typed values to an [`IBStream`]
+pub struct StreamWriter<'a> {
+    stream: &'a IBStream,
+    byte_order: ByteOrder,
+    position: i64,
+}
+impl<'a> StreamWriter<'a> {
+    /// Little-endian writer at the current position of `stream`
+    pub fn new(stream: &'a IBStream) -> Self {
+        Self::with_byte_order(stream, ByteOrder::LittleEndian)
+    }
+    pub fn with_byte_order(stream: &'a IBStream, byte_order: ByteOrder) -> Self {
+        Self {
+            stream,
+            byte_order,
+            position: start_position(stream),
+        }
+    }
+    pub fn stream(&self) -> &'a IBStream {
+        self.stream
+    }
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+    pub fn set_byte_order(&mut self, byte_order: ByteOrder) {
+        self.byte_order = byte_order;
+    }
+    /// Stream offset of the next value
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+    /// Writes all of `buffer`, fails if the stream accepts less
+    pub fn write_raw(&mut self, buffer: &[u8]) -> Result<(), StreamError> {
+        let offset = self.position;
+        let mut written = 0;
+        while written < buffer.len() {
+            let count = self
+                .stream
+                .write(&buffer[written..])
+                .map_err(|error| StreamError::Stream { offset, error })?;
+            if count == 0 {
+                break;
+            }
+            written += count;
+        }
+        self.position += written as i64;
+        if written < buffer.len() {
+            return Err(StreamError::ShortWrite { offset });
+        }
+        Ok(())
+    }
+    write_numbers!(
+        write_i8: i8, write_u8: u8, write_i16: i16, write_u16: u16, write_i32: i32,
+        write_u32: u32, write_i64: i64, write_u64: u64, write_f32: f32, write_f64: f64
+    );
+    /// Writes a bool as a 16 bit integer
+    pub fn write_bool(&mut self, value: bool) -> Result<(), StreamError> {
+        self.write_i16(value as i16)
+    }
+    /// Writes a UTF-8 string as its size including the zero terminator, followed by its
+    /// bytes and the terminator
+    pub fn write_str8(&mut self, value: &str) -> Result<(), StreamError> {
+        let offset = self.position;
+        let size =
+            i32::try_from(value.len() + 1).map_err(|_| StreamError::InvalidString { offset })?;
+        self.write_i32(size)?;
+        self.write_raw(value.as_bytes())?;
+        self.write_u8(0)
+    }
+    /// Writes a UTF-16 string followed by a zero terminator
+    pub fn write_str16(&mut self, value: &str) -> Result<(), StreamError> {
+        let offset = self.position;
+        if value.contains('\0') {
+            return Err(StreamError::InvalidString { offset });
+        }
+        for unit in value.encode_utf16().chain([0]) {
+            self.write_u16(unit)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publicsdk::common::memorystream::MemoryStream;
+
+    #[test]
+    fn round_trip_in_both_byte_orders() {
+        for byte_order in [ByteOrder::LittleEndian, ByteOrder::BigEndian] {
+            let memory = MemoryStream::new();
+            let stream = memory.as_interface();
+            let mut writer = StreamWriter::with_byte_order(&stream, byte_order);
+            writer.write_i8(-3).unwrap();
+            writer.write_u16(0x1234).unwrap();
+            writer.write_i64(-1 << 40).unwrap();
+            writer.write_f32(0.25).unwrap();
+            writer.write_f64(-1.5).unwrap();
+            writer.write_bool(true).unwrap();
+            writer.write_str8("Grüße").unwrap();
+            writer.write_str16("Ω voice").unwrap();
+            writer.write_raw(&[9, 8, 7]).unwrap();
+            assert_eq!(writer.position(), memory.len() as i64);
+            let expected_u16: &[u8] = match byte_order {
+                ByteOrder::LittleEndian => &[0x34, 0x12],
+                ByteOrder::BigEndian => &[0x12, 0x34],
+            };
+            assert_eq!(&memory.data()[1..3], expected_u16);
+
+            memory.set_position(0);
+            let mut reader = StreamReader::with_byte_order(&stream, byte_order);
+            assert_eq!(reader.read_i8(), Ok(-3));
+            assert_eq!(reader.read_u16(), Ok(0x1234));
+            assert_eq!(reader.read_i64(), Ok(-1 << 40));
+            assert_eq!(reader.read_f32(), Ok(0.25));
+            assert_eq!(reader.read_f64(), Ok(-1.5));
+            assert_eq!(reader.read_bool(), Ok(true));
+            assert_eq!(reader.read_str8().as_deref(), Ok("Grüße"));
+            assert_eq!(reader.read_str16().as_deref(), Ok("Ω voice"));
+            assert_eq!(reader.read_block(3), Ok(vec![9, 8, 7]));
+        }
+    }
+
+    #[test]
+    fn short_read_reports_offset() {
+        let memory = MemoryStream::from_vec(vec![1, 0, 0, 0, 2, 0]);
+        let stream = memory.as_interface();
+        let mut reader = StreamReader::new(&stream);
+        assert_eq!(reader.read_i32(), Ok(1));
+        let error = reader.read_i32().unwrap_err();
+        assert_eq!(
+            error,
+            StreamError::UnexpectedEnd {
+                offset: 4,
+                expected: 4,
+                available: 2
+            }
+        );
+        assert_eq!(error.offset(), 4);
+    }
+}
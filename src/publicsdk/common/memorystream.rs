@@ -0,0 +1,230 @@
+//! In-memory stream, see `public.sdk/source/common/memorystream.h`
+//!
+//! Used by hosts to pass states between components and controllers, and to
+//! read and write preset files.
+use crate::plugininterfaces::base::{
+    ibstream::{
+        IBStream, IBStreamImpl, IBStreamVtable, ISizeableStream, ISizeableStreamImpl,
+        ISizeableStreamVtable, IStreamSeekMode,
+    },
+    pluginreexports::*,
+    EInterface, Interface, InterfaceRef,
+};
+use std::{
+    cell::{Cell, Ref, RefCell},
+    os::raw::c_void,
+};
+
+static MEMORY_STREAM_VTABLE: IBStreamVtable = IBStreamVtable::new::<MemoryStream, 0>();
+static MEMORY_STREAM_SIZEABLE_VTABLE: ISizeableStreamVtable =
+    ISizeableStreamVtable::new::<MemoryStream, -1>();
+
+/// Growable stream of bytes
+///
+/// Writing past the end extends the stream, seeking past the end is allowed and
+/// the gap is filled with zeros on the next write.
+#[repr(C)]
+pub struct MemoryStream {
+    vtable: &'static IBStreamVtable,
+    sizeable_vtable: &'static ISizeableStreamVtable,
+    data: RefCell<Vec<u8>>,
+    position: Cell<usize>,
+}
+impl MemoryStream {
+    pub fn new() -> Self {
+        Self::from_vec(Vec::new())
+    }
+    /// Stream over `data`, positioned at the start
+    pub fn from_vec(data: Vec<u8>) -> Self {
+        Self {
+            vtable: &MEMORY_STREAM_VTABLE,
+            sizeable_vtable: &MEMORY_STREAM_SIZEABLE_VTABLE,
+            data: RefCell::new(data),
+            position: Cell::new(0),
+        }
+    }
+    pub fn data(&self) -> Ref<'_, [u8]> {
+        Ref::map(self.data.borrow(), Vec::as_slice)
+    }
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data.into_inner()
+    }
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+    pub fn position(&self) -> usize {
+        self.position.get()
+    }
+    pub fn set_position(&self, position: usize) {
+        self.position.set(position);
+    }
+    /// Removes all data and moves to the start
+    pub fn clear(&mut self) {
+        self.data.get_mut().clear();
+        self.position.set(0);
+    }
+    pub fn as_interface(&self) -> InterfaceRef<'_, IBStream> {
+        unsafe { InterfaceRef::from_raw(self as *const Self as *mut c_void).unwrap() }
+    }
+    pub fn as_sizeable(&self) -> InterfaceRef<'_, ISizeableStream> {
+        unsafe { InterfaceRef::from_raw(&self.sizeable_vtable as *const _ as *mut c_void).unwrap() }
+    }
+}
+impl Default for MemoryStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl FUnknownImpl for MemoryStream {
+    type Impl = Self;
+
+    fn get_impl(&self) -> &Self::Impl {
+        self
+    }
+    unsafe fn query_interface(
+        &self,
+        iid: *const FUID,
+        interface: *mut *mut c_void,
+    ) -> Result<(), EInterface> {
+        if interface.is_null() {
+            return Err(EInterface::Pointer);
+        }
+        if IBStreamVtable::matches(&*iid) || *iid == <FUnknown as Interface>::iid {
+            *interface = self as *const Self as *mut c_void;
+            Ok(())
+        } else if ISizeableStreamVtable::matches(&*iid) {
+            *interface = &self.sizeable_vtable as *const _ as *mut c_void;
+            Ok(())
+        } else {
+            *interface = std::ptr::null_mut();
+            Err(EInterface::BadQuery)
+        }
+    }
+    fn add_ref(&self) -> u32 {
+        1
+    }
+    unsafe fn release(_self: *mut Self) -> u32 {
+        1
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IBStreamImpl for MemoryStream {
+    unsafe fn read(&self, buffer: *mut u8, num_bytes: i32) -> Result<i32, EInterface> {
+        if buffer.is_null() || num_bytes < 0 {
+            return Err(EInterface::InvalidArgument);
+        }
+        let data = self.data.borrow();
+        let position = self.position.get().min(data.len());
+        let count = (num_bytes as usize).min(data.len() - position);
+        std::ptr::copy_nonoverlapping(data[position..].as_ptr(), buffer, count);
+        self.position.set(position + count);
+        Ok(count as i32)
+    }
+    unsafe fn write(&self, buffer: *const u8, num_bytes: i32) -> Result<i32, EInterface> {
+        if buffer.is_null() || num_bytes < 0 {
+            return Err(EInterface::InvalidArgument);
+        }
+        let mut data = self.data.borrow_mut();
+        let position = self.position.get();
+        let end = position
+            .checked_add(num_bytes as usize)
+            .ok_or(EInterface::InvalidArgument)?;
+        if data.len() < end {
+            // A position far past the end would zero-fill a huge gap
+            let additional = end - data.len();
+            data.try_reserve(additional)
+                .map_err(|_| EInterface::OutOfMemory)?;
+            data.resize(end, 0);
+        }
+        std::ptr::copy_nonoverlapping(buffer, data[position..].as_mut_ptr(), num_bytes as usize);
+        self.position.set(end);
+        Ok(num_bytes)
+    }
+    unsafe fn seek(&self, pos: i64, mode: i32) -> Result<i64, EInterface> {
+        let origin = match IStreamSeekMode::from_raw(mode) {
+            Some(IStreamSeekMode::Set) => 0,
+            Some(IStreamSeekMode::Cur) => self.position.get() as i64,
+            Some(IStreamSeekMode::End) => self.len() as i64,
+            None => return Err(EInterface::InvalidArgument),
+        };
+        let position = origin
+            .checked_add(pos)
+            .filter(|position| *position >= 0)
+            .ok_or(EInterface::InvalidArgument)?;
+        self.position
+            .set(usize::try_from(position).map_err(|_| EInterface::InvalidArgument)?);
+        Ok(position)
+    }
+    unsafe fn tell(&self) -> Result<i64, EInterface> {
+        Ok(self.position.get() as i64)
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl ISizeableStreamImpl for MemoryStream {
+    unsafe fn get_stream_size(&self) -> Result<i64, EInterface> {
+        Ok(self.len() as i64)
+    }
+    unsafe fn set_stream_size(&self, size: i64) -> Result<(), EInterface> {
+        let size = usize::try_from(size).map_err(|_| EInterface::InvalidArgument)?;
+        let mut data = self.data.borrow_mut();
+        if size > data.len() {
+            let additional = size - data.len();
+            data.try_reserve(additional)
+                .map_err(|_| EInterface::OutOfMemory)?;
+        }
+        data.resize(size, 0);
+        Ok(())
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    #[test]
+    fn std_io_through_the_interface() {
+        let memory = MemoryStream::new();
+        let stream = memory.as_interface();
+        let mut io = stream.io();
+        io.write_all(b"state").unwrap();
+        assert_eq!(io.seek(SeekFrom::Current(2)).unwrap(), 7);
+        io.write_all(&[1]).unwrap();
+        assert_eq!(&*memory.data(), b"state\0\0\x01");
+
+        io.seek(SeekFrom::Start(1)).unwrap();
+        let mut buffer = [0; 4];
+        io.read_exact(&mut buffer).unwrap();
+        assert_eq!(&buffer, b"tate");
+        assert_eq!(stream.tell(), Ok(5));
+        assert!(io.seek(SeekFrom::End(-9)).is_err());
+        io.seek(SeekFrom::End(0)).unwrap();
+        assert_eq!(io.read(&mut buffer).unwrap(), 0);
+
+        let sizeable = stream.cast::<ISizeableStream>().unwrap();
+        assert_eq!(sizeable.get_stream_size(), Ok(8));
+        sizeable.set_stream_size(3).unwrap();
+        assert_eq!(memory.into_inner(), b"sta");
+    }
+
+    #[test]
+    fn huge_gaps_fail_instead_of_aborting() {
+        let memory = MemoryStream::from_vec(b"data".to_vec());
+        let stream = memory.as_interface();
+        stream.seek(i64::MAX, IStreamSeekMode::Set).unwrap();
+        assert_eq!(stream.write(&[1]), Err(EInterface::OutOfMemory));
+        let sizeable = stream.cast::<ISizeableStream>().unwrap();
+        assert_eq!(
+            sizeable.set_stream_size(i64::MAX),
+            Err(EInterface::OutOfMemory)
+        );
+        assert_eq!(&*memory.data(), b"data");
+    }
+}
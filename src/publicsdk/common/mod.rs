@@ -0,0 +1,2 @@
+pub mod fstreamer;
+pub mod memorystream;
@@ -0,0 +1,166 @@
+//! Host stream telling the plug-in about the context of a state
+//!
+//! [`AttributedStream`] is a [`MemoryStream`] which also implements
+//! [`IStreamAttributes`], so that `setState` and `getState` can tell a project
+//! from a preset and see the name of the preset:
+//!
+//! ```ignore
+//! let stream = AttributedStream::new(MemoryStream::from_vec(data))
+//!     .with_state_type(StateType::PROJECT);
+//! component.set_state(&stream.as_interface())?;
+//! ```
+use super::hostclasses::HostAttributeList;
+use crate::{
+    plugininterfaces::{
+        base::{
+            ibstream::{IBStream, IBStreamImpl, IBStreamVtable},
+            pluginreexports::*,
+            EInterface, Interface, InterfaceRef,
+        },
+        vst::{
+            ivstattributes::{IStreamAttributes, IStreamAttributesImpl, IStreamAttributesVtable},
+            vstpresetkeys::PresetAttributes,
+            vsttypes::{string128_from_str, String128},
+        },
+    },
+    publicsdk::common::memorystream::MemoryStream,
+};
+use std::{os::raw::c_void, path::Path};
+
+static ATTRIBUTED_STREAM_VTABLE: IBStreamVtable = IBStreamVtable::new::<AttributedStream, 0>();
+static ATTRIBUTED_STREAM_ATTRIBUTES_VTABLE: IStreamAttributesVtable =
+    IStreamAttributesVtable::new::<AttributedStream, -1>();
+
+/// Memory stream with a file name and a list of [`PresetAttributes`]
+#[repr(C)]
+pub struct AttributedStream {
+    vtable: &'static IBStreamVtable,
+    attributes_vtable: &'static IStreamAttributesVtable,
+    stream: MemoryStream,
+    file_name: String,
+    attributes: HostAttributeList,
+}
+impl AttributedStream {
+    pub fn new(stream: MemoryStream) -> Self {
+        Self {
+            vtable: &ATTRIBUTED_STREAM_VTABLE,
+            attributes_vtable: &ATTRIBUTED_STREAM_ATTRIBUTES_VTABLE,
+            stream,
+            file_name: String::new(),
+            attributes: HostAttributeList::new(),
+        }
+    }
+    /// Sets the [`PresetAttributes::STATE_TYPE`], one of `StateType`
+    pub fn with_state_type(self, state_type: &str) -> Self {
+        self.set_attribute(PresetAttributes::STATE_TYPE, state_type);
+        self
+    }
+    /// Sets the file name and the [`PresetAttributes::FILE_PATH_STRING_TYPE`] of a preset file
+    pub fn with_file_path(mut self, path: &Path) -> Self {
+        self.file_name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.set_attribute(
+            PresetAttributes::FILE_PATH_STRING_TYPE,
+            &path.to_string_lossy(),
+        );
+        self
+    }
+    /// Sets the [`PresetAttributes::NAME`] of a preset
+    pub fn with_name(self, name: &str) -> Self {
+        self.set_attribute(PresetAttributes::NAME, name);
+        self
+    }
+    fn set_attribute(&self, id: &std::ffi::CStr, value: &str) {
+        // The host attribute list accepts all strings
+        let _ = self.attributes.as_interface().set_string(id, value);
+    }
+    /// File name without extension, reported by [`IStreamAttributes::get_file_name`]
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+    pub fn set_file_name(&mut self, file_name: &str) {
+        self.file_name = file_name.to_owned();
+    }
+    pub fn attributes(&self) -> &HostAttributeList {
+        &self.attributes
+    }
+    pub fn stream(&self) -> &MemoryStream {
+        &self.stream
+    }
+    pub fn into_inner(self) -> MemoryStream {
+        self.stream
+    }
+    pub fn as_interface(&self) -> InterfaceRef<'_, IBStream> {
+        unsafe { InterfaceRef::from_raw(self as *const Self as *mut c_void).unwrap() }
+    }
+    pub fn as_stream_attributes(&self) -> InterfaceRef<'_, IStreamAttributes> {
+        unsafe {
+            InterfaceRef::from_raw(&self.attributes_vtable as *const _ as *mut c_void).unwrap()
+        }
+    }
+}
+impl FUnknownImpl for AttributedStream {
+    type Impl = Self;
+
+    fn get_impl(&self) -> &Self::Impl {
+        self
+    }
+    unsafe fn query_interface(
+        &self,
+        iid: *const FUID,
+        interface: *mut *mut c_void,
+    ) -> Result<(), EInterface> {
+        if interface.is_null() {
+            return Err(EInterface::Pointer);
+        }
+        if IBStreamVtable::matches(&*iid) || *iid == <FUnknown as Interface>::iid {
+            *interface = self as *const Self as *mut c_void;
+            Ok(())
+        } else if IStreamAttributesVtable::matches(&*iid) {
+            *interface = &self.attributes_vtable as *const _ as *mut c_void;
+            Ok(())
+        } else {
+            *interface = std::ptr::null_mut();
+            Err(EInterface::BadQuery)
+        }
+    }
+    fn add_ref(&self) -> u32 {
+        1
+    }
+    unsafe fn release(_self: *mut Self) -> u32 {
+        1
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IBStreamImpl for AttributedStream {
+    unsafe fn read(&self, buffer: *mut u8, num_bytes: i32) -> Result<i32, EInterface> {
+        IBStreamImpl::read(&self.stream, buffer, num_bytes)
+    }
+    unsafe fn write(&self, buffer: *const u8, num_bytes: i32) -> Result<i32, EInterface> {
+        IBStreamImpl::write(&self.stream, buffer, num_bytes)
+    }
+    unsafe fn seek(&self, pos: i64, mode: i32) -> Result<i64, EInterface> {
+        IBStreamImpl::seek(&self.stream, pos, mode)
+    }
+    unsafe fn tell(&self) -> Result<i64, EInterface> {
+        IBStreamImpl::tell(&self.stream)
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IStreamAttributesImpl for AttributedStream {
+    unsafe fn get_file_name(&self) -> Result<String128, EInterface> {
+        if self.file_name.is_empty() {
+            return Err(EInterface::ResultFalse);
+        }
+        Ok(string128_from_str(&self.file_name))
+    }
+    unsafe fn get_attributes(&self) -> *mut c_void {
+        &self.attributes as *const HostAttributeList as *mut c_void
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 1;
+}
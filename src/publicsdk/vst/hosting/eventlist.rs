@@ -0,0 +1,257 @@
+//! Host implementation of an event list, see `public.sdk/source/vst/hosting/eventlist.h`
+//!
+//! The list is preallocated on construction and never allocates afterwards.
+//! Events are kept sorted by sample offset, data and texts are copied into
+//! the list so they stay valid until it is cleared.
+use crate::plugininterfaces::{
+    base::{pluginreexports::*, EInterface, Interface, InterfaceRef},
+    vst::{
+        ivstevents::{Event, EventKind, IEventList, IEventListImpl, IEventListVtable},
+        vsttypes::TChar,
+    },
+};
+use std::{cell::RefCell, os::raw::c_void};
+
+static EVENT_LIST_VTABLE: IEventListVtable = IEventListVtable::new::<EventList, 0>();
+
+/// List of events for one processing block, sorted by sample offset
+///
+/// Holds up to `max_events` events. Data events share a buffer of `max_data_size`
+/// bytes, texts share a buffer of `max_data_size` characters.
+#[repr(C)]
+pub struct EventList {
+    vtable: &'static IEventListVtable,
+    events: RefCell<Vec<Event>>,
+    bytes: RefCell<Vec<u8>>,
+    texts: RefCell<Vec<TChar>>,
+}
+impl EventList {
+    pub fn new(max_events: usize, max_data_size: usize) -> Self {
+        Self {
+            vtable: &EVENT_LIST_VTABLE,
+            events: RefCell::new(Vec::with_capacity(max_events)),
+            bytes: RefCell::new(Vec::with_capacity(max_data_size)),
+            texts: RefCell::new(Vec::with_capacity(max_data_size)),
+        }
+    }
+    /// Removes all events, keeping their storage
+    pub fn clear(&mut self) {
+        self.events.get_mut().clear();
+        self.bytes.get_mut().clear();
+        self.texts.get_mut().clear();
+    }
+    pub fn len(&self) -> usize {
+        self.events.borrow().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.events.borrow().is_empty()
+    }
+    pub fn event(&self, index: usize) -> Option<Event> {
+        self.events.borrow().get(index).copied()
+    }
+    /// Adds `kind` on `bus_index` at `sample_offset`, returns the index of the event
+    pub fn add(
+        &self,
+        bus_index: i32,
+        sample_offset: i32,
+        kind: EventKind<'_>,
+    ) -> Result<usize, EInterface> {
+        unsafe { self.add_event(&Event::new(bus_index, sample_offset, kind)) }
+    }
+    /// Inserts a copy of `event` after the events with the same or an earlier sample offset,
+    /// returns its index. Fails if the list or its data buffers are full.
+    ///
+    /// # Safety
+    ///
+    /// The data and text pointers of `event` must be valid.
+    pub unsafe fn add_event(&self, event: &Event) -> Result<usize, EInterface> {
+        let mut events = self.events.borrow_mut();
+        if events.len() == events.capacity() {
+            return Err(EInterface::OutOfMemory);
+        }
+        let mut event = *event;
+        let kind = event.kind();
+        // Unknown event types are copied as they are
+        if let Some(kind) = kind {
+            event.set_kind(self.copy_payload(kind)?);
+        }
+        let index = events.partition_point(|other| other.sample_offset <= event.sample_offset);
+        events.insert(index, event);
+        Ok(index)
+    }
+    /// Copies data and text of `kind` into the buffers of the list
+    fn copy_payload<'a>(&'a self, kind: EventKind<'_>) -> Result<EventKind<'a>, EInterface> {
+        Ok(match kind {
+            EventKind::Data { type_, bytes } => EventKind::Data {
+                type_,
+                bytes: self.copy_bytes(bytes)?,
+            },
+            EventKind::NoteExpressionText {
+                type_id,
+                note_id,
+                text,
+            } => EventKind::NoteExpressionText {
+                type_id,
+                note_id,
+                text: self.copy_text(text)?,
+            },
+            EventKind::Chord {
+                root,
+                bass_note,
+                mask,
+                text,
+            } => EventKind::Chord {
+                root,
+                bass_note,
+                mask,
+                text: self.copy_text(text)?,
+            },
+            EventKind::Scale { root, mask, text } => EventKind::Scale {
+                root,
+                mask,
+                text: self.copy_text(text)?,
+            },
+            EventKind::NoteOn(event) => EventKind::NoteOn(event),
+            EventKind::NoteOff(event) => EventKind::NoteOff(event),
+            EventKind::PolyPressure(event) => EventKind::PolyPressure(event),
+            EventKind::NoteExpressionValue(event) => EventKind::NoteExpressionValue(event),
+            EventKind::LegacyMIDICCOut(event) => EventKind::LegacyMIDICCOut(event),
+        })
+    }
+    fn copy_bytes<'a>(&'a self, src: &[u8]) -> Result<&'a [u8], EInterface> {
+        let mut bytes = self.bytes.borrow_mut();
+        let start = bytes.len();
+        if bytes.capacity() - start < src.len() {
+            return Err(EInterface::OutOfMemory);
+        }
+        bytes.extend_from_slice(src);
+        // The buffer never reallocates, so the copy lives as long as the list isn't cleared
+        Ok(unsafe { std::slice::from_raw_parts(bytes.as_ptr().add(start), src.len()) })
+    }
+    fn copy_text<'a>(&'a self, src: &[TChar]) -> Result<&'a [TChar], EInterface> {
+        let mut texts = self.texts.borrow_mut();
+        let start = texts.len();
+        if texts.capacity() - start < src.len() + 1 {
+            return Err(EInterface::OutOfMemory);
+        }
+        texts.extend_from_slice(src);
+        texts.push(0);
+        Ok(unsafe { std::slice::from_raw_parts(texts.as_ptr().add(start), src.len()) })
+    }
+    /// View of this list as an interface, valid as long as the list is borrowed
+    pub fn as_interface(&self) -> InterfaceRef<'_, IEventList> {
+        unsafe { InterfaceRef::from_raw(self as *const Self as *mut c_void).unwrap() }
+    }
+}
+impl FUnknownImpl for EventList {
+    type Impl = Self;
+
+    fn get_impl(&self) -> &Self::Impl {
+        self
+    }
+    unsafe fn query_interface(
+        &self,
+        iid: *const FUID,
+        interface: *mut *mut c_void,
+    ) -> Result<(), EInterface> {
+        if interface.is_null() {
+            return Err(EInterface::Pointer);
+        }
+        if IEventListVtable::matches(&*iid) || *iid == <FUnknown as Interface>::iid {
+            *interface = self as *const Self as *mut c_void;
+            Ok(())
+        } else {
+            *interface = std::ptr::null_mut();
+            Err(EInterface::BadQuery)
+        }
+    }
+    fn add_ref(&self) -> u32 {
+        1
+    }
+    unsafe fn release(_self: *mut Self) -> u32 {
+        1
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IEventListImpl for EventList {
+    unsafe fn get_event_count(&self) -> i32 {
+        self.len() as i32
+    }
+    unsafe fn get_event(&self, index: i32, event: *mut Event) -> Result<(), EInterface> {
+        if event.is_null() {
+            return Err(EInterface::Pointer);
+        }
+        let index = usize::try_from(index).map_err(|_| EInterface::InvalidArgument)?;
+        *event = self.event(index).ok_or(EInterface::InvalidArgument)?;
+        Ok(())
+    }
+    unsafe fn add_event(&self, event: *mut Event) -> Result<(), EInterface> {
+        let event = event.as_ref().ok_or(EInterface::Pointer)?;
+        EventList::add_event(self, event).map(|_| ())
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugininterfaces::vst::ivstevents::{NoteOffEvent, NoteOnEvent};
+
+    fn note_on(pitch: i16) -> EventKind<'static> {
+        EventKind::NoteOn(NoteOnEvent {
+            channel: 0,
+            pitch,
+            tuning: 0.0,
+            velocity: 1.0,
+            length: 0,
+            note_id: -1,
+        })
+    }
+
+    #[test]
+    fn sorted_and_copied() {
+        let mut list = EventList::new(3, 4);
+        list.add(0, 64, note_on(60)).unwrap();
+        list.add(0, 0, note_on(62)).unwrap();
+        {
+            let sysex = vec![0xF0, 0x01, 0xF7];
+            let interface = list.as_interface();
+            interface.add(0, 64, EventKind::sysex(&sysex)).unwrap();
+        }
+        assert_eq!(list.add(0, 0, note_on(64)), Err(EInterface::OutOfMemory));
+
+        let interface = list.as_interface();
+        let events: Vec<_> = interface.events().collect();
+        assert_eq!(
+            events
+                .iter()
+                .map(|(event, _)| event.sample_offset)
+                .collect::<Vec<_>>(),
+            [0, 64, 64]
+        );
+        assert_eq!(events[0].1, note_on(62));
+        assert_eq!(events[1].1, note_on(60));
+        assert_eq!(events[2].1, EventKind::sysex(&[0xF0, 0x01, 0xF7]));
+
+        list.clear();
+        let text = [0x43u16, 0x44, 0x45, 0x46];
+        let chord = EventKind::Chord {
+            root: 0,
+            bass_note: 0,
+            mask: 1,
+            text: &text,
+        };
+        // Four characters and the terminating zero don't fit
+        assert_eq!(list.add(0, 0, chord), Err(EInterface::OutOfMemory));
+        let release = EventKind::NoteOff(NoteOffEvent {
+            channel: 0,
+            pitch: 60,
+            velocity: 0.0,
+            note_id: -1,
+            tuning: 0.0,
+        });
+        assert_eq!(list.add(0, 10, release), Ok(0));
+    }
+}
@@ -0,0 +1,464 @@
+//! Host implementations of SDK interfaces, see `public.sdk/source/vst/hosting/hostclasses.h`
+//!
+//! Objects created with `new` are owned by Rust code and ignore the reference
+//! counting of the interfaces, the ones created with `new_shared` or handed out by
+//! [`HostApplication`] are freed when the last reference is released.
+use crate::plugininterfaces::{
+    base::{
+        pluginreexports::*,
+        ustring::{string_from_utf16, utf16_len},
+        EInterface, Interface, InterfaceRef, TUID,
+    },
+    vst::{
+        ivstattributes::{AttrID, IAttributeList, IAttributeListImpl, IAttributeListVtable},
+        ivsthostapplication::{IHostApplication, IHostApplicationImpl, IHostApplicationVtable},
+        ivstmessage::{IMessage, IMessageImpl, IMessageVtable},
+        vsttypes::{string128_from_str, String128, TChar},
+    },
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ffi::{c_char, CStr, CString},
+    os::raw::c_void,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+static HOST_ATTRIBUTE_LIST_VTABLE: IAttributeListVtable =
+    IAttributeListVtable::new::<HostAttributeList, 0>();
+static HOST_MESSAGE_VTABLE: IMessageVtable = IMessageVtable::new::<HostMessage, 0>();
+static HOST_APPLICATION_VTABLE: IHostApplicationVtable =
+    IHostApplicationVtable::new::<HostApplication, 0>();
+
+/// Reference count of an object, `None` if it is owned by Rust code
+type RefCount = Option<AtomicU32>;
+
+fn add_ref(ref_count: &RefCount) -> u32 {
+    ref_count
+        .as_ref()
+        .map_or(1, |count| count.fetch_add(1, Ordering::AcqRel) + 1)
+}
+
+/// Releases a reference, frees the boxed `object` with the last one
+///
+/// # Safety
+///
+/// `object` must have been created by `Box::into_raw` if `ref_count(object)` is `Some`.
+unsafe fn release<T>(object: *mut T, ref_count: impl FnOnce(&T) -> &RefCount) -> u32 {
+    let Some(count) = ref_count(&*object) else {
+        return 1;
+    };
+    let count = count.fetch_sub(1, Ordering::AcqRel) - 1;
+    if count == 0 {
+        drop(Box::from_raw(object));
+    }
+    count
+}
+
+/// Value of an attribute
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeValue {
+    Int(i64),
+    Float(f64),
+    /// UTF-16 string without terminator
+    String(Vec<TChar>),
+    Binary(Vec<u8>),
+}
+
+/// Attribute list storing copies of the values
+#[repr(C)]
+pub struct HostAttributeList {
+    vtable: &'static IAttributeListVtable,
+    values: RefCell<HashMap<CString, AttributeValue>>,
+    ref_count: RefCount,
+}
+impl HostAttributeList {
+    pub fn new() -> Self {
+        Self {
+            vtable: &HOST_ATTRIBUTE_LIST_VTABLE,
+            values: RefCell::new(HashMap::new()),
+            ref_count: None,
+        }
+    }
+    /// Reference counted list, freed when the returned interface and all other
+    /// references are released
+    pub fn new_shared() -> IAttributeList {
+        let list = Box::new(Self {
+            ref_count: Some(AtomicU32::new(1)),
+            ..Self::new()
+        });
+        unsafe { IAttributeList::from_raw(Box::into_raw(list) as *mut c_void) }
+    }
+    pub fn value(&self, id: AttrID) -> Option<AttributeValue> {
+        self.values.borrow().get(id).cloned()
+    }
+    /// String attribute converted from UTF-16
+    pub fn string(&self, id: AttrID) -> Option<String> {
+        match self.values.borrow().get(id) {
+            Some(AttributeValue::String(value)) => Some(string_from_utf16(value)),
+            _ => None,
+        }
+    }
+    pub fn set_value(&self, id: AttrID, value: AttributeValue) {
+        self.values.borrow_mut().insert(id.to_owned(), value);
+    }
+    pub fn remove(&self, id: AttrID) -> Option<AttributeValue> {
+        self.values.borrow_mut().remove(id)
+    }
+    pub fn len(&self) -> usize {
+        self.values.borrow().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.values.borrow().is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.values.get_mut().clear();
+    }
+    pub fn as_interface(&self) -> InterfaceRef<'_, IAttributeList> {
+        unsafe { InterfaceRef::from_raw(self as *const Self as *mut c_void).unwrap() }
+    }
+}
+impl Default for HostAttributeList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl FUnknownImpl for HostAttributeList {
+    type Impl = Self;
+
+    fn get_impl(&self) -> &Self::Impl {
+        self
+    }
+    unsafe fn query_interface(
+        &self,
+        iid: *const FUID,
+        interface: *mut *mut c_void,
+    ) -> Result<(), EInterface> {
+        if interface.is_null() {
+            return Err(EInterface::Pointer);
+        }
+        if IAttributeListVtable::matches(&*iid) || *iid == <FUnknown as Interface>::iid {
+            self.add_ref();
+            *interface = self as *const Self as *mut c_void;
+            Ok(())
+        } else {
+            *interface = std::ptr::null_mut();
+            Err(EInterface::BadQuery)
+        }
+    }
+    fn add_ref(&self) -> u32 {
+        add_ref(&self.ref_count)
+    }
+    unsafe fn release(self_: *mut Self) -> u32 {
+        release(self_, |list| &list.ref_count)
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IAttributeListImpl for HostAttributeList {
+    unsafe fn set_int(&self, id: AttrID, value: i64) -> Result<(), EInterface> {
+        self.set_value(id, AttributeValue::Int(value));
+        Ok(())
+    }
+    unsafe fn get_int(&self, id: AttrID) -> Result<i64, EInterface> {
+        match self.values.borrow().get(id) {
+            Some(AttributeValue::Int(value)) => Ok(*value),
+            _ => Err(EInterface::ResultFalse),
+        }
+    }
+    unsafe fn set_float(&self, id: AttrID, value: f64) -> Result<(), EInterface> {
+        self.set_value(id, AttributeValue::Float(value));
+        Ok(())
+    }
+    unsafe fn get_float(&self, id: AttrID) -> Result<f64, EInterface> {
+        match self.values.borrow().get(id) {
+            Some(AttributeValue::Float(value)) => Ok(*value),
+            _ => Err(EInterface::ResultFalse),
+        }
+    }
+    unsafe fn set_string(&self, id: AttrID, string: *const TChar) -> Result<(), EInterface> {
+        if string.is_null() {
+            return Err(EInterface::InvalidArgument);
+        }
+        let mut len = 0;
+        while *string.add(len) != 0 {
+            len += 1;
+        }
+        let string = std::slice::from_raw_parts(string, len).to_vec();
+        self.set_value(id, AttributeValue::String(string));
+        Ok(())
+    }
+    unsafe fn get_string(
+        &self,
+        id: AttrID,
+        string: *mut TChar,
+        size_in_bytes: u32,
+    ) -> Result<(), EInterface> {
+        let capacity = size_in_bytes as usize / std::mem::size_of::<TChar>();
+        if string.is_null() || capacity == 0 {
+            return Err(EInterface::InvalidArgument);
+        }
+        let values = self.values.borrow();
+        let Some(AttributeValue::String(value)) = values.get(id) else {
+            return Err(EInterface::ResultFalse);
+        };
+        let dest = std::slice::from_raw_parts_mut(string, capacity);
+        let len = utf16_len(value).min(capacity - 1);
+        dest[..len].copy_from_slice(&value[..len]);
+        dest[len] = 0;
+        Ok(())
+    }
+    unsafe fn set_binary(
+        &self,
+        id: AttrID,
+        data: *const c_void,
+        size: u32,
+    ) -> Result<(), EInterface> {
+        let data = if size == 0 {
+            Vec::new()
+        } else if data.is_null() {
+            return Err(EInterface::InvalidArgument);
+        } else {
+            std::slice::from_raw_parts(data as *const u8, size as usize).to_vec()
+        };
+        self.set_value(id, AttributeValue::Binary(data));
+        Ok(())
+    }
+    unsafe fn get_binary(&self, id: AttrID) -> Result<(*const c_void, u32), EInterface> {
+        match self.values.borrow().get(id) {
+            Some(AttributeValue::Binary(data)) => {
+                Ok((data.as_ptr() as *const c_void, data.len() as u32))
+            }
+            _ => Err(EInterface::ResultFalse),
+        }
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+
+/// Message with an ID and a [`HostAttributeList`]
+#[repr(C)]
+pub struct HostMessage {
+    vtable: &'static IMessageVtable,
+    message_id: RefCell<Option<CString>>,
+    attributes: HostAttributeList,
+    ref_count: RefCount,
+}
+impl HostMessage {
+    pub fn new() -> Self {
+        Self {
+            vtable: &HOST_MESSAGE_VTABLE,
+            message_id: RefCell::new(None),
+            attributes: HostAttributeList::new(),
+            ref_count: None,
+        }
+    }
+    /// Reference counted message, freed when the returned interface and all other
+    /// references are released
+    pub fn new_shared() -> IMessage {
+        let message = Box::new(Self {
+            ref_count: Some(AtomicU32::new(1)),
+            ..Self::new()
+        });
+        unsafe { IMessage::from_raw(Box::into_raw(message) as *mut c_void) }
+    }
+    pub fn with_message_id(self, id: &CStr) -> Self {
+        self.set_message_id(id);
+        self
+    }
+    pub fn message_id(&self) -> Option<CString> {
+        self.message_id.borrow().clone()
+    }
+    pub fn set_message_id(&self, id: &CStr) {
+        *self.message_id.borrow_mut() = Some(id.to_owned());
+    }
+    pub fn attributes(&self) -> &HostAttributeList {
+        &self.attributes
+    }
+    pub fn as_interface(&self) -> InterfaceRef<'_, IMessage> {
+        unsafe { InterfaceRef::from_raw(self as *const Self as *mut c_void).unwrap() }
+    }
+}
+impl Default for HostMessage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl FUnknownImpl for HostMessage {
+    type Impl = Self;
+
+    fn get_impl(&self) -> &Self::Impl {
+        self
+    }
+    unsafe fn query_interface(
+        &self,
+        iid: *const FUID,
+        interface: *mut *mut c_void,
+    ) -> Result<(), EInterface> {
+        if interface.is_null() {
+            return Err(EInterface::Pointer);
+        }
+        if IMessageVtable::matches(&*iid) || *iid == <FUnknown as Interface>::iid {
+            self.add_ref();
+            *interface = self as *const Self as *mut c_void;
+            Ok(())
+        } else {
+            *interface = std::ptr::null_mut();
+            Err(EInterface::BadQuery)
+        }
+    }
+    fn add_ref(&self) -> u32 {
+        add_ref(&self.ref_count)
+    }
+    unsafe fn release(self_: *mut Self) -> u32 {
+        release(self_, |message| &message.ref_count)
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IMessageImpl for HostMessage {
+    unsafe fn get_message_id(&self) -> *const c_char {
+        // The string stays in place until the ID is changed
+        self.message_id
+            .borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |id| id.as_ptr())
+    }
+    unsafe fn set_message_id(&self, id: &CStr) {
+        HostMessage::set_message_id(self, id);
+    }
+    unsafe fn get_attributes(&self) -> *mut c_void {
+        &self.attributes as *const HostAttributeList as *mut c_void
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+
+/// Host context passed to `IPluginBase::initialize`, creating [`HostMessage`]s and
+/// [`HostAttributeList`]s for the plug-in
+///
+/// Owned by Rust code like the streams and event lists, so it isn't reference counted.
+#[repr(C)]
+pub struct HostApplication {
+    vtable: &'static IHostApplicationVtable,
+    name: String,
+}
+impl HostApplication {
+    pub fn new(name: &str) -> Self {
+        Self {
+            vtable: &HOST_APPLICATION_VTABLE,
+            name: name.to_owned(),
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn as_interface(&self) -> InterfaceRef<'_, IHostApplication> {
+        unsafe { InterfaceRef::from_raw(self as *const Self as *mut c_void).unwrap() }
+    }
+}
+impl FUnknownImpl for HostApplication {
+    type Impl = Self;
+
+    fn get_impl(&self) -> &Self::Impl {
+        self
+    }
+    unsafe fn query_interface(
+        &self,
+        iid: *const FUID,
+        interface: *mut *mut c_void,
+    ) -> Result<(), EInterface> {
+        if interface.is_null() {
+            return Err(EInterface::Pointer);
+        }
+        if IHostApplicationVtable::matches(&*iid) || *iid == <FUnknown as Interface>::iid {
+            *interface = self as *const Self as *mut c_void;
+            Ok(())
+        } else {
+            *interface = std::ptr::null_mut();
+            Err(EInterface::BadQuery)
+        }
+    }
+    fn add_ref(&self) -> u32 {
+        1
+    }
+    unsafe fn release(_self: *mut Self) -> u32 {
+        1
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IHostApplicationImpl for HostApplication {
+    unsafe fn get_name(&self) -> Result<String128, EInterface> {
+        Ok(string128_from_str(&self.name))
+    }
+    unsafe fn create_instance(
+        &self,
+        cid: &TUID,
+        iid: &TUID,
+        obj: *mut *mut c_void,
+    ) -> Result<(), EInterface> {
+        if obj.is_null() {
+            return Err(EInterface::Pointer);
+        }
+        let (cid, iid) = (FUID::from(*cid), FUID::from(*iid));
+        // The query adds the reference handed out, dropping the object releases ours
+        if cid == <IMessage as Interface>::iid {
+            HostMessage::new_shared().query(&iid, obj)
+        } else if cid == <IAttributeList as Interface>::iid {
+            HostAttributeList::new_shared().query(&iid, obj)
+        } else {
+            *obj = std::ptr::null_mut();
+            Err(EInterface::ResultFalse)
+        }
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_from_the_host_application() {
+        let host = HostApplication::new("Test Host");
+        let host = host.as_interface();
+        assert_eq!(host.get_name().unwrap(), "Test Host");
+
+        let message = host.allocate_message().unwrap();
+        assert_eq!(message.get_message_id(), None);
+        message.set_message_id(c"Spectrum");
+        assert_eq!(message.get_message_id(), Some(c"Spectrum"));
+        let attributes = message.get_attributes().unwrap();
+        let long = "Ä".repeat(300);
+        attributes.set_string(c"Title", &long).unwrap();
+        attributes.set_binary(c"Bins", &[1, 2, 3]).unwrap();
+        assert_eq!(attributes.get_str(c"Title").unwrap(), long);
+        assert_eq!(attributes.get_binary(c"Bins").unwrap(), [1, 2, 3]);
+        assert_eq!(attributes.get_str(c"Bins"), Err(EInterface::ResultFalse));
+
+        let copy = message.clone();
+        drop(message);
+        assert_eq!(copy.get_message_id(), Some(c"Spectrum"));
+
+        let list = host.create_instance::<IAttributeList>().unwrap();
+        list.set_float(c"Gain", 0.5).unwrap();
+        assert_eq!(list.get_float(c"Gain"), Ok(0.5));
+        assert_eq!(
+            unsafe {
+                host.create_instance_raw(&FUnknown::iid, &FUnknown::iid, &mut std::ptr::null_mut())
+            },
+            Err(EInterface::ResultFalse)
+        );
+
+        let local = HostMessage::new().with_message_id(c"Local");
+        local
+            .attributes()
+            .as_interface()
+            .set_string(c"Name", "Pad")
+            .unwrap();
+        assert_eq!(local.message_id().as_deref(), Some(c"Local"));
+        assert_eq!(local.attributes().string(c"Name").as_deref(), Some("Pad"));
+    }
+}
@@ -0,0 +1,224 @@
+//! Host side routing of MIDI controllers to parameters
+//!
+//! VST 3 plug-ins don't receive MIDI controllers as events, the host asks the edit
+//! controller which parameter a controller is assigned to through [`IMidiMapping`]
+//! and sends the controller values as parameter changes.
+use crate::{
+    plugininterfaces::{
+        base::{EInterface, Interface},
+        vst::{
+            ivsteditcontroller::IEditController,
+            ivstmidicontrollers::{kCountCtrlNumber, CtrlNumber, IMidiMapping},
+            ivstparameterchanges::IParameterChanges,
+            vsttypes::{kNoParamId, ParamID, ParamValue},
+        },
+    },
+    publicsdk::vst::midi::midi1::MidiMessage,
+};
+
+/// Count of MIDI channels queried per event bus
+const CHANNEL_COUNT: usize = 16;
+
+type BusAssignments = [[ParamID; kCountCtrlNumber as usize]; CHANNEL_COUNT];
+
+/// Table of MIDI controller assignments, queried once from the edit controller
+///
+/// Routing doesn't call into the plug-in, so it can be done from the audio thread.
+/// Call [`MidiControllerRouter::update`] when the plug-in restarts with
+/// `RestartFlags::MidiCCAssignmentChanged`.
+pub struct MidiControllerRouter {
+    assignments: Vec<Box<BusAssignments>>,
+}
+impl MidiControllerRouter {
+    /// Queries the assignments of the `bus_count` first event input buses
+    pub fn new(mapping: &IMidiMapping, bus_count: usize) -> Self {
+        let mut router = Self {
+            assignments: (0..bus_count)
+                .map(|_| Box::new([[kNoParamId; kCountCtrlNumber as usize]; CHANNEL_COUNT]))
+                .collect(),
+        };
+        router.update(mapping);
+        router
+    }
+    /// Queries the mapping of an edit controller, `None` if it doesn't implement [`IMidiMapping`]
+    pub fn from_controller(controller: &IEditController, bus_count: usize) -> Option<Self> {
+        let mapping = controller.cast::<IMidiMapping>().ok()?;
+        Some(Self::new(&mapping, bus_count))
+    }
+    /// Queries all assignments again
+    pub fn update(&mut self, mapping: &IMidiMapping) {
+        for (bus_index, bus) in self.assignments.iter_mut().enumerate() {
+            for (channel, controllers) in bus.iter_mut().enumerate() {
+                for (controller, id) in controllers.iter_mut().enumerate() {
+                    *id = mapping
+                        .get_midi_controller_assignment(
+                            bus_index as i32,
+                            channel as i16,
+                            controller as CtrlNumber,
+                        )
+                        .unwrap_or(kNoParamId);
+                }
+            }
+        }
+    }
+    /// Returns the parameter assigned to `controller` on the given bus and channel
+    pub fn parameter(
+        &self,
+        bus_index: i32,
+        channel: i16,
+        controller: CtrlNumber,
+    ) -> Option<ParamID> {
+        let bus = self.assignments.get(usize::try_from(bus_index).ok()?)?;
+        let controllers = bus.get(usize::try_from(channel).ok()?)?;
+        let id = *controllers.get(usize::try_from(controller).ok()?)?;
+        (id != kNoParamId).then_some(id)
+    }
+    /// Adds the controller value to `changes` at `sample_offset` if it is assigned,
+    /// returns the parameter it was routed to
+    pub fn route(
+        &self,
+        bus_index: i32,
+        channel: i16,
+        controller: CtrlNumber,
+        value: ParamValue,
+        sample_offset: i32,
+        changes: &IParameterChanges,
+    ) -> Result<Option<ParamID>, EInterface> {
+        let Some(id) = self.parameter(bus_index, channel, controller) else {
+            return Ok(None);
+        };
+        let (_, queue) = changes.add_parameter_data(id)?;
+        queue.add_point(sample_offset, value)?;
+        Ok(Some(id))
+    }
+    /// Routes a control change, program change, channel pressure or pitch bend message,
+    /// other messages aren't routed
+    pub fn route_message(
+        &self,
+        bus_index: i32,
+        message: &MidiMessage,
+        sample_offset: i32,
+        changes: &IParameterChanges,
+    ) -> Result<Option<ParamID>, EInterface> {
+        match message.controller() {
+            Some((channel, controller, value)) => self.route(
+                bus_index,
+                channel,
+                controller,
+                value,
+                sample_offset,
+                changes,
+            ),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        plugininterfaces::{
+            base::{pluginreexports::*, InterfaceRef},
+            vst::ivstmidicontrollers::{
+                kAfterTouch, kCtrlModWheel, kPitchBend, IMidiMappingImpl, IMidiMappingVtable,
+            },
+        },
+        publicsdk::vst::hosting::parameterchanges::ParameterChanges,
+    };
+    use std::os::raw::c_void;
+
+    static MAPPING_VTABLE: IMidiMappingVtable = IMidiMappingVtable::new::<Mapping, 0>();
+
+    /// Mod wheel, aftertouch and pitch bend of channel 0 map to parameters 1, 2 and 3
+    #[repr(C)]
+    struct Mapping {
+        vtable: &'static IMidiMappingVtable,
+    }
+    impl FUnknownImpl for Mapping {
+        type Impl = Self;
+
+        fn get_impl(&self) -> &Self::Impl {
+            self
+        }
+        unsafe fn query_interface(
+            &self,
+            _iid: *const FUID,
+            _interface: *mut *mut c_void,
+        ) -> Result<(), EInterface> {
+            Err(EInterface::BadQuery)
+        }
+        fn add_ref(&self) -> u32 {
+            1
+        }
+        unsafe fn release(_self: *mut Self) -> u32 {
+            1
+        }
+
+        const INNER_OFFSET_IN_POINTERS: usize = 0;
+    }
+    impl IMidiMappingImpl for Mapping {
+        unsafe fn get_midi_controller_assignment(
+            &self,
+            bus_index: i32,
+            channel: i16,
+            midi_controller_number: CtrlNumber,
+        ) -> Result<ParamID, EInterface> {
+            if bus_index != 0 || channel != 0 {
+                return Err(EInterface::ResultFalse);
+            }
+            [kCtrlModWheel, kAfterTouch, kPitchBend]
+                .iter()
+                .position(|&controller| controller == midi_controller_number)
+                .map(|index| index as ParamID + 1)
+                .ok_or(EInterface::ResultFalse)
+        }
+
+        const INNER_OFFSET_IN_POINTERS: usize = 0;
+    }
+
+    #[test]
+    fn routes_assigned_controllers() {
+        let mapping = Mapping {
+            vtable: &MAPPING_VTABLE,
+        };
+        let mapping: InterfaceRef<IMidiMapping> =
+            unsafe { InterfaceRef::from_raw(&mapping as *const Mapping as *mut c_void).unwrap() };
+        let router = MidiControllerRouter::new(&mapping, 1);
+        assert_eq!(router.parameter(0, 0, kPitchBend), Some(3));
+        assert_eq!(router.parameter(0, 1, kPitchBend), None);
+        assert_eq!(router.parameter(1, 0, kPitchBend), None);
+
+        let changes = ParameterChanges::new(4, 4);
+        let interface = changes.as_interface();
+        let messages = [
+            MidiMessage::ControlChange {
+                channel: 0,
+                controller: 1,
+                value: 127,
+            },
+            MidiMessage::PitchBend {
+                channel: 0,
+                value: 0,
+            },
+            MidiMessage::ChannelPressure {
+                channel: 1,
+                pressure: 10,
+            },
+            MidiMessage::NoteOn {
+                channel: 0,
+                pitch: 60,
+                velocity: 100,
+            },
+        ];
+        let routed: Vec<_> = messages
+            .iter()
+            .map(|message| router.route_message(0, message, 16, &interface).unwrap())
+            .collect();
+        assert_eq!(routed, [Some(1), Some(3), None, None]);
+        assert_eq!(
+            interface.points().collect::<Vec<_>>(),
+            [(1, 16, 1.0), (3, 16, 0.0)]
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub mod attributedstream;
+pub mod eventlist;
+pub mod hostclasses;
+pub mod midimapping;
+pub mod parameterchanges;
+pub mod presetlibrary;
+pub mod transport;
@@ -0,0 +1,281 @@
+//! Host implementation of parameter changes, see `public.sdk/source/vst/hosting/parameterchanges.h`
+//!
+//! Both types are preallocated on construction and never allocate afterwards,
+//! so one instance can be filled and handed to the processor every block.
+use crate::plugininterfaces::{
+    base::{pluginreexports::*, EInterface, Interface, InterfaceRef},
+    vst::{
+        ivstparameterchanges::{
+            IParamValueQueue, IParamValueQueueImpl, IParamValueQueueVtable, IParameterChanges,
+            IParameterChangesImpl, IParameterChangesVtable,
+        },
+        vsttypes::{kNoParamId, ParamID, ParamValue},
+    },
+};
+use std::{
+    cell::{Cell, RefCell},
+    os::raw::c_void,
+};
+
+static PARAMETER_VALUE_QUEUE_VTABLE: IParamValueQueueVtable =
+    IParamValueQueueVtable::new::<ParameterValueQueue, 0>();
+static PARAMETER_CHANGES_VTABLE: IParameterChangesVtable =
+    IParameterChangesVtable::new::<ParameterChanges, 0>();
+
+/// Queue of `(sample_offset, value)` points of a single parameter, sorted by sample offset
+///
+/// The queue is owned by its [`ParameterChanges`], reference counting is a no-op.
+#[repr(C)]
+pub struct ParameterValueQueue {
+    vtable: &'static IParamValueQueueVtable,
+    id: Cell<ParamID>,
+    points: RefCell<Vec<(i32, ParamValue)>>,
+}
+impl ParameterValueQueue {
+    fn new(max_points: usize) -> Self {
+        Self {
+            vtable: &PARAMETER_VALUE_QUEUE_VTABLE,
+            id: Cell::new(kNoParamId),
+            points: RefCell::new(Vec::with_capacity(max_points)),
+        }
+    }
+    pub fn id(&self) -> ParamID {
+        self.id.get()
+    }
+    pub fn len(&self) -> usize {
+        self.points.borrow().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.points.borrow().is_empty()
+    }
+    pub fn point(&self, index: usize) -> Option<(i32, ParamValue)> {
+        self.points.borrow().get(index).copied()
+    }
+    /// Inserts a point keeping the queue sorted, a point at the same
+    /// sample offset is replaced. Fails if the queue is full.
+    pub fn add_point(&self, sample_offset: i32, value: ParamValue) -> Result<usize, EInterface> {
+        let mut points = self.points.borrow_mut();
+        let index = points.partition_point(|(offset, _)| *offset < sample_offset);
+        if let Some(point) = points.get_mut(index) {
+            if point.0 == sample_offset {
+                point.1 = value;
+                return Ok(index);
+            }
+        }
+        if points.len() == points.capacity() {
+            return Err(EInterface::OutOfMemory);
+        }
+        points.insert(index, (sample_offset, value));
+        Ok(index)
+    }
+    fn reset(&self, id: ParamID) {
+        self.id.set(id);
+        self.points.borrow_mut().clear();
+    }
+    /// View of this queue as an interface, valid as long as the queue is borrowed
+    pub fn as_interface(&self) -> InterfaceRef<'_, IParamValueQueue> {
+        unsafe { InterfaceRef::from_raw(self as *const Self as *mut c_void).unwrap() }
+    }
+}
+impl FUnknownImpl for ParameterValueQueue {
+    type Impl = Self;
+
+    fn get_impl(&self) -> &Self::Impl {
+        self
+    }
+    unsafe fn query_interface(
+        &self,
+        iid: *const FUID,
+        interface: *mut *mut c_void,
+    ) -> Result<(), EInterface> {
+        if interface.is_null() {
+            return Err(EInterface::Pointer);
+        }
+        if IParamValueQueueVtable::matches(&*iid) || *iid == <FUnknown as Interface>::iid {
+            *interface = self as *const Self as *mut c_void;
+            Ok(())
+        } else {
+            *interface = std::ptr::null_mut();
+            Err(EInterface::BadQuery)
+        }
+    }
+    fn add_ref(&self) -> u32 {
+        1
+    }
+    unsafe fn release(_self: *mut Self) -> u32 {
+        1
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IParamValueQueueImpl for ParameterValueQueue {
+    unsafe fn get_parameter_id(&self) -> ParamID {
+        self.id()
+    }
+    unsafe fn get_point_count(&self) -> i32 {
+        self.len() as i32
+    }
+    unsafe fn get_point(&self, index: i32) -> Result<(i32, ParamValue), EInterface> {
+        let index = usize::try_from(index).map_err(|_| EInterface::InvalidArgument)?;
+        self.point(index).ok_or(EInterface::InvalidArgument)
+    }
+    unsafe fn add_point(&self, sample_offset: i32, value: ParamValue) -> Result<i32, EInterface> {
+        ParameterValueQueue::add_point(self, sample_offset, value).map(|index| index as i32)
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+
+/// List of parameter queues for one processing block
+///
+/// Holds up to `max_parameters` queues of `max_points` points each. Fill it from
+/// automation with [`ParameterChanges::add_point`], pass [`ParameterChanges::as_interface`]
+/// to the processor and [`ParameterChanges::clear`] it before the next block.
+#[repr(C)]
+pub struct ParameterChanges {
+    vtable: &'static IParameterChangesVtable,
+    queues: Box<[ParameterValueQueue]>,
+    used_queue_count: Cell<usize>,
+}
+impl ParameterChanges {
+    pub fn new(max_parameters: usize, max_points: usize) -> Self {
+        Self {
+            vtable: &PARAMETER_CHANGES_VTABLE,
+            queues: (0..max_parameters)
+                .map(|_| ParameterValueQueue::new(max_points))
+                .collect(),
+            used_queue_count: Cell::new(0),
+        }
+    }
+    /// Removes all queues, keeping their storage
+    pub fn clear(&mut self) {
+        for queue in self.queues() {
+            queue.reset(kNoParamId);
+        }
+        self.used_queue_count.set(0);
+    }
+    pub fn len(&self) -> usize {
+        self.used_queue_count.get()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Queues in use, in the order they were added
+    pub fn queues(&self) -> impl Iterator<Item = &ParameterValueQueue> {
+        self.queues[..self.len()].iter()
+    }
+    pub fn queue(&self, id: ParamID) -> Option<&ParameterValueQueue> {
+        self.queues().find(|queue| queue.id() == id)
+    }
+    /// Returns the queue for `id` and its index, taking a free queue if there is none yet
+    pub fn add_parameter_data(
+        &self,
+        id: ParamID,
+    ) -> Result<(usize, &ParameterValueQueue), EInterface> {
+        if let Some(index) = self.queues().position(|queue| queue.id() == id) {
+            return Ok((index, &self.queues[index]));
+        }
+        let index = self.len();
+        let queue = self.queues.get(index).ok_or(EInterface::OutOfMemory)?;
+        queue.reset(id);
+        self.used_queue_count.set(index + 1);
+        Ok((index, queue))
+    }
+    /// Adds an automation point for `id`
+    pub fn add_point(
+        &self,
+        id: ParamID,
+        sample_offset: i32,
+        value: ParamValue,
+    ) -> Result<(), EInterface> {
+        let (_, queue) = self.add_parameter_data(id)?;
+        queue.add_point(sample_offset, value).map(|_| ())
+    }
+    /// View of this list as an interface, valid as long as the list is borrowed
+    pub fn as_interface(&self) -> InterfaceRef<'_, IParameterChanges> {
+        unsafe { InterfaceRef::from_raw(self as *const Self as *mut c_void).unwrap() }
+    }
+}
+impl FUnknownImpl for ParameterChanges {
+    type Impl = Self;
+
+    fn get_impl(&self) -> &Self::Impl {
+        self
+    }
+    unsafe fn query_interface(
+        &self,
+        iid: *const FUID,
+        interface: *mut *mut c_void,
+    ) -> Result<(), EInterface> {
+        if interface.is_null() {
+            return Err(EInterface::Pointer);
+        }
+        if IParameterChangesVtable::matches(&*iid) || *iid == <FUnknown as Interface>::iid {
+            *interface = self as *const Self as *mut c_void;
+            Ok(())
+        } else {
+            *interface = std::ptr::null_mut();
+            Err(EInterface::BadQuery)
+        }
+    }
+    fn add_ref(&self) -> u32 {
+        1
+    }
+    unsafe fn release(_self: *mut Self) -> u32 {
+        1
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IParameterChangesImpl for ParameterChanges {
+    unsafe fn get_parameter_count(&self) -> i32 {
+        self.len() as i32
+    }
+    unsafe fn get_parameter_data(&self, index: i32) -> *mut c_void {
+        match usize::try_from(index) {
+            Ok(index) if index < self.len() => {
+                &self.queues[index] as *const ParameterValueQueue as *mut c_void
+            }
+            _ => std::ptr::null_mut(),
+        }
+    }
+    unsafe fn add_parameter_data(&self, id: ParamID) -> Result<(i32, *mut c_void), EInterface> {
+        let (index, queue) = ParameterChanges::add_parameter_data(self, id)?;
+        Ok((
+            index as i32,
+            queue as *const ParameterValueQueue as *mut c_void,
+        ))
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_and_read_through_interface() {
+        let mut changes = ParameterChanges::new(2, 3);
+        changes.add_point(7, 64, 0.5).unwrap();
+        changes.add_point(7, 0, 0.25).unwrap();
+        changes.add_point(9, 10, 1.0).unwrap();
+        changes.add_point(7, 64, 0.75).unwrap();
+        assert_eq!(changes.add_point(11, 0, 0.0), Err(EInterface::OutOfMemory));
+
+        let interface = changes.as_interface();
+        assert_eq!(interface.get_parameter_count(), 2);
+        let points: Vec<_> = interface.points().collect();
+        assert_eq!(points, [(7, 0, 0.25), (7, 64, 0.75), (9, 10, 1.0)]);
+
+        let (index, queue) = interface.add_parameter_data(9).unwrap();
+        assert_eq!(index, 1);
+        queue.add_point(20, 0.5).unwrap();
+        queue.add_point(30, 0.5).unwrap();
+        assert!(queue.add_point(40, 0.5).is_err());
+        assert_eq!(queue.points().count(), 3);
+
+        changes.clear();
+        assert!(changes.as_interface().points().next().is_none());
+    }
+}
@@ -0,0 +1,396 @@
+//! Discovery of `.vstpreset` files in the VST 3 preset locations on Linux
+//!
+//! Presets of a plug-in are stored per vendor and plug-in name:
+//!
+//! | Location | Folder |
+//! |----------|--------|
+//! | User     | `$HOME/.vst3/presets/<Vendor>/<Plugin>/` |
+//! | Shared   | `/usr/share/vst3/presets/<Vendor>/<Plugin>/`, `/usr/local/share/vst3/presets/<Vendor>/<Plugin>/` |
+//! | Factory  | `<Plugin>.vst3/Contents/Resources/Presets/` |
+//!
+//! Subfolders of these folders are scanned as well, new presets are saved to the
+//! user folder.
+use crate::{
+    plugininterfaces::base::FUID,
+    publicsdk::vst::{
+        vstpresetfile::{class_id_to_string, VstPreset},
+        vstpresetmetainfo::PresetMetaInfo,
+    },
+};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Extension of preset files
+pub const PRESET_EXTENSION: &str = "vstpreset";
+
+/// Kind of a preset folder
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PresetLocation {
+    /// Presets of the current user, writable
+    User,
+    /// Presets installed for all users
+    Shared,
+    /// Presets shipped inside the plug-in bundle
+    Factory,
+}
+
+/// Preset found by [`PresetLibrary::scan`]
+#[derive(Clone, Debug)]
+pub struct PresetEntry {
+    pub location: PresetLocation,
+    pub path: PathBuf,
+    /// Class ID as stored in the file, see [`class_id_to_string`]
+    pub class_id: String,
+    /// Meta information of the `Info` chunk, `None` if missing or malformed
+    pub meta_info: Option<PresetMetaInfo>,
+}
+impl PresetEntry {
+    /// Name of the meta information, or the file name without extension
+    pub fn name(&self) -> String {
+        self.meta_info
+            .as_ref()
+            .and_then(|info| info.name.clone())
+            .unwrap_or_else(|| {
+                self.path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            })
+    }
+}
+
+/// Preset folders of a plug-in and the presets found in them
+pub struct PresetLibrary {
+    vendor: String,
+    plugin: String,
+    folders: Vec<(PresetLocation, PathBuf)>,
+    entries: Vec<PresetEntry>,
+    by_class_id: HashMap<String, Vec<usize>>,
+}
+impl PresetLibrary {
+    /// Library with the user and shared folders of the plug-in `plugin` of `vendor`
+    pub fn new(vendor: &str, plugin: &str) -> Self {
+        let mut library = Self::empty(vendor, plugin);
+        if let Some(home) = env::var_os("HOME").filter(|home| !home.is_empty()) {
+            library.add_root(
+                PresetLocation::User,
+                &Path::new(&home).join(".vst3/presets"),
+            );
+        }
+        for shared in ["/usr/share/vst3/presets", "/usr/local/share/vst3/presets"] {
+            library.add_root(PresetLocation::Shared, Path::new(shared));
+        }
+        library
+    }
+    /// Library without folders
+    pub fn empty(vendor: &str, plugin: &str) -> Self {
+        Self {
+            vendor: vendor.to_owned(),
+            plugin: plugin.to_owned(),
+            folders: Vec::new(),
+            entries: Vec::new(),
+            by_class_id: HashMap::new(),
+        }
+    }
+    pub fn vendor(&self) -> &str {
+        &self.vendor
+    }
+    pub fn plugin(&self) -> &str {
+        &self.plugin
+    }
+    /// Adds the folder `<root>/<Vendor>/<Plugin>`
+    pub fn add_root(&mut self, location: PresetLocation, root: &Path) {
+        let folder = root
+            .join(sanitize_file_name(&self.vendor))
+            .join(sanitize_file_name(&self.plugin));
+        self.add_folder(location, folder);
+    }
+    /// Adds the factory folder of the plug-in bundle at `bundle`
+    pub fn add_factory_bundle(&mut self, bundle: &Path) {
+        self.add_folder(
+            PresetLocation::Factory,
+            bundle.join("Contents/Resources/Presets"),
+        );
+    }
+    /// Adds a folder as it is
+    pub fn add_folder(&mut self, location: PresetLocation, folder: PathBuf) {
+        if !self.folders.iter().any(|(_, other)| *other == folder) {
+            self.folders.push((location, folder));
+        }
+    }
+    pub fn folders(&self) -> impl Iterator<Item = (PresetLocation, &Path)> + '_ {
+        self.folders
+            .iter()
+            .map(|(location, folder)| (*location, folder.as_path()))
+    }
+    /// Folder new presets are saved to
+    pub fn user_folder(&self) -> Option<&Path> {
+        self.folders
+            .iter()
+            .find(|(location, _)| *location == PresetLocation::User)
+            .map(|(_, folder)| folder.as_path())
+    }
+    /// Indexes the presets of all folders and their subfolders, returns their number.
+    ///
+    /// Missing folders, unreadable files and files which aren't presets are skipped.
+    pub fn scan(&mut self) -> usize {
+        self.entries.clear();
+        self.by_class_id.clear();
+        let folders = self.folders.clone();
+        for (location, folder) in folders {
+            let mut files = Vec::new();
+            collect_presets(&folder, &mut HashSet::new(), &mut files);
+            files.sort();
+            for path in files {
+                if let Ok(entry) = read_entry(location, &path) {
+                    self.insert(entry);
+                }
+            }
+        }
+        self.entries.len()
+    }
+    fn insert(&mut self, entry: PresetEntry) {
+        if let Some(index) = self
+            .entries
+            .iter()
+            .position(|other| other.path == entry.path)
+        {
+            self.entries[index] = entry;
+            self.by_class_id
+                .values_mut()
+                .for_each(|indices| indices.retain(|&other| other != index));
+            self.by_class_id.retain(|_, indices| !indices.is_empty());
+            self.by_class_id
+                .entry(self.entries[index].class_id.clone())
+                .or_default()
+                .push(index);
+        } else {
+            self.by_class_id
+                .entry(entry.class_id.clone())
+                .or_default()
+                .push(self.entries.len());
+            self.entries.push(entry);
+        }
+    }
+    pub fn entries(&self) -> &[PresetEntry] {
+        &self.entries
+    }
+    /// Presets of the plug-in class `class_id`
+    pub fn presets_for(&self, class_id: &FUID) -> impl Iterator<Item = &PresetEntry> + '_ {
+        self.by_class_id
+            .get(&class_id_to_string(class_id))
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.entries[index])
+    }
+    /// Presets whose meta information has the character tag `tag`
+    pub fn with_character<'a>(
+        &'a self,
+        tag: &'a str,
+    ) -> impl Iterator<Item = &'a PresetEntry> + 'a {
+        self.entries.iter().filter(move |entry| {
+            entry
+                .meta_info
+                .as_ref()
+                .is_some_and(|info| info.character_tags().contains(&tag))
+        })
+    }
+    /// Saves `preset` as `<user folder>/<subfolder>/<name>.vstpreset`, replacing an existing
+    /// preset of the same name, and adds it to the library. Returns the path of the file.
+    pub fn save(
+        &mut self,
+        preset: &VstPreset,
+        name: &str,
+        subfolder: Option<&str>,
+    ) -> io::Result<PathBuf> {
+        let mut folder = self
+            .user_folder()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No user preset folder"))?
+            .to_path_buf();
+        if let Some(subfolder) = subfolder {
+            folder.push(sanitize_file_name(subfolder));
+        }
+        fs::create_dir_all(&folder)?;
+        let path = folder.join(format!("{}.{PRESET_EXTENSION}", sanitize_file_name(name)));
+        fs::write(&path, preset.to_bytes())?;
+        self.insert(entry_of(PresetLocation::User, &path, preset));
+        Ok(path)
+    }
+}
+
+/// Maximum length of a file name in bytes, leaving room for the extension
+const MAX_FILE_NAME_LENGTH: usize = 200;
+
+/// Turns `name` into a file name which is valid on all platforms
+///
+/// Path separators, characters reserved on Windows and control characters are
+/// replaced by `_`, leading and trailing dots and spaces are removed and reserved
+/// device names get a `_` appended. An empty name becomes `Preset`.
+pub fn sanitize_file_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let mut end = replaced.len().min(MAX_FILE_NAME_LENGTH);
+    while !replaced.is_char_boundary(end) {
+        end -= 1;
+    }
+    // Trimmed after truncating, which may leave a dot or space at the end
+    let mut sanitized = replaced[..end]
+        .trim_matches(|c: char| c == '.' || c.is_whitespace())
+        .to_owned();
+    if sanitized.is_empty() {
+        return "Preset".to_owned();
+    }
+    let stem = sanitized.split('.').next().unwrap_or_default();
+    let reserved = ["CON", "PRN", "AUX", "NUL"]
+        .iter()
+        .any(|device| stem.eq_ignore_ascii_case(device))
+        || (stem.len() == 4
+            && [b"COM", b"LPT"]
+                .iter()
+                .any(|device| stem.as_bytes()[..3].eq_ignore_ascii_case(*device))
+            && stem.as_bytes()[3].is_ascii_digit());
+    if reserved {
+        sanitized.push('_');
+    }
+    sanitized
+}
+
+/// Collects the presets in `folder` and its subfolders. Symbolic links are followed,
+/// folders reached a second time through them are skipped to not loop forever.
+fn collect_presets(folder: &Path, visited: &mut HashSet<PathBuf>, files: &mut Vec<PathBuf>) {
+    let Ok(canonical) = folder.canonicalize() else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(folder) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_presets(&path, visited, files);
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case(PRESET_EXTENSION))
+        {
+            files.push(path);
+        }
+    }
+}
+
+fn read_entry(location: PresetLocation, path: &Path) -> io::Result<PresetEntry> {
+    let preset = VstPreset::from_bytes(fs::read(path)?)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    Ok(entry_of(location, path, &preset))
+}
+
+fn entry_of(location: PresetLocation, path: &Path, preset: &VstPreset) -> PresetEntry {
+    PresetEntry {
+        location,
+        path: path.to_path_buf(),
+        class_id: class_id_to_string(&preset.class_id()),
+        meta_info: PresetMetaInfo::from_preset(preset).and_then(Result::ok),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitizes_file_names() {
+        assert_eq!(sanitize_file_name("Bass: Deep/Dark?"), "Bass_ Deep_Dark_");
+        assert_eq!(sanitize_file_name("  ..hidden. "), "hidden");
+        assert_eq!(sanitize_file_name("con.txt"), "con.txt_");
+        assert_eq!(sanitize_file_name("COM1"), "COM1_");
+        assert_eq!(sanitize_file_name("Combo"), "Combo");
+        assert_eq!(sanitize_file_name("..."), "Preset");
+        assert_eq!(sanitize_file_name(&"é".repeat(150)).len(), 200);
+        assert_eq!(sanitize_file_name("a€"), "a€");
+        assert_eq!(sanitize_file_name("aaé"), "aaé");
+        let name = format!("{}. x", "a".repeat(MAX_FILE_NAME_LENGTH - 2));
+        assert_eq!(
+            sanitize_file_name(&name),
+            "a".repeat(MAX_FILE_NAME_LENGTH - 2)
+        );
+    }
+
+    #[test]
+    fn saves_and_indexes_presets() {
+        let root = env::temp_dir().join(format!("vst3-preset-library-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let synth = FUID::from([1, 2, 3, 4]);
+        let other = FUID::from([5, 6, 7, 8]);
+
+        let mut library = PresetLibrary::empty("Acme Audio", "Synth/X");
+        library.add_root(PresetLocation::User, &root.join("user"));
+        library.add_factory_bundle(&root.join("Synth.vst3"));
+        assert_eq!(
+            library.user_folder(),
+            Some(root.join("user/Acme Audio/Synth_X").as_path())
+        );
+
+        let mut preset = VstPreset::new(synth);
+        let mut info = PresetMetaInfo::new("Synth/X", "Instrument|Synth");
+        info.character = Some("Warm|Soft".to_owned());
+        info.store(&mut preset);
+        let path = library.save(&preset, "Pad: Warm", Some("Pads")).unwrap();
+        assert_eq!(
+            path,
+            root.join("user/Acme Audio/Synth_X/Pads/Pad_ Warm.vstpreset")
+        );
+        assert_eq!(library.presets_for(&synth).count(), 1);
+
+        let factory = root.join("Synth.vst3/Contents/Resources/Presets/Leads");
+        fs::create_dir_all(&factory).unwrap();
+        fs::write(
+            factory.join("Lead.vstpreset"),
+            VstPreset::new(other).to_bytes(),
+        )
+        .unwrap();
+        fs::write(factory.join("Broken.vstpreset"), b"VST2").unwrap();
+
+        assert_eq!(library.scan(), 2);
+        let names: Vec<_> = library
+            .presets_for(&synth)
+            .map(|entry| (entry.location, entry.name()))
+            .collect();
+        assert_eq!(names, [(PresetLocation::User, "Pad_ Warm".to_owned())]);
+        let factory: Vec<_> = library.presets_for(&other).collect();
+        assert_eq!(factory[0].location, PresetLocation::Factory);
+        assert!(factory[0].meta_info.is_none());
+        assert_eq!(library.with_character("Warm").count(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scans_symlink_loops_once() {
+        let root = env::temp_dir().join(format!("vst3-preset-loop-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let mut library = PresetLibrary::empty("V", "P");
+        library.add_root(PresetLocation::User, &root);
+        let folder = library.user_folder().unwrap().to_path_buf();
+        fs::create_dir_all(folder.join("Pads")).unwrap();
+        let preset = VstPreset::new(FUID::from([1, 2, 3, 4]));
+        fs::write(folder.join("Pads/Pad.vstpreset"), preset.to_bytes()).unwrap();
+        std::os::unix::fs::symlink("..", folder.join("Pads/loop")).unwrap();
+        std::os::unix::fs::symlink("Pads", folder.join("Linked")).unwrap();
+
+        assert_eq!(library.scan(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
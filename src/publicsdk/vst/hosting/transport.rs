@@ -0,0 +1,307 @@
+//! Host side transport producing the [`ProcessContext`] of each processing block
+//!
+//! The transport owns a [`TempoMap`] and a [`TimeSignatureMap`] and advances through
+//! them block by block. Blocks are shortened at the cycle end, so that every block
+//! is musically continuous:
+//!
+//! ```ignore
+//! let mut remaining = total_samples;
+//! while remaining > 0 {
+//!     let block = transport.next_block(remaining.min(block_size));
+//!     process(&block.context, block.num_samples);
+//!     remaining -= block.num_samples;
+//! }
+//! ```
+use crate::{
+    plugininterfaces::vst::{
+        ivstprocesscontext::{FrameRate, ProcessContext, StatesAndFlags},
+        vsttypes::{TQuarterNotes, TSamples},
+    },
+    publicsdk::vst::utility::tempomap::{TempoMap, TimeSignatureMap},
+};
+
+/// MIDI clocks per quarter note
+const CLOCKS_PER_QUARTER: f64 = 24.0;
+
+/// Context and length of a processing block
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TransportBlock {
+    /// Context of the first sample of the block
+    pub context: ProcessContext,
+    /// Number of samples to process, at most the requested block size
+    pub num_samples: i32,
+}
+
+/// Simulated transport with play, record and cycle state
+#[derive(Clone, Debug)]
+pub struct Transport {
+    tempo_map: TempoMap,
+    time_signatures: TimeSignatureMap,
+    sample_rate: f64,
+    project_time_samples: TSamples,
+    continuous_time_samples: TSamples,
+    playing: bool,
+    recording: bool,
+    cycle: Option<(TQuarterNotes, TQuarterNotes)>,
+    cycle_active: bool,
+    smpte: Option<(i32, FrameRate)>,
+}
+impl Transport {
+    /// Stopped transport at 120 BPM in 4/4
+    pub fn new(sample_rate: f64) -> Self {
+        Self::with_maps(
+            sample_rate,
+            TempoMap::default(),
+            TimeSignatureMap::default(),
+        )
+    }
+    pub fn with_maps(
+        sample_rate: f64,
+        tempo_map: TempoMap,
+        time_signatures: TimeSignatureMap,
+    ) -> Self {
+        Self {
+            tempo_map,
+            time_signatures,
+            sample_rate,
+            project_time_samples: 0,
+            continuous_time_samples: 0,
+            playing: false,
+            recording: false,
+            cycle: None,
+            cycle_active: false,
+            smpte: None,
+        }
+    }
+    pub fn tempo_map(&self) -> &TempoMap {
+        &self.tempo_map
+    }
+    pub fn tempo_map_mut(&mut self) -> &mut TempoMap {
+        &mut self.tempo_map
+    }
+    pub fn time_signatures(&self) -> &TimeSignatureMap {
+        &self.time_signatures
+    }
+    pub fn time_signatures_mut(&mut self) -> &mut TimeSignatureMap {
+        &mut self.time_signatures
+    }
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+    /// Changes the sample rate, keeping the musical position
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        let position = self.position_music();
+        self.sample_rate = sample_rate;
+        self.locate(position);
+    }
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+    /// Stops playing and recording, the position is kept
+    pub fn stop(&mut self) {
+        self.playing = false;
+        self.recording = false;
+    }
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+    /// Sets the cycle range, `None` removes it. An empty range is ignored.
+    pub fn set_cycle(&mut self, cycle: Option<(TQuarterNotes, TQuarterNotes)>) {
+        self.cycle = cycle.filter(|(start, end)| start < end);
+    }
+    pub fn cycle(&self) -> Option<(TQuarterNotes, TQuarterNotes)> {
+        self.cycle
+    }
+    /// Enables or disables looping over the cycle range
+    pub fn set_cycle_active(&mut self, active: bool) {
+        self.cycle_active = active;
+    }
+    pub fn is_cycle_active(&self) -> bool {
+        self.cycle_active && self.cycle.is_some()
+    }
+    /// Sets the SMPTE offset in subframes and the frame rate, `None` doesn't report SMPTE time
+    pub fn set_smpte(&mut self, smpte: Option<(i32, FrameRate)>) {
+        self.smpte = smpte;
+    }
+    /// Moves to the musical position `ppq_position`
+    pub fn locate(&mut self, ppq_position: TQuarterNotes) {
+        self.project_time_samples = self
+            .tempo_map
+            .samples_at(ppq_position, self.sample_rate)
+            .round() as TSamples;
+    }
+    /// Moves to the sample position `samples`
+    pub fn locate_samples(&mut self, samples: TSamples) {
+        self.project_time_samples = samples;
+    }
+    /// Moves to the start of the zero based bar `bar`
+    pub fn locate_bar(&mut self, bar: i64) {
+        self.locate(self.time_signatures.ppq_at_bar(bar));
+    }
+    pub fn position_samples(&self) -> TSamples {
+        self.project_time_samples
+    }
+    pub fn position_music(&self) -> TQuarterNotes {
+        self.tempo_map
+            .ppq_at_samples(self.project_time_samples as f64, self.sample_rate)
+    }
+    pub fn continuous_time_samples(&self) -> TSamples {
+        self.continuous_time_samples
+    }
+    /// Sample position of the cycle range
+    fn cycle_samples(&self) -> Option<(TSamples, TSamples)> {
+        if !self.is_cycle_active() {
+            return None;
+        }
+        let (start, end) = self.cycle?;
+        let to_samples = |position| {
+            self.tempo_map
+                .samples_at(position, self.sample_rate)
+                .round() as TSamples
+        };
+        Some((to_samples(start), to_samples(end)))
+    }
+    /// Context of the current position, without advancing
+    pub fn context(&self) -> ProcessContext {
+        let position = self.position_music();
+        let (numerator, denominator) = self.time_signatures.time_signature_at(position);
+        let (_, bar_position) = self.time_signatures.bar_at(position);
+        let tempo = self.tempo_map.tempo_at(position);
+        let samples_per_quarter = 60.0 / tempo * self.sample_rate;
+        let next_clock = (position * CLOCKS_PER_QUARTER).ceil() / CLOCKS_PER_QUARTER;
+
+        let mut context = ProcessContext {
+            state: StatesAndFlags::SystemTimeValid
+                | StatesAndFlags::ContTimeValid
+                | StatesAndFlags::ProjectTimeMusicValid
+                | StatesAndFlags::BarPositionValid
+                | StatesAndFlags::TempoValid
+                | StatesAndFlags::TimeSigValid
+                | StatesAndFlags::ClockValid,
+            sample_rate: self.sample_rate,
+            project_time_samples: self.project_time_samples,
+            system_time: (self.continuous_time_samples as f64 / self.sample_rate * 1e9) as i64,
+            continous_time_samples: self.continuous_time_samples,
+            project_time_music: position,
+            bar_position_music: bar_position,
+            tempo,
+            time_sig_numerator: numerator,
+            time_sig_denominator: denominator,
+            samples_to_next_clock: ((next_clock - position) * samples_per_quarter).round() as i32,
+            ..Default::default()
+        };
+        if self.playing {
+            context.state |= StatesAndFlags::Playing;
+        }
+        if self.recording {
+            context.state |= StatesAndFlags::Recording;
+        }
+        if let Some((start, end)) = self.cycle {
+            context.state |= StatesAndFlags::CycleValid;
+            context.cycle_start_music = start;
+            context.cycle_end_music = end;
+            if self.cycle_active {
+                context.state |= StatesAndFlags::CycleActive;
+            }
+        }
+        if let Some((offset_subframes, frame_rate)) = self.smpte {
+            context.state |= StatesAndFlags::SmpteValid;
+            context.smpte_offset_subframes = offset_subframes;
+            context.frame_rate = frame_rate;
+        }
+        context
+    }
+    /// Returns the context of the next block of up to `num_samples` samples and advances.
+    ///
+    /// While playing, the block ends early at the cycle end and the next block starts
+    /// at the cycle start. The continuous time advances even while stopped.
+    pub fn next_block(&mut self, num_samples: i32) -> TransportBlock {
+        let mut num_samples = num_samples.max(0);
+        let mut wrap_to = None;
+        if let (true, Some((start, end))) = (self.playing, self.cycle_samples()) {
+            // A block starting at or after the cycle end plays through
+            let remaining = end - self.project_time_samples;
+            if remaining > 0 && remaining <= num_samples as TSamples {
+                num_samples = remaining as i32;
+                wrap_to = Some(start);
+            }
+        }
+        let block = TransportBlock {
+            context: self.context(),
+            num_samples,
+        };
+        self.continuous_time_samples += num_samples as TSamples;
+        if self.playing {
+            self.project_time_samples =
+                wrap_to.unwrap_or(self.project_time_samples + num_samples as TSamples);
+        }
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_wraps_at_block_boundary() {
+        // 1000 Hz at 120 BPM: 500 samples per quarter note
+        let mut transport = Transport::new(1000.0);
+        transport.time_signatures_mut().add_change(1, 3, 4);
+        transport.set_cycle(Some((4.0, 6.0)));
+        transport.set_cycle_active(true);
+        transport.locate(5.0);
+
+        let stopped = transport.next_block(400);
+        assert!(!stopped.context.is_playing());
+        assert_eq!(transport.position_samples(), 2500);
+        assert_eq!(transport.continuous_time_samples(), 400);
+
+        transport.play();
+        let blocks: Vec<_> = (0..3).map(|_| transport.next_block(400)).collect();
+        let summary: Vec<_> = blocks
+            .iter()
+            .map(|block| {
+                (
+                    block.num_samples,
+                    block.context.project_time_samples,
+                    block.context.project_time_music,
+                    block.context.bar_position_music,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (400, 2500, 5.0, 4.0),
+                (100, 2900, 5.8, 4.0),
+                (400, 2000, 4.0, 4.0)
+            ]
+        );
+        let context = blocks[1].context;
+        assert!(context.is_cycle_active());
+        assert_eq!(context.continous_time_samples, 800);
+        assert_eq!(context.time_signature(), Some((3, 4)));
+        assert_eq!(context.samples_to_next_clock(), Some(17));
+    }
+
+    #[test]
+    fn follows_the_tempo_map() {
+        let mut transport = Transport::new(1000.0);
+        transport.tempo_map_mut().add_change(1.0, 60.0);
+        transport.play();
+        transport.next_block(500);
+        let block = transport.next_block(500);
+        assert_eq!(block.context.project_time_music, 1.0);
+        assert_eq!(block.context.tempo, 60.0);
+        assert_eq!(transport.position_music(), 1.5);
+        transport.locate_bar(1);
+        assert_eq!(transport.position_samples(), 3500);
+    }
+}
@@ -0,0 +1,660 @@
+//! Conversion between MIDI 1.0 messages and VST 3 events
+//!
+//! VST 3 has no MIDI events: notes, poly pressure and SysEx become [`EventKind`]s,
+//! controllers (CC, channel pressure, pitch bend) become parameter changes through
+//! the plug-in's `IMidiMapping`, so [`MidiToEvents::convert`] reports them as
+//! [`ConvertedMidi::Controller`]. In the other direction plug-ins send MIDI
+//! controllers to the host as [`LegacyMIDICCOutEvent`]s.
+use crate::plugininterfaces::vst::{
+    ivstevents::{
+        DataTypes, EventKind, LegacyMIDICCOutEvent, NoteOffEvent, NoteOnEvent, PolyPressureEvent,
+    },
+    ivstmidicontrollers::{
+        kAfterTouch, kCtrlPolyPressure, kCtrlProgramChange, kPitchBend, CtrlNumber,
+    },
+    vsttypes::ParamValue,
+};
+use std::iter::FusedIterator;
+
+/// MIDI 1.0 channel voice message or system exclusive message
+///
+/// Channels are in `0..16`, data values in `0..128`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MidiMessage<'a> {
+    NoteOff {
+        channel: u8,
+        pitch: u8,
+        velocity: u8,
+    },
+    NoteOn {
+        channel: u8,
+        pitch: u8,
+        velocity: u8,
+    },
+    PolyPressure {
+        channel: u8,
+        pitch: u8,
+        pressure: u8,
+    },
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    ProgramChange {
+        channel: u8,
+        program: u8,
+    },
+    ChannelPressure {
+        channel: u8,
+        pressure: u8,
+    },
+    /// 14 bit value, 8192 is the center
+    PitchBend {
+        channel: u8,
+        value: u16,
+    },
+    /// Complete message, from `0xF0` to `0xF7`
+    SysEx(&'a [u8]),
+}
+impl<'a> MidiMessage<'a> {
+    /// Parses the message at the start of `bytes` (no running status),
+    /// returns it with the count of bytes it takes
+    pub fn parse(bytes: &'a [u8]) -> Option<(Self, usize)> {
+        let (&status, data) = bytes.split_first()?;
+        if status == 0xF0 {
+            return Self::parse_sysex(bytes);
+        }
+        let (message, len) = Self::parse_with_status(status, data)?;
+        Some((message, len + 1))
+    }
+    /// Parses the SysEx message at the start of `bytes`, up to and including `0xF7`
+    fn parse_sysex(bytes: &'a [u8]) -> Option<(Self, usize)> {
+        let len = bytes.iter().position(|&byte| byte == 0xF7)? + 1;
+        Some((MidiMessage::SysEx(&bytes[..len]), len))
+    }
+    /// Parses the data bytes of a channel message with the given `status`,
+    /// returns it with the count of data bytes it takes
+    fn parse_with_status(status: u8, data: &'a [u8]) -> Option<(Self, usize)> {
+        let channel = status & 0x0F;
+        let len = data_len(status)?;
+        let data = data.get(..len)?;
+        if data.iter().any(|&byte| byte >= 0x80) {
+            return None;
+        }
+        let message = match status & 0xF0 {
+            0x80 => MidiMessage::NoteOff {
+                channel,
+                pitch: data[0],
+                velocity: data[1],
+            },
+            0x90 => MidiMessage::NoteOn {
+                channel,
+                pitch: data[0],
+                velocity: data[1],
+            },
+            0xA0 => MidiMessage::PolyPressure {
+                channel,
+                pitch: data[0],
+                pressure: data[1],
+            },
+            0xB0 => MidiMessage::ControlChange {
+                channel,
+                controller: data[0],
+                value: data[1],
+            },
+            0xC0 => MidiMessage::ProgramChange {
+                channel,
+                program: data[0],
+            },
+            0xD0 => MidiMessage::ChannelPressure {
+                channel,
+                pressure: data[0],
+            },
+            0xE0 => MidiMessage::PitchBend {
+                channel,
+                value: data[0] as u16 | (data[1] as u16) << 7,
+            },
+            _ => return None,
+        };
+        Some((message, len))
+    }
+    /// Channel of a channel voice message
+    pub fn channel(&self) -> Option<u8> {
+        match *self {
+            MidiMessage::NoteOff { channel, .. }
+            | MidiMessage::NoteOn { channel, .. }
+            | MidiMessage::PolyPressure { channel, .. }
+            | MidiMessage::ControlChange { channel, .. }
+            | MidiMessage::ProgramChange { channel, .. }
+            | MidiMessage::ChannelPressure { channel, .. }
+            | MidiMessage::PitchBend { channel, .. } => Some(channel),
+            MidiMessage::SysEx(_) => None,
+        }
+    }
+    /// Copy of a channel voice message which doesn't borrow, `None` for SysEx
+    pub fn to_static(&self) -> Option<MidiMessage<'static>> {
+        Some(match *self {
+            MidiMessage::NoteOff {
+                channel,
+                pitch,
+                velocity,
+            } => MidiMessage::NoteOff {
+                channel,
+                pitch,
+                velocity,
+            },
+            MidiMessage::NoteOn {
+                channel,
+                pitch,
+                velocity,
+            } => MidiMessage::NoteOn {
+                channel,
+                pitch,
+                velocity,
+            },
+            MidiMessage::PolyPressure {
+                channel,
+                pitch,
+                pressure,
+            } => MidiMessage::PolyPressure {
+                channel,
+                pitch,
+                pressure,
+            },
+            MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            } => MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            },
+            MidiMessage::ProgramChange { channel, program } => {
+                MidiMessage::ProgramChange { channel, program }
+            }
+            MidiMessage::ChannelPressure { channel, pressure } => {
+                MidiMessage::ChannelPressure { channel, pressure }
+            }
+            MidiMessage::PitchBend { channel, value } => MidiMessage::PitchBend { channel, value },
+            MidiMessage::SysEx(_) => return None,
+        })
+    }
+    /// Channel, controller number and normalized value of a controller message: control
+    /// change, program change ([`kCtrlProgramChange`]), channel pressure ([`kAfterTouch`])
+    /// or pitch bend ([`kPitchBend`])
+    pub fn controller(&self) -> Option<(i16, CtrlNumber, ParamValue)> {
+        let (channel, controller, value) = match *self {
+            MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            } => (channel, controller as CtrlNumber, value as f64 / 127.0),
+            MidiMessage::ProgramChange { channel, program } => {
+                (channel, kCtrlProgramChange, program as f64 / 127.0)
+            }
+            MidiMessage::ChannelPressure { channel, pressure } => {
+                (channel, kAfterTouch, pressure as f64 / 127.0)
+            }
+            MidiMessage::PitchBend { channel, value } => {
+                (channel, kPitchBend, value.min(0x3FFF) as f64 / 16383.0)
+            }
+            _ => return None,
+        };
+        Some((channel as i16, controller, value))
+    }
+    /// Appends the bytes of the message to `out`, always with a status byte
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        let mut channel_message = |status: u8, channel: u8, data: &[u8]| {
+            out.push(status | (channel & 0x0F));
+            out.extend(data.iter().map(|byte| byte & 0x7F));
+        };
+        match *self {
+            MidiMessage::NoteOff {
+                channel,
+                pitch,
+                velocity,
+            } => channel_message(0x80, channel, &[pitch, velocity]),
+            MidiMessage::NoteOn {
+                channel,
+                pitch,
+                velocity,
+            } => channel_message(0x90, channel, &[pitch, velocity]),
+            MidiMessage::PolyPressure {
+                channel,
+                pitch,
+                pressure,
+            } => channel_message(0xA0, channel, &[pitch, pressure]),
+            MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            } => channel_message(0xB0, channel, &[controller, value]),
+            MidiMessage::ProgramChange { channel, program } => {
+                channel_message(0xC0, channel, &[program])
+            }
+            MidiMessage::ChannelPressure { channel, pressure } => {
+                channel_message(0xD0, channel, &[pressure])
+            }
+            MidiMessage::PitchBend { channel, value } => {
+                channel_message(0xE0, channel, &[value as u8, (value >> 7) as u8])
+            }
+            MidiMessage::SysEx(bytes) => out.extend_from_slice(bytes),
+        }
+    }
+}
+
+/// Count of data bytes following a status byte, `None` for SysEx and undefined status bytes
+fn data_len(status: u8) -> Option<usize> {
+    match status {
+        0x80..=0xBF | 0xE0..=0xEF | 0xF2 => Some(2),
+        0xC0..=0xDF | 0xF1 | 0xF3 => Some(1),
+        0xF6..=0xFF => Some(0),
+        _ => None,
+    }
+}
+
+/// Iterates over the messages of a MIDI 1.0 byte stream, see [`messages`]
+pub struct MidiMessages<'a> {
+    bytes: &'a [u8],
+    running_status: Option<u8>,
+}
+impl<'a> Iterator for MidiMessages<'a> {
+    type Item = MidiMessage<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(&first) = self.bytes.first() {
+            let (status, data) = if first >= 0x80 {
+                (first, &self.bytes[1..])
+            } else if let Some(status) = self.running_status {
+                (status, self.bytes)
+            } else {
+                // Data byte without a status, skip it
+                self.bytes = &self.bytes[1..];
+                continue;
+            };
+            let status_len = (first >= 0x80) as usize;
+            match status {
+                0x80..=0xEF => self.running_status = Some(status),
+                // Real-time messages don't touch the running status
+                0xF8..=0xFF => {}
+                _ => self.running_status = None,
+            }
+            let message = if status == 0xF0 {
+                MidiMessage::parse_sysex(self.bytes)
+                    .map(|(message, len)| (message, len - status_len))
+            } else {
+                MidiMessage::parse_with_status(status, data)
+            };
+            match message {
+                Some((message, len)) => {
+                    self.bytes = &self.bytes[status_len + len..];
+                    return Some(message);
+                }
+                None => {
+                    // System common and real-time messages aren't converted, skip them.
+                    // Anything else is incomplete or malformed, resynchronize on the next status byte
+                    let len = match data_len(status) {
+                        Some(len) if status >= 0xF0 => len.min(data.len()),
+                        _ => data
+                            .iter()
+                            .position(|&byte| byte >= 0x80)
+                            .unwrap_or(data.len()),
+                    };
+                    self.bytes = &self.bytes[(status_len + len).max(1)..];
+                }
+            }
+        }
+        None
+    }
+}
+impl FusedIterator for MidiMessages<'_> {}
+
+/// Iterates over the messages of a MIDI 1.0 byte stream, handling running status
+///
+/// System common and real-time messages are skipped.
+pub fn messages(bytes: &[u8]) -> MidiMessages<'_> {
+    MidiMessages {
+        bytes,
+        running_status: None,
+    }
+}
+
+/// Result of [`MidiToEvents::convert`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConvertedMidi<'a> {
+    /// Event to add to the input event list of the processor
+    Event(EventKind<'a>),
+    /// Controller to map to a parameter through `IMidiMapping`. Pitch bend and channel pressure
+    /// use [`kPitchBend`] and [`kAfterTouch`], program changes use [`kCtrlProgramChange`]
+    /// and should go to the program change parameter of the unit.
+    Controller {
+        channel: i16,
+        controller: CtrlNumber,
+        value: ParamValue,
+    },
+}
+
+/// Converts incoming MIDI 1.0 messages to VST 3 events and controllers
+///
+/// Note IDs are -1 unless created with [`MidiToEvents::with_note_ids`], in which
+/// case each note-on gets a new ID which is reused by its poly pressure and note-off.
+pub struct MidiToEvents {
+    note_ids: Option<Box<[[i32; 128]; 16]>>,
+    next_note_id: i32,
+}
+impl Default for MidiToEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl MidiToEvents {
+    pub fn new() -> Self {
+        Self {
+            note_ids: None,
+            next_note_id: 0,
+        }
+    }
+    pub fn with_note_ids() -> Self {
+        Self {
+            note_ids: Some(Box::new([[-1; 128]; 16])),
+            next_note_id: 0,
+        }
+    }
+    /// Forgets the IDs of sounding notes
+    pub fn reset(&mut self) {
+        if let Some(note_ids) = &mut self.note_ids {
+            **note_ids = [[-1; 128]; 16];
+        }
+    }
+    fn note_id(&mut self, channel: u8, pitch: u8, note_on: Option<bool>) -> i32 {
+        let Some(note_ids) = &mut self.note_ids else {
+            return -1;
+        };
+        let slot = &mut note_ids[channel as usize & 0x0F][pitch as usize & 0x7F];
+        match note_on {
+            Some(true) => {
+                *slot = self.next_note_id;
+                self.next_note_id = self.next_note_id.wrapping_add(1) & i32::MAX;
+                *slot
+            }
+            Some(false) => std::mem::replace(slot, -1),
+            None => *slot,
+        }
+    }
+    pub fn convert<'a>(&mut self, message: &MidiMessage<'a>) -> Option<ConvertedMidi<'a>> {
+        Some(match *message {
+            MidiMessage::NoteOn {
+                channel,
+                pitch,
+                velocity,
+            } if velocity > 0 => ConvertedMidi::Event(EventKind::NoteOn(NoteOnEvent {
+                channel: channel as i16,
+                pitch: pitch as i16,
+                tuning: 0.0,
+                velocity: velocity as f32 / 127.0,
+                length: 0,
+                note_id: self.note_id(channel, pitch, Some(true)),
+            })),
+            // A note-on with a velocity of 0 is a note-off
+            MidiMessage::NoteOn { channel, pitch, .. } => self.note_off(channel, pitch, 64),
+            MidiMessage::NoteOff {
+                channel,
+                pitch,
+                velocity,
+            } => self.note_off(channel, pitch, velocity),
+            MidiMessage::PolyPressure {
+                channel,
+                pitch,
+                pressure,
+            } => ConvertedMidi::Event(EventKind::PolyPressure(PolyPressureEvent {
+                channel: channel as i16,
+                pitch: pitch as i16,
+                pressure: pressure as f32 / 127.0,
+                note_id: self.note_id(channel, pitch, None),
+            })),
+            MidiMessage::ControlChange { .. }
+            | MidiMessage::ProgramChange { .. }
+            | MidiMessage::ChannelPressure { .. }
+            | MidiMessage::PitchBend { .. } => {
+                let (channel, controller, value) = message.controller()?;
+                ConvertedMidi::Controller {
+                    channel,
+                    controller,
+                    value,
+                }
+            }
+            MidiMessage::SysEx(bytes) => ConvertedMidi::Event(EventKind::sysex(bytes)),
+        })
+    }
+    fn note_off<'a>(&mut self, channel: u8, pitch: u8, velocity: u8) -> ConvertedMidi<'a> {
+        ConvertedMidi::Event(EventKind::NoteOff(NoteOffEvent {
+            channel: channel as i16,
+            pitch: pitch as i16,
+            velocity: velocity as f32 / 127.0,
+            note_id: self.note_id(channel, pitch, Some(false)),
+            tuning: 0.0,
+        }))
+    }
+}
+
+fn to_7bit(value: f64) -> u8 {
+    (value * 127.0).round().clamp(0.0, 127.0) as u8
+}
+
+/// Converts an event sent by a plug-in to MIDI, `None` for events without MIDI 1.0 equivalent
+///
+/// Tuning and note IDs are dropped, note-ons keep a velocity of at least 1.
+pub fn event_to_midi<'a>(kind: &EventKind<'a>) -> Option<MidiMessage<'a>> {
+    Some(match *kind {
+        EventKind::NoteOn(event) => MidiMessage::NoteOn {
+            channel: event.channel as u8 & 0x0F,
+            pitch: event.pitch as u8 & 0x7F,
+            velocity: to_7bit(event.velocity as f64).max(1),
+        },
+        EventKind::NoteOff(event) => MidiMessage::NoteOff {
+            channel: event.channel as u8 & 0x0F,
+            pitch: event.pitch as u8 & 0x7F,
+            velocity: to_7bit(event.velocity as f64),
+        },
+        EventKind::PolyPressure(event) => MidiMessage::PolyPressure {
+            channel: event.channel as u8 & 0x0F,
+            pitch: event.pitch as u8 & 0x7F,
+            pressure: to_7bit(event.pressure as f64),
+        },
+        EventKind::Data { type_, bytes } if type_ == DataTypes::MidiSysEx as u32 => {
+            MidiMessage::SysEx(bytes)
+        }
+        EventKind::LegacyMIDICCOut(event) => {
+            let channel = event.channel as u8 & 0x0F;
+            let value = event.value as u8 & 0x7F;
+            let value2 = event.value2 as u8 & 0x7F;
+            let controller = event.control_number as CtrlNumber;
+            if controller < 128 {
+                MidiMessage::ControlChange {
+                    channel,
+                    controller: controller as u8,
+                    value,
+                }
+            } else if controller == kAfterTouch {
+                MidiMessage::ChannelPressure {
+                    channel,
+                    pressure: value,
+                }
+            } else if controller == kPitchBend {
+                MidiMessage::PitchBend {
+                    channel,
+                    value: value as u16 | (value2 as u16) << 7,
+                }
+            } else if controller == kCtrlProgramChange {
+                MidiMessage::ProgramChange {
+                    channel,
+                    program: value,
+                }
+            } else if controller == kCtrlPolyPressure {
+                MidiMessage::PolyPressure {
+                    channel,
+                    pitch: value,
+                    pressure: value2,
+                }
+            } else {
+                return None;
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// Converts a MIDI controller, program change or pressure message to the event a
+/// plug-in sends to output it, `None` for notes and SysEx which have their own events
+pub fn legacy_midi_cc_out(message: &MidiMessage) -> Option<LegacyMIDICCOutEvent> {
+    let (channel, control_number, value, value2) = match *message {
+        MidiMessage::ControlChange {
+            channel,
+            controller,
+            value,
+        } => (channel, controller as CtrlNumber, value, 0),
+        MidiMessage::ChannelPressure { channel, pressure } => (channel, kAfterTouch, pressure, 0),
+        MidiMessage::PitchBend { channel, value } => (
+            channel,
+            kPitchBend,
+            value as u8 & 0x7F,
+            (value >> 7) as u8 & 0x7F,
+        ),
+        MidiMessage::ProgramChange { channel, program } => {
+            (channel, kCtrlProgramChange, program, 0)
+        }
+        MidiMessage::PolyPressure {
+            channel,
+            pitch,
+            pressure,
+        } => (channel, kCtrlPolyPressure, pitch, pressure),
+        _ => return None,
+    };
+    Some(LegacyMIDICCOutEvent {
+        control_number: control_number as u8,
+        channel: (channel & 0x0F) as i8,
+        value: (value & 0x7F) as i8,
+        value2: (value2 & 0x7F) as i8,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stream_with_running_status() {
+        let bytes = [
+            0x91, 60, 100, 62, 0,    // note-on, then a note-off through running status
+            0xF8, // clock, skipped without breaking the running status
+            64, 90, 0xF0, 0x7E, 0x01, 0xF7, // SysEx
+            0xE2, 0x00, 0x40, // pitch bend center
+            0xC2, 5, 6, // program changes through running status
+        ];
+        let parsed: Vec<_> = messages(&bytes).collect();
+        assert_eq!(
+            parsed,
+            [
+                MidiMessage::NoteOn {
+                    channel: 1,
+                    pitch: 60,
+                    velocity: 100
+                },
+                MidiMessage::NoteOn {
+                    channel: 1,
+                    pitch: 62,
+                    velocity: 0
+                },
+                MidiMessage::NoteOn {
+                    channel: 1,
+                    pitch: 64,
+                    velocity: 90
+                },
+                MidiMessage::SysEx(&[0xF0, 0x7E, 0x01, 0xF7]),
+                MidiMessage::PitchBend {
+                    channel: 2,
+                    value: 8192
+                },
+                MidiMessage::ProgramChange {
+                    channel: 2,
+                    program: 5
+                },
+                MidiMessage::ProgramChange {
+                    channel: 2,
+                    program: 6
+                },
+            ]
+        );
+        let mut encoded = Vec::new();
+        parsed[4].encode(&mut encoded);
+        assert_eq!(encoded, [0xE2, 0x00, 0x40]);
+    }
+
+    #[test]
+    fn note_ids_and_controllers() {
+        let mut converter = MidiToEvents::with_note_ids();
+        let note_on = MidiMessage::NoteOn {
+            channel: 0,
+            pitch: 60,
+            velocity: 127,
+        };
+        let Some(ConvertedMidi::Event(EventKind::NoteOn(on))) = converter.convert(&note_on) else {
+            panic!("note-on expected");
+        };
+        assert_eq!((on.note_id, on.velocity), (0, 1.0));
+        let note_off = MidiMessage::NoteOn {
+            channel: 0,
+            pitch: 60,
+            velocity: 0,
+        };
+        let Some(ConvertedMidi::Event(EventKind::NoteOff(off))) = converter.convert(&note_off)
+        else {
+            panic!("note-off expected");
+        };
+        assert_eq!(off.note_id, 0);
+        assert_eq!(
+            converter.convert(&MidiMessage::ChannelPressure {
+                channel: 3,
+                pressure: 127
+            }),
+            Some(ConvertedMidi::Controller {
+                channel: 3,
+                controller: kAfterTouch,
+                value: 1.0
+            })
+        );
+    }
+
+    #[test]
+    fn plugin_output() {
+        let bend = MidiMessage::PitchBend {
+            channel: 4,
+            value: 0x2001,
+        };
+        let event = legacy_midi_cc_out(&bend).unwrap();
+        assert_eq!((event.value, event.value2), (0x01, 0x40));
+        assert_eq!(
+            event_to_midi(&EventKind::LegacyMIDICCOut(event)),
+            Some(bend)
+        );
+
+        let note_on = EventKind::NoteOn(NoteOnEvent {
+            channel: 2,
+            pitch: 61,
+            tuning: 0.0,
+            velocity: 0.001,
+            length: 0,
+            note_id: 3,
+        });
+        assert_eq!(
+            event_to_midi(&note_on),
+            Some(MidiMessage::NoteOn {
+                channel: 2,
+                pitch: 61,
+                velocity: 1
+            })
+        );
+    }
+}
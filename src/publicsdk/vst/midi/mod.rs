@@ -0,0 +1,3 @@
+pub mod midi1;
+pub mod smf;
+pub mod ump;
@@ -0,0 +1,464 @@
+//! Standard MIDI File (type 0 and 1) reading and playback into per-block event lists
+//!
+//! [`MidiFile::parse`] reads the tracks of a file, [`SmfPlayer`] schedules its
+//! events at sample accurate offsets for consecutive processing blocks. Notes and
+//! SysEx go to an [`IEventList`], controllers to an [`IParameterChanges`] through
+//! the plug-in's MIDI mapping.
+use super::midi1::{ConvertedMidi, MidiMessage, MidiToEvents};
+use crate::{
+    plugininterfaces::{
+        base::EInterface,
+        vst::{
+            ivstevents::{Event, EventKind, IEventList},
+            ivstparameterchanges::IParameterChanges,
+            vsttypes::{TQuarterNotes, TSamples},
+        },
+    },
+    publicsdk::vst::{hosting::midimapping::MidiControllerRouter, utility::tempomap::TempoMap},
+};
+use std::fmt;
+
+/// Error while reading a MIDI file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmfError {
+    /// The file doesn't start with a valid `MThd` chunk
+    InvalidHeader,
+    /// Type 2 files (independent sequences) aren't supported
+    UnsupportedFormat(u16),
+    /// SMPTE based time division isn't supported
+    UnsupportedTimeDivision,
+    /// The track at the given index is truncated or malformed
+    InvalidTrack(usize),
+}
+impl fmt::Display for SmfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SmfError::InvalidHeader => write!(f, "Invalid MIDI file header"),
+            SmfError::UnsupportedFormat(format) => write!(f, "Unsupported MIDI file type {format}"),
+            SmfError::UnsupportedTimeDivision => write!(f, "Unsupported SMPTE time division"),
+            SmfError::InvalidTrack(track) => write!(f, "Invalid MIDI file track {track}"),
+        }
+    }
+}
+impl std::error::Error for SmfError {}
+
+/// Content of a [`SmfEvent`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SmfEventKind {
+    /// Channel voice message
+    Message(MidiMessage<'static>),
+    /// System exclusive message, from `0xF0` to `0xF7`
+    SysEx(Box<[u8]>),
+    /// Tempo meta event, in microseconds per quarter note
+    Tempo(u32),
+}
+
+/// Event of a MIDI file track
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmfEvent {
+    /// Absolute position in ticks
+    pub tick: u64,
+    /// Index of the track the event belongs to
+    pub track: usize,
+    pub kind: SmfEventKind,
+}
+
+/// Events of a type 0 or type 1 Standard MIDI File, merged into one list sorted by tick
+#[derive(Clone, Debug)]
+pub struct MidiFile {
+    format: u16,
+    track_count: usize,
+    ticks_per_quarter: u16,
+    events: Vec<SmfEvent>,
+}
+
+/// Big endian reader over the bytes of a file
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(taken)
+    }
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.take(2)?.try_into().ok()?))
+    }
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+    /// Variable length quantity, at most 4 bytes
+    fn var_len(&mut self) -> Option<u32> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = value << 7 | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+impl MidiFile {
+    pub fn parse(bytes: &[u8]) -> Result<Self, SmfError> {
+        let mut reader = Reader { bytes };
+        let header = (|| {
+            if reader.take(4)? != b"MThd" {
+                return None;
+            }
+            let len = reader.u32()? as usize;
+            let mut header = Reader {
+                bytes: reader.take(len)?,
+            };
+            Some((header.u16()?, header.u16()?, header.u16()?))
+        })();
+        let (format, track_count, division) = header.ok_or(SmfError::InvalidHeader)?;
+        if format > 1 {
+            return Err(SmfError::UnsupportedFormat(format));
+        }
+        if division & 0x8000 != 0 || division == 0 {
+            return Err(SmfError::UnsupportedTimeDivision);
+        }
+        let mut events = Vec::new();
+        let mut track = 0;
+        while track < track_count as usize {
+            let (Some(id), Some(len)) = (reader.take(4), reader.u32()) else {
+                return Err(SmfError::InvalidTrack(track));
+            };
+            let chunk = reader
+                .take(len as usize)
+                .ok_or(SmfError::InvalidTrack(track))?;
+            // Unknown chunks are skipped
+            if id == b"MTrk" {
+                Self::parse_track(chunk, track, &mut events)
+                    .ok_or(SmfError::InvalidTrack(track))?;
+                track += 1;
+            }
+        }
+        // Stable, so events at the same tick keep the track order
+        events.sort_by_key(|event| event.tick);
+        Ok(Self {
+            format,
+            track_count: track_count as usize,
+            ticks_per_quarter: division,
+            events,
+        })
+    }
+    fn parse_track(bytes: &[u8], track: usize, events: &mut Vec<SmfEvent>) -> Option<()> {
+        let mut reader = Reader { bytes };
+        let mut tick = 0u64;
+        let mut running_status = None;
+        while !reader.bytes.is_empty() {
+            tick += reader.var_len()? as u64;
+            let mut status = reader.u8()?;
+            let kind = match status {
+                // Meta event
+                0xFF => {
+                    let type_ = reader.u8()?;
+                    let len = reader.var_len()? as usize;
+                    let data = reader.take(len)?;
+                    match (type_, data) {
+                        (0x2F, _) => break,
+                        (0x51, &[a, b, c]) => SmfEventKind::Tempo(u32::from_be_bytes([0, a, b, c])),
+                        _ => continue,
+                    }
+                }
+                // SysEx, the length doesn't include the leading 0xF0
+                0xF0 => {
+                    let len = reader.var_len()? as usize;
+                    let data = reader.take(len)?;
+                    let mut sysex = Vec::with_capacity(len + 2);
+                    sysex.push(0xF0);
+                    sysex.extend_from_slice(data);
+                    if sysex.last() != Some(&0xF7) {
+                        sysex.push(0xF7);
+                    }
+                    SmfEventKind::SysEx(sysex.into_boxed_slice())
+                }
+                // Escaped bytes, not converted
+                0xF7 => {
+                    let len = reader.var_len()? as usize;
+                    reader.take(len)?;
+                    continue;
+                }
+                _ => {
+                    let mut message = [0; 3];
+                    if status < 0x80 {
+                        // Running status, the byte read is the first data byte
+                        message[1] = status;
+                        status = running_status?;
+                    } else {
+                        message[1] = reader.u8()?;
+                        running_status = Some(status);
+                    }
+                    message[0] = status;
+                    if !matches!(status & 0xF0, 0xC0 | 0xD0) {
+                        message[2] = reader.u8()?;
+                    }
+                    let (message, _) = MidiMessage::parse(&message)?;
+                    SmfEventKind::Message(message.to_static()?)
+                }
+            };
+            events.push(SmfEvent { tick, track, kind });
+        }
+        Some(())
+    }
+    /// 0 for a single track file, 1 for simultaneous tracks
+    pub fn format(&self) -> u16 {
+        self.format
+    }
+    pub fn track_count(&self) -> usize {
+        self.track_count
+    }
+    pub fn ticks_per_quarter(&self) -> u16 {
+        self.ticks_per_quarter
+    }
+    /// Events of all tracks, sorted by tick
+    pub fn events(&self) -> &[SmfEvent] {
+        &self.events
+    }
+    /// Position of `tick` in quarter notes
+    pub fn ppq_position(&self, tick: u64) -> TQuarterNotes {
+        tick as f64 / self.ticks_per_quarter as f64
+    }
+    /// Tempo map built from the tempo events of the file, 120 BPM until the first one
+    pub fn tempo_map(&self) -> TempoMap {
+        let mut tempo_map = TempoMap::default();
+        for event in &self.events {
+            if let SmfEventKind::Tempo(micros_per_quarter) = event.kind {
+                if micros_per_quarter > 0 {
+                    tempo_map.add_change(
+                        self.ppq_position(event.tick),
+                        60_000_000.0 / micros_per_quarter as f64,
+                    );
+                }
+            }
+        }
+        tempo_map
+    }
+}
+
+/// Event of a [`SmfPlayer`] with its position
+struct ScheduledEvent {
+    sample_position: TSamples,
+    ppq_position: TQuarterNotes,
+    kind: SmfEventKind,
+}
+
+/// Plays a [`MidiFile`] block by block on event bus 0
+///
+/// Note IDs are assigned to notes, controllers are routed to parameters if a
+/// [`MidiControllerRouter`] is set and dropped otherwise.
+pub struct SmfPlayer {
+    events: Vec<ScheduledEvent>,
+    next_event: usize,
+    block_size: usize,
+    position: TSamples,
+    converter: MidiToEvents,
+    router: Option<MidiControllerRouter>,
+}
+impl SmfPlayer {
+    /// Schedules the events of `file` with the tempo map of the file
+    pub fn new(file: &MidiFile, sample_rate: f64, block_size: usize) -> Self {
+        Self::with_tempo_map(file, &file.tempo_map(), sample_rate, block_size)
+    }
+    /// Schedules the events of `file` with the given tempo map, ignoring the tempo events of the file
+    pub fn with_tempo_map(
+        file: &MidiFile,
+        tempo_map: &TempoMap,
+        sample_rate: f64,
+        block_size: usize,
+    ) -> Self {
+        let events = file
+            .events()
+            .iter()
+            .filter(|event| !matches!(event.kind, SmfEventKind::Tempo(_)))
+            .map(|event| {
+                let ppq_position = file.ppq_position(event.tick);
+                ScheduledEvent {
+                    sample_position: tempo_map.samples_at(ppq_position, sample_rate).round()
+                        as TSamples,
+                    ppq_position,
+                    kind: event.kind.clone(),
+                }
+            })
+            .collect();
+        Self {
+            events,
+            next_event: 0,
+            block_size: block_size.max(1),
+            position: 0,
+            converter: MidiToEvents::with_note_ids(),
+            router: None,
+        }
+    }
+    /// Routes controllers to parameters with `router`
+    pub fn set_router(&mut self, router: MidiControllerRouter) {
+        self.router = Some(router);
+    }
+    /// Sample position of the next block
+    pub fn position(&self) -> TSamples {
+        self.position
+    }
+    /// Returns true once all events have been played
+    pub fn is_finished(&self) -> bool {
+        self.next_event >= self.events.len()
+    }
+    /// Sample position of the last event
+    pub fn length(&self) -> TSamples {
+        self.events.last().map_or(0, |event| event.sample_position)
+    }
+    /// Restarts playback from the beginning
+    pub fn rewind(&mut self) {
+        self.next_event = 0;
+        self.position = 0;
+        self.converter.reset();
+    }
+    /// Adds the events of the next block to `events` and `changes` and advances by the block size
+    pub fn process_block(
+        &mut self,
+        events: &IEventList,
+        changes: &IParameterChanges,
+    ) -> Result<(), EInterface> {
+        let block_end = self.position + self.block_size as TSamples;
+        while let Some(scheduled) = self.events.get(self.next_event) {
+            if scheduled.sample_position >= block_end {
+                break;
+            }
+            self.next_event += 1;
+            let sample_offset = (scheduled.sample_position - self.position).max(0) as i32;
+            let converted = match &scheduled.kind {
+                SmfEventKind::Message(message) => self.converter.convert(message),
+                SmfEventKind::SysEx(bytes) => Some(ConvertedMidi::Event(EventKind::sysex(bytes))),
+                SmfEventKind::Tempo(_) => None,
+            };
+            match converted {
+                Some(ConvertedMidi::Event(kind)) => {
+                    let mut event = Event::new(0, sample_offset, kind);
+                    event.ppq_position = scheduled.ppq_position;
+                    events.add_event(&event)?;
+                }
+                Some(ConvertedMidi::Controller {
+                    channel,
+                    controller,
+                    value,
+                }) => {
+                    if let Some(router) = &self.router {
+                        router.route(0, channel, controller, value, sample_offset, changes)?;
+                    }
+                }
+                None => {}
+            }
+        }
+        self.position = block_end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publicsdk::vst::hosting::{
+        eventlist::EventList, parameterchanges::ParameterChanges,
+    };
+
+    fn chunk(id: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = id.to_vec();
+        chunk.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        chunk.extend_from_slice(data);
+        chunk
+    }
+
+    /// Type 1 file at 96 ticks per quarter: a tempo track switching from 120 to 60 BPM
+    /// after one quarter and a note track with notes on every quarter
+    fn test_file() -> Vec<u8> {
+        let mut file = chunk(b"MThd", &[0, 1, 0, 2, 0, 96]);
+        file.extend(chunk(
+            b"MTrk",
+            &[
+                0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, // 500000 us per quarter
+                0x60, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, // 1000000 us per quarter
+                0x00, 0xFF, 0x2F, 0x00,
+            ],
+        ));
+        file.extend(chunk(
+            b"MTrk",
+            &[
+                0x00, 0x90, 60, 100, // note-on at 0
+                0x60, 60, 0, // note-off at one quarter through running status
+                0x00, 0x90, 62, 100, // note-on at one quarter
+                0x60, 0x80, 62, 0, // note-off at two quarters
+                0x00, 0xF0, 0x03, 0x7E, 0x01, 0xF7, // SysEx at two quarters
+                0x00, 0xFF, 0x2F, 0x00,
+            ],
+        ));
+        file
+    }
+
+    #[test]
+    fn parse() {
+        let file = MidiFile::parse(&test_file()).unwrap();
+        assert_eq!((file.format(), file.track_count()), (1, 2));
+        assert_eq!(file.events().len(), 7);
+        assert_eq!(file.tempo_map().seconds_at(2.0), 1.5);
+        assert_eq!(
+            file.events()[3].kind,
+            SmfEventKind::Message(MidiMessage::NoteOn {
+                channel: 0,
+                pitch: 60,
+                velocity: 0
+            })
+        );
+        assert_eq!(
+            MidiFile::parse(b"MThd\0\0\0\x06\0\x02\0\x01\0\x60").unwrap_err(),
+            SmfError::UnsupportedFormat(2)
+        );
+    }
+
+    #[test]
+    fn play_blocks() {
+        let file = MidiFile::parse(&test_file()).unwrap();
+        // 1000 Hz: the first quarter lasts 500 samples, the second 1000
+        let mut player = SmfPlayer::new(&file, 1000.0, 400);
+        let mut events = EventList::new(8, 16);
+        let changes = ParameterChanges::new(1, 1);
+        let mut played = Vec::new();
+        while !player.is_finished() {
+            events.clear();
+            player
+                .process_block(&events.as_interface(), &changes.as_interface())
+                .unwrap();
+            let block_start = player.position() - 400;
+            for (event, kind) in events.as_interface().events() {
+                let type_ = match kind {
+                    EventKind::NoteOn(_) => "NoteOn",
+                    EventKind::NoteOff(_) => "NoteOff",
+                    EventKind::Data { .. } => "Data",
+                    _ => "Other",
+                };
+                played.push((
+                    block_start + event.sample_offset as i64,
+                    event.ppq_position,
+                    type_,
+                ));
+            }
+        }
+        let expected = [
+            (0, 0.0, "NoteOn"),
+            (500, 1.0, "NoteOff"),
+            (500, 1.0, "NoteOn"),
+            (1500, 2.0, "NoteOff"),
+            (1500, 2.0, "Data"),
+        ];
+        assert_eq!(played, expected);
+    }
+}
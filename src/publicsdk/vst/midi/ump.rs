@@ -0,0 +1,477 @@
+//! Conversion of MIDI 2.0 Universal MIDI Packets to VST 3 events
+//!
+//! MIDI 2.0 notes map to note events with note IDs, per-note controllers and
+//! per-note pitch bend to note expressions of those IDs. Channel controllers are
+//! reported as [`ConvertedMidi::Controller`], to be routed to parameters through
+//! `IMidiMapping` like MIDI 1.0 controllers. The UMP group is used as event bus index.
+#![allow(non_upper_case_globals)]
+
+use super::midi1::{ConvertedMidi, MidiMessage};
+use crate::plugininterfaces::vst::{
+    ivstevents::{EventKind, NoteOffEvent, NoteOnEvent, PolyPressureEvent},
+    ivstmidicontrollers::{kAfterTouch, kCtrlProgramChange, kPitchBend, CtrlNumber},
+    ivstnoteexpression::{
+        kBrightnessTypeID, kCustomStart, kPanTypeID, kTuningTypeID, kVibratoTypeID, kVolumeTypeID,
+        NoteExpressionTypeID, NoteExpressionValueEvent,
+    },
+};
+
+/// UMP message types
+const kMidi1ChannelVoice: u32 = 0x2;
+const kData64: u32 = 0x3;
+const kMidi2ChannelVoice: u32 = 0x4;
+
+/// Note attribute carrying the pitch of the note as 7.9 fixed point semitones
+const kPitch7_9Attribute: u8 = 0x3;
+/// Registered per-note controller carrying the pitch of the note as 7.25 fixed point semitones
+const kPitch7_25Controller: u8 = 3;
+
+/// Count of 32 bit words of a packet, given its first word
+pub fn packet_len(first_word: u32) -> usize {
+    match first_word >> 28 {
+        0x0..=0x2 | 0x6 | 0x7 => 1,
+        0x3 | 0x4 | 0x8..=0xA => 2,
+        0xB | 0xC => 3,
+        _ => 4,
+    }
+}
+
+/// Splits a stream of 32 bit words into packets, an incomplete packet at the end is dropped
+pub fn packets(words: &[u32]) -> impl Iterator<Item = &[u32]> {
+    let mut words = words;
+    std::iter::from_fn(move || {
+        let len = packet_len(*words.first()?);
+        if words.len() < len {
+            words = &[];
+            return None;
+        }
+        let (packet, rest) = words.split_at(len);
+        words = rest;
+        Some(packet)
+    })
+}
+
+fn normalize_16(value: u32) -> f64 {
+    value as f64 / u16::MAX as f64
+}
+
+fn normalize_32(value: u32) -> f64 {
+    value as f64 / u32::MAX as f64
+}
+
+/// Converts MIDI 2.0 (and MIDI 1.0 in UMP) packets to VST 3 events
+///
+/// Each note-on gets a new note ID which its per-note messages and note-off reuse.
+pub struct UmpToEvents {
+    note_ids: Box<[[[i32; 128]; 16]; 16]>,
+    /// Notes detached from per-note control, one bit per pitch, they keep their ID until the note-off
+    detached: [[u128; 16]; 16],
+    next_note_id: i32,
+    per_note_pitch_bend_range: f64,
+    sysex: Vec<Vec<u8>>,
+}
+impl Default for UmpToEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl UmpToEvents {
+    pub fn new() -> Self {
+        Self {
+            note_ids: Box::new([[[-1; 128]; 16]; 16]),
+            detached: [[0; 16]; 16],
+            next_note_id: 0,
+            per_note_pitch_bend_range: 48.0,
+            sysex: vec![Vec::new(); 16],
+        }
+    }
+    /// Sets the range of per-note pitch bend in semitones, 48 by default
+    pub fn set_per_note_pitch_bend_range(&mut self, semitones: f64) {
+        self.per_note_pitch_bend_range = semitones;
+    }
+    /// Forgets sounding notes and pending SysEx data
+    pub fn reset(&mut self) {
+        *self.note_ids = [[[-1; 128]; 16]; 16];
+        self.detached = [[0; 16]; 16];
+        for sysex in &mut self.sysex {
+            sysex.clear();
+        }
+    }
+    /// Converts one packet, returns the event bus index (the group) with the converted message.
+    /// Messages without VST 3 equivalent and incomplete SysEx return `None`.
+    pub fn convert(&mut self, packet: &[u32]) -> Option<(i32, ConvertedMidi<'_>)> {
+        let first = *packet.first()?;
+        if packet.len() < packet_len(first) {
+            return None;
+        }
+        let group = (first >> 24 & 0x0F) as usize;
+        let converted = match first >> 28 {
+            kMidi1ChannelVoice => self.convert_midi1(group, first)?,
+            kMidi2ChannelVoice => self.convert_midi2(group, first, packet[1])?,
+            kData64 => return self.convert_sysex7(group, first, packet[1]),
+            _ => return None,
+        };
+        Some((group as i32, converted))
+    }
+    fn convert_midi1(&mut self, group: usize, word: u32) -> Option<ConvertedMidi<'static>> {
+        let bytes = [(word >> 16) as u8, (word >> 8) as u8, word as u8];
+        let (message, _) = MidiMessage::parse(&bytes)?;
+        Some(match message {
+            MidiMessage::NoteOn {
+                channel,
+                pitch,
+                velocity,
+            } if velocity > 0 => self.note_on(group, channel, pitch, velocity as f32 / 127.0, 0.0),
+            MidiMessage::NoteOn { channel, pitch, .. } => self.note_off(group, channel, pitch, 0.5),
+            MidiMessage::NoteOff {
+                channel,
+                pitch,
+                velocity,
+            } => self.note_off(group, channel, pitch, velocity as f32 / 127.0),
+            MidiMessage::PolyPressure {
+                channel,
+                pitch,
+                pressure,
+            } => self.poly_pressure(group, channel, pitch, pressure as f32 / 127.0),
+            message => {
+                let (channel, controller, value) = message.controller()?;
+                ConvertedMidi::Controller {
+                    channel,
+                    controller,
+                    value,
+                }
+            }
+        })
+    }
+    fn convert_midi2(
+        &mut self,
+        group: usize,
+        first: u32,
+        data: u32,
+    ) -> Option<ConvertedMidi<'static>> {
+        let status = (first >> 20 & 0x0F) as u8;
+        let channel = (first >> 16 & 0x0F) as u8;
+        let index = (first >> 8 & 0x7F) as u8;
+        let byte4 = first as u8;
+        let controller = |controller: CtrlNumber, value: f64| ConvertedMidi::Controller {
+            channel: channel as i16,
+            controller,
+            value,
+        };
+        Some(match status {
+            // Note on
+            0x9 => {
+                let tuning = if byte4 == kPitch7_9Attribute {
+                    ((data & 0xFFFF) as f32 / 512.0 - index as f32) * 100.0
+                } else {
+                    0.0
+                };
+                let velocity = normalize_16(data >> 16) as f32;
+                // A velocity of 0 is a valid note-on in MIDI 2.0, but not in VST 3
+                self.note_on(group, channel, index, velocity.max(f32::EPSILON), tuning)
+            }
+            // Note off
+            0x8 => self.note_off(group, channel, index, normalize_16(data >> 16) as f32),
+            // Poly pressure
+            0xA => self.poly_pressure(group, channel, index, normalize_32(data) as f32),
+            // Registered per-note controller
+            0x0 => {
+                let value = normalize_32(data);
+                let (type_id, value) = match byte4 {
+                    1 => (kVibratoTypeID, value),
+                    kPitch7_25Controller => {
+                        let semitones = data as f64 / (1 << 25) as f64 - index as f64;
+                        (kTuningTypeID, tuning_expression(semitones))
+                    }
+                    7 => (kVolumeTypeID, value),
+                    10 => (kPanTypeID, value),
+                    74 => (kBrightnessTypeID, value),
+                    _ => return None,
+                };
+                self.note_expression(group, channel, index, type_id, value)?
+            }
+            // Assignable per-note controller
+            0x1 => {
+                let type_id = kCustomStart + byte4 as NoteExpressionTypeID;
+                self.note_expression(group, channel, index, type_id, normalize_32(data))?
+            }
+            // Per-note pitch bend, centered on 0x80000000
+            0x6 => {
+                let bend = (data as f64 - 2_147_483_648.0) / 2_147_483_648.0;
+                let value = tuning_expression(bend * self.per_note_pitch_bend_range);
+                self.note_expression(group, channel, index, kTuningTypeID, value)?
+            }
+            // Per-note management, detaching ends the per-note control of sounding notes
+            0xF => {
+                if byte4 & 0x02 != 0 {
+                    self.detached[group][channel as usize] |= 1 << index;
+                }
+                return None;
+            }
+            0xB => controller(index as CtrlNumber, normalize_32(data)),
+            0xC => controller(kCtrlProgramChange, (data >> 24 & 0x7F) as f64 / 127.0),
+            0xD => controller(kAfterTouch, normalize_32(data)),
+            0xE => controller(kPitchBend, normalize_32(data)),
+            _ => return None,
+        })
+    }
+    /// Collects 7 bit SysEx packets, the complete message is returned with `0xF0`/`0xF7` added
+    fn convert_sysex7(
+        &mut self,
+        group: usize,
+        first: u32,
+        second: u32,
+    ) -> Option<(i32, ConvertedMidi<'_>)> {
+        let status = first >> 20 & 0x0F;
+        let len = (first >> 16 & 0x0F).min(6) as usize;
+        let data = [
+            (first >> 8) as u8,
+            first as u8,
+            (second >> 24) as u8,
+            (second >> 16) as u8,
+            (second >> 8) as u8,
+            second as u8,
+        ];
+        let sysex = &mut self.sysex[group];
+        // Complete message or start
+        if status == 0 || status == 1 {
+            sysex.clear();
+            sysex.push(0xF0);
+        } else if sysex.is_empty() {
+            // Continuation without a start
+            return None;
+        }
+        sysex.extend(data[..len].iter().map(|byte| byte & 0x7F));
+        // Complete message or end
+        if status == 0 || status == 3 {
+            sysex.push(0xF7);
+            let sysex = &self.sysex[group];
+            Some((group as i32, ConvertedMidi::Event(EventKind::sysex(sysex))))
+        } else {
+            None
+        }
+    }
+    fn note_on(
+        &mut self,
+        group: usize,
+        channel: u8,
+        pitch: u8,
+        velocity: f32,
+        tuning: f32,
+    ) -> ConvertedMidi<'static> {
+        let note_id = self.next_note_id;
+        self.next_note_id = self.next_note_id.wrapping_add(1) & i32::MAX;
+        self.note_ids[group][channel as usize][pitch as usize] = note_id;
+        self.detached[group][channel as usize] &= !(1 << pitch);
+        ConvertedMidi::Event(EventKind::NoteOn(NoteOnEvent {
+            channel: channel as i16,
+            pitch: pitch as i16,
+            tuning,
+            velocity,
+            length: 0,
+            note_id,
+        }))
+    }
+    fn note_off(
+        &mut self,
+        group: usize,
+        channel: u8,
+        pitch: u8,
+        velocity: f32,
+    ) -> ConvertedMidi<'static> {
+        let note_id = std::mem::replace(
+            &mut self.note_ids[group][channel as usize][pitch as usize],
+            -1,
+        );
+        self.detached[group][channel as usize] &= !(1 << pitch);
+        ConvertedMidi::Event(EventKind::NoteOff(NoteOffEvent {
+            channel: channel as i16,
+            pitch: pitch as i16,
+            velocity,
+            note_id,
+            tuning: 0.0,
+        }))
+    }
+    fn poly_pressure(
+        &self,
+        group: usize,
+        channel: u8,
+        pitch: u8,
+        pressure: f32,
+    ) -> ConvertedMidi<'static> {
+        ConvertedMidi::Event(EventKind::PolyPressure(PolyPressureEvent {
+            channel: channel as i16,
+            pitch: pitch as i16,
+            pressure,
+            note_id: self.controlled_note_id(group, channel, pitch),
+        }))
+    }
+    /// ID of a sounding note under per-note control, -1 if there is none
+    fn controlled_note_id(&self, group: usize, channel: u8, pitch: u8) -> i32 {
+        if self.detached[group][channel as usize] & 1 << pitch != 0 {
+            return -1;
+        }
+        self.note_ids[group][channel as usize][pitch as usize]
+    }
+    /// Note expression for a sounding note, `None` if the note isn't sounding
+    fn note_expression(
+        &self,
+        group: usize,
+        channel: u8,
+        pitch: u8,
+        type_id: NoteExpressionTypeID,
+        value: f64,
+    ) -> Option<ConvertedMidi<'static>> {
+        let note_id = self.controlled_note_id(group, channel, pitch);
+        (note_id >= 0).then_some(ConvertedMidi::Event(EventKind::NoteExpressionValue(
+            NoteExpressionValueEvent {
+                type_id,
+                note_id,
+                value,
+            },
+        )))
+    }
+}
+
+/// Normalized value of [`kTuningTypeID`] for an offset in semitones
+fn tuning_expression(semitones: f64) -> f64 {
+    (0.5 + semitones / 240.0).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(converter: &mut UmpToEvents, words: &[u32]) -> Vec<String> {
+        packets(words)
+            .filter_map(|packet| {
+                let (bus, converted) = converter.convert(packet)?;
+                Some(format!("{bus} {converted:?}"))
+            })
+            .collect()
+    }
+
+    /// Note events of the packets, which don't borrow from the converter
+    fn note_events(converter: &mut UmpToEvents, words: &[u32]) -> Vec<(i32, EventKind<'static>)> {
+        packets(words)
+            .filter_map(|packet| match converter.convert(packet)? {
+                (bus, ConvertedMidi::Event(EventKind::NoteOn(event))) => {
+                    Some((bus, EventKind::NoteOn(event)))
+                }
+                (bus, ConvertedMidi::Event(EventKind::NoteOff(event))) => {
+                    Some((bus, EventKind::NoteOff(event)))
+                }
+                (bus, ConvertedMidi::Event(EventKind::NoteExpressionValue(event))) => {
+                    Some((bus, EventKind::NoteExpressionValue(event)))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn notes_with_expressions() {
+        let mut converter = UmpToEvents::new();
+        let words = [
+            // Group 1, channel 2, note-on 60 with full velocity and a pitch of 60.5 semitones
+            0x4192_3C03,
+            0xFFFF_7900,
+            // Per-note pitch bend up by half the range
+            0x4162_3C00,
+            0xC000_0000,
+            // Registered per-note controller 74 (brightness)
+            0x4102_3C4A,
+            0x0000_0000,
+            // Note-off
+            0x4182_3C00,
+            0x0000_0000,
+            // Per-note controllers of released notes are dropped
+            0x4102_3C4A,
+            0xFFFF_FFFF,
+        ];
+        let note_on = NoteOnEvent {
+            channel: 2,
+            pitch: 60,
+            tuning: 50.0,
+            velocity: 1.0,
+            length: 0,
+            note_id: 0,
+        };
+        let expression = |type_id, value| {
+            EventKind::NoteExpressionValue(NoteExpressionValueEvent {
+                type_id,
+                note_id: 0,
+                value,
+            })
+        };
+        let note_off = NoteOffEvent {
+            channel: 2,
+            pitch: 60,
+            velocity: 0.0,
+            note_id: 0,
+            tuning: 0.0,
+        };
+        assert_eq!(
+            note_events(&mut converter, &words),
+            [
+                (1, EventKind::NoteOn(note_on)),
+                (1, expression(kTuningTypeID, 0.6)),
+                (1, expression(kBrightnessTypeID, 0.0)),
+                (1, EventKind::NoteOff(note_off)),
+            ]
+        );
+    }
+
+    #[test]
+    fn detached_notes_keep_their_id() {
+        let mut converter = UmpToEvents::new();
+        let words = [
+            // Note-on 64, channel 0
+            0x4090_4000,
+            0xFFFF_0000,
+            // Per-note management with the detach flag
+            0x40F0_4002,
+            0x0000_0000,
+            // Per-note pitch bend of the detached note is dropped
+            0x4060_4000,
+            0xC000_0000,
+            // Note-off
+            0x4080_4000,
+            0x0000_0000,
+        ];
+        let note_ids: Vec<_> = note_events(&mut converter, &words)
+            .into_iter()
+            .map(|(_, event)| match event {
+                EventKind::NoteOn(event) => ("on", event.note_id),
+                EventKind::NoteOff(event) => ("off", event.note_id),
+                _ => ("other", -1),
+            })
+            .collect();
+        assert_eq!(note_ids, [("on", 0), ("off", 0)]);
+    }
+
+    #[test]
+    fn controllers_and_sysex() {
+        let mut converter = UmpToEvents::new();
+        let words = [
+            // MIDI 2.0 pitch bend, channel 0
+            0x40E0_0000,
+            0xFFFF_FFFF,
+            // MIDI 1.0 CC 7 on channel 3
+            0x20B3_077F,
+            // SysEx split in two packets: F0 7E 7F 06 01 F7
+            0x3012_7E7F,
+            0x0000_0000,
+            0x3032_0601,
+            0x0000_0000,
+        ];
+        assert_eq!(
+            events(&mut converter, &words),
+            [
+                "0 Controller { channel: 0, controller: 129, value: 1.0 }",
+                "0 Controller { channel: 3, controller: 7, value: 1.0 }",
+                "0 Event(Data { type_: 0, bytes: [240, 126, 127, 6, 1, 247] })",
+            ]
+        );
+    }
+}
@@ -0,0 +1,11 @@
+pub mod hosting;
+pub mod midi;
+pub mod params;
+pub mod utility;
+pub mod vst2preset;
+pub mod vsteditcontroller;
+pub mod vstkeyswitch;
+pub mod vstnoteexpressiontypes;
+pub mod vstparameters;
+pub mod vstpresetfile;
+pub mod vstpresetmetainfo;
@@ -0,0 +1,387 @@
+//! Declarative parameters, see [`Params`]
+//!
+//! A struct deriving [`Params`] describes every parameter of a plug-in once.
+//! Its [`AtomicParam`] fields hold the normalized values and can be shared
+//! between the processor and the controller through an `Arc` without locking:
+//!
+//! ```ignore
+//! #[derive(Params)]
+//! #[params(version = 2)]
+//! struct GainParams {
+//!     #[param(id = 0, range = -60.0..=12.0, default = 0.0, unit = "dB", automate)]
+//!     gain: AtomicParam,
+//!     #[param(id = 1, bool, bypass, automate)]
+//!     bypass: AtomicParam,
+//! }
+//! ```
+use super::vstparameters::{
+    BasicParameter, BoolParameter, Parameter, ParameterContainer, RangeParameter,
+    StringListParameter,
+};
+use crate::plugininterfaces::vst::{
+    ivsteditcontroller::{from_normalized, to_normalized, ParameterInfo},
+    vsttypes::{ParamID, ParamValue, UnitID},
+};
+use std::{
+    io::{self, Read, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+pub use vst3_derive::Params;
+
+/// Value mapping of a declared parameter
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParamKind {
+    /// Continuous or stepped range of plain values
+    Range { min: ParamValue, max: ParamValue },
+    /// One string per step, the plain value is the index
+    List(&'static [&'static str]),
+    /// On/off, the plain value is 0 or 1
+    Bool,
+}
+
+/// Static description of a declared parameter, generated by `#[derive(Params)]`
+#[derive(Copy, Clone, Debug)]
+pub struct ParamDescriptor {
+    pub id: ParamID,
+    pub title: &'static str,
+    pub short_title: &'static str,
+    pub units: &'static str,
+    pub kind: ParamKind,
+    pub step_count: i32,
+    pub default_plain: ParamValue,
+    pub flags: i32,
+    pub unit_id: UnitID,
+    pub precision: usize,
+}
+impl ParamDescriptor {
+    pub fn to_plain(&self, value_normalized: ParamValue) -> ParamValue {
+        match self.kind {
+            ParamKind::Range { min, .. } if self.step_count > 0 => {
+                from_normalized(value_normalized, self.step_count) as f64 + min
+            }
+            ParamKind::Range { min, max } => value_normalized * (max - min) + min,
+            ParamKind::List(_) | ParamKind::Bool => {
+                from_normalized(value_normalized, self.step_count) as f64
+            }
+        }
+    }
+    pub fn to_normalized(&self, plain_value: ParamValue) -> ParamValue {
+        let value_normalized = match self.kind {
+            ParamKind::Range { min, .. } if self.step_count > 0 => {
+                to_normalized((plain_value - min).round(), self.step_count)
+            }
+            ParamKind::Range { min, max } if max == min => 0.0,
+            ParamKind::Range { min, max } => (plain_value - min) / (max - min),
+            ParamKind::List(_) | ParamKind::Bool => {
+                to_normalized(plain_value.round(), self.step_count)
+            }
+        };
+        value_normalized.clamp(0.0, 1.0)
+    }
+    pub fn default_normalized(&self) -> ParamValue {
+        self.to_normalized(self.default_plain)
+    }
+    /// The `ParameterInfo` exported by the controller for this parameter
+    pub fn info(&self) -> ParameterInfo {
+        let mut info = ParameterInfo::new(self.id, self.title, self.units);
+        info.set_short_title(self.short_title);
+        info.step_count = self.step_count;
+        info.default_normalized_value = self.default_normalized();
+        info.unit_id = self.unit_id;
+        info.flags = self.flags;
+        info
+    }
+    /// Creates the matching runtime parameter, holding its own value
+    pub fn to_parameter(&self) -> Box<dyn Parameter> {
+        match self.kind {
+            ParamKind::Range { min, max } => self.configure(
+                RangeParameter::new(
+                    self.id,
+                    self.title,
+                    self.units,
+                    min,
+                    max,
+                    self.default_plain,
+                )
+                .with_step_count(self.step_count),
+            ),
+            ParamKind::List(strings) => self.configure(
+                StringListParameter::new(self.id, self.title)
+                    .with_strings(strings)
+                    .with_default_normalized(self.default_normalized()),
+            ),
+            ParamKind::Bool => self.configure(BoolParameter::new(
+                self.id,
+                self.title,
+                self.default_plain > 0.5,
+            )),
+        }
+    }
+    fn configure<P: Parameter + 'static>(&self, parameter: P) -> Box<dyn Parameter> {
+        Box::new(
+            parameter
+                .with_flags(self.flags)
+                .with_unit_id(self.unit_id)
+                .with_short_title(self.short_title)
+                .with_precision(self.precision),
+        )
+    }
+}
+
+/// Lock-free normalized value of a declared parameter
+#[derive(Debug)]
+pub struct AtomicParam {
+    descriptor: &'static ParamDescriptor,
+    value_normalized: AtomicU64,
+}
+impl AtomicParam {
+    pub fn new(descriptor: &'static ParamDescriptor) -> Self {
+        Self {
+            descriptor,
+            value_normalized: AtomicU64::new(descriptor.default_normalized().to_bits()),
+        }
+    }
+    pub fn descriptor(&self) -> &'static ParamDescriptor {
+        self.descriptor
+    }
+    pub fn id(&self) -> ParamID {
+        self.descriptor.id
+    }
+    pub fn normalized(&self) -> ParamValue {
+        f64::from_bits(self.value_normalized.load(Ordering::Relaxed))
+    }
+    /// Sets the normalized value, clamped to [0, 1].
+    /// Returns true if the value changed.
+    pub fn set_normalized(&self, value: ParamValue) -> bool {
+        let value = value.clamp(0.0, 1.0);
+        let previous = self
+            .value_normalized
+            .swap(value.to_bits(), Ordering::Relaxed);
+        f64::from_bits(previous) != value
+    }
+    pub fn plain(&self) -> ParamValue {
+        self.descriptor.to_plain(self.normalized())
+    }
+    pub fn set_plain(&self, value: ParamValue) -> bool {
+        self.set_normalized(self.descriptor.to_normalized(value))
+    }
+    /// Plain value of a bool parameter, or a normalized value above 0.5
+    pub fn as_bool(&self) -> bool {
+        self.normalized() > 0.5
+    }
+    /// Selected index of a list or stepped parameter
+    pub fn index(&self) -> usize {
+        from_normalized(self.normalized(), self.descriptor.step_count.max(0)) as usize
+    }
+    pub fn reset(&self) {
+        self.set_normalized(self.descriptor.default_normalized());
+    }
+}
+
+/// Parameters of a plug-in, implemented with `#[derive(Params)]`
+pub trait Params: Default + Send + Sync + 'static {
+    /// Version written by [`Params::get_state`], set with `#[params(version = ...)]`
+    const STATE_VERSION: u32;
+
+    /// Descriptors of all parameters, in declaration order
+    const DESCRIPTORS: &'static [ParamDescriptor];
+
+    fn param(&self, id: ParamID) -> Option<&AtomicParam>;
+
+    fn params(&self) -> impl Iterator<Item = &AtomicParam> {
+        Self::DESCRIPTORS
+            .iter()
+            .filter_map(|descriptor| self.param(descriptor.id))
+    }
+    fn parameter_infos() -> Vec<ParameterInfo> {
+        Self::DESCRIPTORS
+            .iter()
+            .map(ParamDescriptor::info)
+            .collect()
+    }
+    fn get_normalized(&self, id: ParamID) -> Option<ParamValue> {
+        self.param(id).map(AtomicParam::normalized)
+    }
+    /// Sets a normalized value, returns false if `id` is unknown
+    fn set_normalized(&self, id: ParamID, value: ParamValue) -> bool {
+        match self.param(id) {
+            Some(param) => {
+                param.set_normalized(value);
+                true
+            }
+            None => false,
+        }
+    }
+    fn reset(&self) {
+        self.params().for_each(AtomicParam::reset);
+    }
+    /// Creates a container for an `EditController` whose parameters
+    /// read and write the values of `params`
+    fn parameter_container(params: &Arc<Self>) -> ParameterContainer {
+        let mut container = ParameterContainer::new();
+        for descriptor in Self::DESCRIPTORS {
+            let parameter = SharedParameter {
+                inner: descriptor.to_parameter(),
+                params: Arc::clone(params),
+                id: descriptor.id,
+            };
+            // IDs are unique, checked by the derive
+            let _ = container.add_parameter(parameter);
+        }
+        container
+    }
+    /// Writes the state version followed by the plain value of every parameter.
+    ///
+    /// Values are stored by ID as little endian, so parameters can be added or
+    /// removed in later versions without breaking older states.
+    fn get_state<W: Write>(&self, stream: &mut W) -> io::Result<()> {
+        stream.write_all(&Self::STATE_VERSION.to_le_bytes())?;
+        stream.write_all(&(Self::DESCRIPTORS.len() as u32).to_le_bytes())?;
+        for param in self.params() {
+            stream.write_all(&param.id().to_le_bytes())?;
+            stream.write_all(&param.plain().to_le_bytes())?;
+        }
+        Ok(())
+    }
+    /// Reads a state written by [`Params::get_state`].
+    ///
+    /// Unknown IDs are skipped and missing parameters keep their value,
+    /// states of a newer version are rejected.
+    fn set_state<R: Read>(&self, stream: &mut R) -> io::Result<()> {
+        fn read_u32<R: Read>(stream: &mut R) -> io::Result<u32> {
+            let mut bytes = [0; 4];
+            stream.read_exact(&mut bytes)?;
+            Ok(u32::from_le_bytes(bytes))
+        }
+        let version = read_u32(stream)?;
+        if version > Self::STATE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "state version {version} is newer than {}",
+                    Self::STATE_VERSION
+                ),
+            ));
+        }
+        let count = read_u32(stream)?;
+        for _ in 0..count {
+            let id = read_u32(stream)?;
+            let mut bytes = [0; 8];
+            stream.read_exact(&mut bytes)?;
+            if let Some(param) = self.param(id) {
+                param.set_plain(f64::from_le_bytes(bytes));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Controller parameter which stores its value in a shared [`Params`] struct
+struct SharedParameter<P: Params> {
+    inner: Box<dyn Parameter>,
+    params: Arc<P>,
+    id: ParamID,
+}
+impl<P: Params> SharedParameter<P> {
+    fn value(&self) -> Option<&AtomicParam> {
+        self.params.param(self.id)
+    }
+}
+impl<P: Params> Parameter for SharedParameter<P> {
+    fn base(&self) -> &BasicParameter {
+        self.inner.base()
+    }
+    fn base_mut(&mut self) -> &mut BasicParameter {
+        self.inner.base_mut()
+    }
+    fn normalized(&self) -> ParamValue {
+        self.value().map_or(0.0, AtomicParam::normalized)
+    }
+    fn set_normalized(&self, value: ParamValue) -> bool {
+        self.value()
+            .is_some_and(|param| param.set_normalized(value))
+    }
+    fn to_plain(&self, value_normalized: ParamValue) -> ParamValue {
+        self.inner.to_plain(value_normalized)
+    }
+    fn to_normalized(&self, plain_value: ParamValue) -> ParamValue {
+        self.inner.to_normalized(plain_value)
+    }
+    fn format_plain(&self, plain_value: ParamValue) -> String {
+        self.inner.format_plain(plain_value)
+    }
+    fn parse_plain(&self, string: &str) -> Option<ParamValue> {
+        self.inner.parse_plain(string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugininterfaces::vst::ivsteditcontroller::ParameterFlags;
+
+    #[derive(Params)]
+    #[params(version = 2)]
+    struct TestParams {
+        #[param(id = 3, range = -60.0..=12.0, default = 0.0, unit = "dB", automate)]
+        gain: AtomicParam,
+        #[param(id = 4, list = ["Sine", "Saw", "Square"], default = 1)]
+        waveform: AtomicParam,
+        #[param(id = 5, bool, bypass, automate)]
+        bypass: AtomicParam,
+        #[param(id = 6, range = -24..=24, steps = 48, title = "Transpose")]
+        transpose: AtomicParam,
+        not_a_param: u32,
+    }
+
+    #[test]
+    fn descriptors_and_defaults() {
+        let params = TestParams::default();
+        assert_eq!(params.gain.plain(), 0.0);
+        assert_eq!(params.waveform.index(), 1);
+        assert!(!params.bypass.as_bool());
+        assert_eq!(params.not_a_param, 0);
+
+        let infos = TestParams::parameter_infos();
+        assert_eq!(infos.len(), 4);
+        assert_eq!(infos[0].title(), "Gain");
+        assert_eq!(infos[0].units(), "dB");
+        assert!(infos[1].has_flag(ParameterFlags::IsList));
+        assert_eq!(infos[1].step_count, 2);
+        assert!(infos[2].has_flag(ParameterFlags::IsBypass));
+        assert_eq!(infos[3].step_count, 48);
+        assert_eq!(infos[3].default_normalized_value, 0.0);
+    }
+
+    #[test]
+    fn state_round_trip() {
+        let params = TestParams::default();
+        params.gain.set_plain(-6.0);
+        params.transpose.set_plain(7.0);
+        let mut state = Vec::new();
+        params.get_state(&mut state).unwrap();
+
+        let restored = TestParams::default();
+        restored.set_state(&mut state.as_slice()).unwrap();
+        assert!((restored.gain.plain() + 6.0).abs() < 1e-9);
+        assert_eq!(restored.transpose.plain(), 7.0);
+
+        state[0] = 3;
+        assert!(restored.set_state(&mut state.as_slice()).is_err());
+    }
+
+    #[test]
+    fn container_shares_values() {
+        let params = Arc::new(TestParams::default());
+        let container = TestParams::parameter_container(&params);
+        let waveform = container.get_parameter(4).unwrap();
+        assert_eq!(waveform.value_to_string(waveform.normalized()), "Saw");
+        waveform.set_normalized(1.0);
+        assert_eq!(params.waveform.index(), 2);
+        let gain = container.get_parameter(3).unwrap();
+        assert_eq!(gain.value_from_string("12 dB"), Some(1.0));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod sampleaccurate;
+pub mod statecontext;
+pub mod tempomap;
+pub mod versionedstate;
+pub mod voicemanager;
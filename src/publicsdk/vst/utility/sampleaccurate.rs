@@ -0,0 +1,289 @@
+//! Sample accurate parameter values, see `public.sdk/source/vst/utility/sampleaccurate.h`
+//!
+//! A [`ParamSmoother`] consumes the points of a parameter's [`IParamValueQueue`]
+//! at their exact sample offsets while a block is processed:
+//!
+//! ```ignore
+//! let queue = changes.queue(GAIN_ID);
+//! let mut gain = self.gain.process(queue.as_deref());
+//! for sample in buffer.iter_mut() {
+//!     *sample *= gain.next_value() as f32;
+//! }
+//! ```
+use crate::plugininterfaces::vst::{
+    ivstparameterchanges::IParamValueQueue,
+    vsttypes::{ParamID, ParamValue},
+};
+
+/// How a [`ParamSmoother`] moves between the points of a queue
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SmoothingMode {
+    /// Linear ramp reaching each point at its sample offset, as specified by the SDK
+    Linear,
+    /// One-pole filter following the point values, `coefficient` is applied per sample
+    Exponential { coefficient: f64 },
+    /// Jumps to each point value at its sample offset
+    Held,
+}
+impl SmoothingMode {
+    /// Exponential smoothing reaching ~63% of a step after `time_ms`
+    pub fn exponential(time_ms: f64, sample_rate: f64) -> Self {
+        let time_samples = time_ms * 0.001 * sample_rate;
+        let coefficient = if time_samples > 1.0 {
+            1.0 - (-1.0 / time_samples).exp()
+        } else {
+            1.0
+        };
+        SmoothingMode::Exponential { coefficient }
+    }
+}
+
+/// Per-parameter smoother driven by the points of an [`IParamValueQueue`]
+#[derive(Clone, Debug)]
+pub struct ParamSmoother {
+    id: ParamID,
+    mode: SmoothingMode,
+    value: ParamValue,
+    target: ParamValue,
+}
+impl ParamSmoother {
+    pub fn new(id: ParamID, initial_value: ParamValue, mode: SmoothingMode) -> Self {
+        Self {
+            id,
+            mode,
+            value: initial_value,
+            target: initial_value,
+        }
+    }
+    pub fn id(&self) -> ParamID {
+        self.id
+    }
+    pub fn mode(&self) -> SmoothingMode {
+        self.mode
+    }
+    pub fn set_mode(&mut self, mode: SmoothingMode) {
+        self.mode = mode;
+    }
+    /// Last produced value
+    pub fn value(&self) -> ParamValue {
+        self.value
+    }
+    /// Value the smoother is heading to
+    pub fn target(&self) -> ParamValue {
+        self.target
+    }
+    /// Jumps to `value` without smoothing
+    pub fn set_value(&mut self, value: ParamValue) {
+        self.value = value;
+        self.target = value;
+    }
+    /// Sets a new target, reached according to the mode
+    /// (immediately unless the mode is exponential)
+    pub fn set_target(&mut self, target: ParamValue) {
+        self.target = target;
+        if !matches!(self.mode, SmoothingMode::Exponential { .. }) {
+            self.value = target;
+        }
+    }
+    /// Returns true while an exponential smoother has not settled on its target
+    pub fn is_smoothing(&self) -> bool {
+        (self.target - self.value).abs() > 1e-6
+    }
+    /// Starts a block, consuming the points of `queue` if the parameter changed.
+    ///
+    /// The returned values start at sample offset 0 of the block, when dropped
+    /// the smoother jumps to the last point of the queue if it was not reached.
+    pub fn process<'a>(&'a mut self, queue: Option<&'a IParamValueQueue>) -> SmoothedValues<'a> {
+        debug_assert!(queue.is_none_or(|queue| queue.get_parameter_id() == self.id));
+        let point_count = queue.map_or(0, |queue| queue.get_point_count().max(0));
+        let start = match self.mode {
+            SmoothingMode::Exponential { .. } => self.target,
+            SmoothingMode::Linear | SmoothingMode::Held => self.value,
+        };
+        let mut values = SmoothedValues {
+            smoother: self,
+            queue,
+            point_count,
+            point_index: 0,
+            next_point: None,
+            start: (-1, start),
+            position: 0,
+        };
+        values.load_next_point();
+        values
+    }
+}
+
+/// Values of a [`ParamSmoother`] during one block, see [`ParamSmoother::process`]
+///
+/// Iterating yields one value per sample, indefinitely holding the last
+/// value once all points are consumed.
+pub struct SmoothedValues<'a> {
+    smoother: &'a mut ParamSmoother,
+    queue: Option<&'a IParamValueQueue>,
+    point_count: i32,
+    point_index: i32,
+    next_point: Option<(i32, ParamValue)>,
+    start: (i32, ParamValue),
+    position: i32,
+}
+impl SmoothedValues<'_> {
+    fn load_next_point(&mut self) {
+        self.next_point = None;
+        let Some(queue) = self.queue else {
+            return;
+        };
+        while self.point_index < self.point_count {
+            let point = queue.get_point(self.point_index);
+            self.point_index += 1;
+            if let Ok(point) = point {
+                self.next_point = Some(point);
+                return;
+            }
+        }
+    }
+    /// Sample offset of the next value
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+    /// Returns the value of the next sample
+    pub fn next_value(&mut self) -> ParamValue {
+        let offset = self.position;
+        self.position += 1;
+        while let Some(point) = self.next_point {
+            if point.0 > offset {
+                break;
+            }
+            self.start = point;
+            self.load_next_point();
+        }
+        let (start_offset, start_value) = self.start;
+        let value = match self.smoother.mode {
+            SmoothingMode::Held => start_value,
+            SmoothingMode::Linear => match self.next_point {
+                Some((end_offset, end_value)) => {
+                    let progress =
+                        (offset - start_offset) as f64 / (end_offset - start_offset) as f64;
+                    start_value + (end_value - start_value) * progress
+                }
+                None => start_value,
+            },
+            SmoothingMode::Exponential { coefficient } => {
+                self.smoother.value + (start_value - self.smoother.value) * coefficient
+            }
+        };
+        self.smoother.value = value;
+        self.smoother.target = start_value;
+        value
+    }
+    /// Advances by a sub-block of `num_samples` and returns the value of its last sample
+    pub fn advance(&mut self, num_samples: usize) -> ParamValue {
+        let mut value = self.smoother.value;
+        for _ in 0..num_samples {
+            value = self.next_value();
+        }
+        value
+    }
+    /// Writes one value per sample of `output`
+    pub fn fill(&mut self, output: &mut [f32]) {
+        for sample in output {
+            *sample = self.next_value() as f32;
+        }
+    }
+    /// Ends the block early, jumping to the last point of the queue
+    pub fn flush(mut self) -> ParamValue {
+        self.flush_changes();
+        self.smoother.value
+    }
+    fn flush_changes(&mut self) {
+        let mut last = self.start.1;
+        while let Some(point) = self.next_point {
+            last = point.1;
+            self.load_next_point();
+        }
+        self.start.1 = last;
+        self.smoother.target = last;
+        if !matches!(self.smoother.mode, SmoothingMode::Exponential { .. }) {
+            self.smoother.value = last;
+        }
+    }
+}
+impl Iterator for SmoothedValues<'_> {
+    type Item = ParamValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_value())
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+impl Drop for SmoothedValues<'_> {
+    fn drop(&mut self) {
+        self.flush_changes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publicsdk::vst::hosting::parameterchanges::ParameterChanges;
+
+    fn assert_close(values: &[ParamValue], expected: &[ParamValue]) {
+        assert_eq!(values.len(), expected.len());
+        for (value, expected) in values.iter().zip(expected) {
+            assert!(
+                (value - expected).abs() < 1e-9,
+                "{values:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn linear_reaches_points_at_their_offsets() {
+        let changes = ParameterChanges::new(1, 4);
+        changes.add_point(1, 3, 1.0).unwrap();
+        changes.add_point(1, 5, 0.0).unwrap();
+        let changes = changes.as_interface();
+        let queue = changes.queue(1);
+
+        let mut smoother = ParamSmoother::new(1, 0.0, SmoothingMode::Linear);
+        let values: Vec<_> = smoother.process(queue.as_deref()).take(8).collect();
+        assert_close(&values, &[0.25, 0.5, 0.75, 1.0, 0.5, 0.0, 0.0, 0.0]);
+        assert_eq!(smoother.value(), 0.0);
+    }
+
+    #[test]
+    fn held_and_flush() {
+        let changes = ParameterChanges::new(1, 4);
+        changes.add_point(1, 2, 0.5).unwrap();
+        changes.add_point(1, 6, 1.0).unwrap();
+        let changes = changes.as_interface();
+        let queue = changes.queue(1);
+
+        let mut smoother = ParamSmoother::new(1, 0.0, SmoothingMode::Held);
+        let mut values = smoother.process(queue.as_deref());
+        assert_eq!(values.advance(2), 0.0);
+        assert_eq!(values.advance(2), 0.5);
+        assert_eq!(values.flush(), 1.0);
+
+        let mut values = smoother.process(None);
+        assert_eq!(values.advance(4), 1.0);
+    }
+
+    #[test]
+    fn exponential_follows_target_across_blocks() {
+        let changes = ParameterChanges::new(1, 1);
+        changes.add_point(1, 0, 1.0).unwrap();
+        let changes = changes.as_interface();
+        let queue = changes.queue(1);
+
+        let mut smoother =
+            ParamSmoother::new(1, 0.0, SmoothingMode::Exponential { coefficient: 0.5 });
+        let values: Vec<_> = smoother.process(queue.as_deref()).take(2).collect();
+        assert_close(&values, &[0.5, 0.75]);
+        assert!(smoother.is_smoothing());
+        assert_eq!(smoother.target(), 1.0);
+        assert!((smoother.process(None).advance(1) - 0.875).abs() < 1e-9);
+    }
+}
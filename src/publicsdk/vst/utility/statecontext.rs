@@ -0,0 +1,102 @@
+//! Context of a `setState` or `getState` call, read from the [`IStreamAttributes`] of the stream
+//!
+//! Hosts attach [`PresetAttributes`] to the stream, so a plug-in can e.g. skip
+//! restoring UI-only settings when a preset is loaded:
+//!
+//! ```ignore
+//! unsafe fn set_state(&self, state: &IBStream) -> Result<(), EInterface> {
+//!     let context = StateContext::from_stream(state);
+//!     ...
+//!     if !context.is_preset() {
+//!         self.restore_ui_settings(&mut reader)?;
+//!     }
+//! }
+//! ```
+use crate::plugininterfaces::{
+    base::{ibstream::IBStream, Interface},
+    vst::{
+        ivstattributes::IStreamAttributes,
+        vstpresetkeys::{PresetAttributes, StateType},
+    },
+};
+
+/// Attributes of a state stream, `None` if the host didn't provide them
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateContext {
+    /// [`PresetAttributes::STATE_TYPE`], one of [`StateType`]
+    pub state_type: Option<String>,
+    /// File name of the stream without extension
+    pub file_name: Option<String>,
+    /// [`PresetAttributes::FILE_PATH_STRING_TYPE`]
+    pub file_path: Option<String>,
+    /// [`PresetAttributes::NAME`]
+    pub name: Option<String>,
+}
+impl StateContext {
+    /// Reads the attributes of `stream`, an empty context if it has none
+    pub fn from_stream(stream: &IBStream) -> Self {
+        let Ok(stream_attributes) = stream.cast::<IStreamAttributes>() else {
+            return Self::default();
+        };
+        let mut context = StateContext {
+            file_name: stream_attributes
+                .get_file_name()
+                .ok()
+                .filter(|name| !name.is_empty()),
+            ..Default::default()
+        };
+        if let Some(attributes) = stream_attributes.get_attributes() {
+            context.state_type = attributes.get_str(PresetAttributes::STATE_TYPE).ok();
+            context.file_path = attributes
+                .get_str(PresetAttributes::FILE_PATH_STRING_TYPE)
+                .ok();
+            context.name = attributes.get_str(PresetAttributes::NAME).ok();
+        }
+        context
+    }
+    /// Whether the state is stored in or loaded from a project
+    pub fn is_project(&self) -> bool {
+        self.state_type.as_deref() == Some(StateType::PROJECT)
+    }
+    /// Whether the state is a preset, the default preset or a preset file
+    pub fn is_preset(&self) -> bool {
+        !self.is_project()
+            && (self.state_type.as_deref() == Some(StateType::DEFAULT)
+                || self.file_path.is_some()
+                || self.file_name.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publicsdk::{
+        common::memorystream::MemoryStream, vst::hosting::attributedstream::AttributedStream,
+    };
+    use std::path::Path;
+
+    #[test]
+    fn project_and_preset_loads() {
+        let plain = MemoryStream::new();
+        let context = StateContext::from_stream(&plain.as_interface());
+        assert_eq!(context, StateContext::default());
+        assert!(!context.is_project() && !context.is_preset());
+
+        let project =
+            AttributedStream::new(MemoryStream::new()).with_state_type(StateType::PROJECT);
+        assert!(StateContext::from_stream(&project.as_interface()).is_project());
+
+        let preset = AttributedStream::new(MemoryStream::new())
+            .with_file_path(Path::new("/presets/Warm Pad.vstpreset"))
+            .with_name("Warm Pad");
+        let context = StateContext::from_stream(&preset.as_interface());
+        assert!(context.is_preset());
+        assert_eq!(context.file_name.as_deref(), Some("Warm Pad"));
+        assert_eq!(
+            context.file_path.as_deref(),
+            Some("/presets/Warm Pad.vstpreset")
+        );
+        assert_eq!(context.name.as_deref(), Some("Warm Pad"));
+        assert_eq!(context.state_type, None);
+    }
+}
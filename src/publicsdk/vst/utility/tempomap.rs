@@ -0,0 +1,240 @@
+//! Tempo and time signature maps converting between musical time (quarter notes),
+//! seconds and bars
+
+use crate::plugininterfaces::vst::{
+    ivstprocesscontext::quarter_notes_per_bar, vsttypes::TQuarterNotes,
+};
+
+/// Tempo change at a musical position
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TempoChange {
+    /// Position of the change in quarter notes
+    pub ppq_position: TQuarterNotes,
+    /// Tempo in quarter notes per minute
+    pub tempo: f64,
+    /// Time of the change in seconds, derived from the previous changes
+    seconds: f64,
+}
+impl TempoChange {
+    /// Time of the change in seconds
+    pub fn seconds(&self) -> f64 {
+        self.seconds
+    }
+}
+
+/// Sorted list of tempo changes, the tempo is constant between two changes
+#[derive(Clone, Debug, PartialEq)]
+pub struct TempoMap {
+    changes: Vec<TempoChange>,
+}
+impl Default for TempoMap {
+    fn default() -> Self {
+        Self::new(120.0)
+    }
+}
+impl TempoMap {
+    /// Creates a tempo map with a constant `tempo`
+    pub fn new(tempo: f64) -> Self {
+        Self {
+            changes: vec![TempoChange {
+                ppq_position: 0.0,
+                tempo,
+                seconds: 0.0,
+            }],
+        }
+    }
+    /// Sets the tempo from `ppq_position` on, replacing a change at the same position
+    pub fn add_change(&mut self, ppq_position: TQuarterNotes, tempo: f64) {
+        let ppq_position = ppq_position.max(0.0);
+        let index = self
+            .changes
+            .partition_point(|change| change.ppq_position < ppq_position);
+        let change = TempoChange {
+            ppq_position,
+            tempo,
+            seconds: 0.0,
+        };
+        match self.changes.get_mut(index) {
+            Some(existing) if existing.ppq_position == ppq_position => *existing = change,
+            _ => self.changes.insert(index, change),
+        }
+        self.update_seconds(index);
+    }
+    fn update_seconds(&mut self, from: usize) {
+        for index in from.max(1)..self.changes.len() {
+            let previous = self.changes[index - 1];
+            let change = &mut self.changes[index];
+            change.seconds = previous.seconds
+                + (change.ppq_position - previous.ppq_position) * 60.0 / previous.tempo;
+        }
+    }
+    pub fn changes(&self) -> &[TempoChange] {
+        &self.changes
+    }
+    /// Last change at or before `ppq_position`
+    fn change_at_ppq(&self, ppq_position: TQuarterNotes) -> &TempoChange {
+        let index = self
+            .changes
+            .partition_point(|change| change.ppq_position <= ppq_position);
+        &self.changes[index.saturating_sub(1)]
+    }
+    /// Last change at or before `seconds`
+    fn change_at_seconds(&self, seconds: f64) -> &TempoChange {
+        let index = self
+            .changes
+            .partition_point(|change| change.seconds <= seconds);
+        &self.changes[index.saturating_sub(1)]
+    }
+    /// Tempo in quarter notes per minute at `ppq_position`
+    pub fn tempo_at(&self, ppq_position: TQuarterNotes) -> f64 {
+        self.change_at_ppq(ppq_position).tempo
+    }
+    /// Time in seconds of `ppq_position`
+    pub fn seconds_at(&self, ppq_position: TQuarterNotes) -> f64 {
+        let change = self.change_at_ppq(ppq_position);
+        change.seconds + (ppq_position - change.ppq_position) * 60.0 / change.tempo
+    }
+    /// Musical position of the time `seconds`
+    pub fn ppq_at(&self, seconds: f64) -> TQuarterNotes {
+        let change = self.change_at_seconds(seconds);
+        change.ppq_position + (seconds - change.seconds) * change.tempo / 60.0
+    }
+    /// Sample position of `ppq_position`
+    pub fn samples_at(&self, ppq_position: TQuarterNotes, sample_rate: f64) -> f64 {
+        self.seconds_at(ppq_position) * sample_rate
+    }
+    /// Musical position of the sample position `samples`
+    pub fn ppq_at_samples(&self, samples: f64, sample_rate: f64) -> TQuarterNotes {
+        self.ppq_at(samples / sample_rate)
+    }
+}
+
+/// Time signature change at the start of a bar
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimeSignatureChange {
+    /// Zero based bar index of the change
+    pub bar: i64,
+    pub numerator: i32,
+    pub denominator: i32,
+    /// Position of the bar in quarter notes, derived from the previous changes
+    ppq_position: TQuarterNotes,
+}
+impl TimeSignatureChange {
+    /// Position of the change in quarter notes
+    pub fn ppq_position(&self) -> TQuarterNotes {
+        self.ppq_position
+    }
+    /// Length of a bar in quarter notes
+    pub fn bar_length(&self) -> TQuarterNotes {
+        quarter_notes_per_bar(self.numerator, self.denominator)
+    }
+}
+
+/// Sorted list of time signature changes, the time signature is constant between two changes
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeSignatureMap {
+    changes: Vec<TimeSignatureChange>,
+}
+impl Default for TimeSignatureMap {
+    fn default() -> Self {
+        Self::new(4, 4)
+    }
+}
+impl TimeSignatureMap {
+    /// Creates a map with a constant time signature, `numerator` and `denominator` must be positive
+    pub fn new(numerator: i32, denominator: i32) -> Self {
+        Self {
+            changes: vec![TimeSignatureChange {
+                bar: 0,
+                numerator,
+                denominator,
+                ppq_position: 0.0,
+            }],
+        }
+    }
+    /// Sets the time signature from the bar `bar` on, replacing a change at the same bar
+    pub fn add_change(&mut self, bar: i64, numerator: i32, denominator: i32) {
+        let bar = bar.max(0);
+        let index = self.changes.partition_point(|change| change.bar < bar);
+        let change = TimeSignatureChange {
+            bar,
+            numerator,
+            denominator,
+            ppq_position: 0.0,
+        };
+        match self.changes.get_mut(index) {
+            Some(existing) if existing.bar == bar => *existing = change,
+            _ => self.changes.insert(index, change),
+        }
+        for index in index.max(1)..self.changes.len() {
+            let previous = self.changes[index - 1];
+            let change = &mut self.changes[index];
+            change.ppq_position =
+                previous.ppq_position + (change.bar - previous.bar) as f64 * previous.bar_length();
+        }
+    }
+    pub fn changes(&self) -> &[TimeSignatureChange] {
+        &self.changes
+    }
+    /// Last change at or before `ppq_position`
+    fn change_at_ppq(&self, ppq_position: TQuarterNotes) -> &TimeSignatureChange {
+        let index = self
+            .changes
+            .partition_point(|change| change.ppq_position <= ppq_position);
+        &self.changes[index.saturating_sub(1)]
+    }
+    /// Numerator and denominator of the time signature at `ppq_position`
+    pub fn time_signature_at(&self, ppq_position: TQuarterNotes) -> (i32, i32) {
+        let change = self.change_at_ppq(ppq_position);
+        (change.numerator, change.denominator)
+    }
+    /// Zero based index and start position of the bar containing `ppq_position`
+    pub fn bar_at(&self, ppq_position: TQuarterNotes) -> (i64, TQuarterNotes) {
+        let change = self.change_at_ppq(ppq_position);
+        let bar_length = change.bar_length();
+        let bars = ((ppq_position - change.ppq_position) / bar_length).floor();
+        (
+            change.bar + bars as i64,
+            change.ppq_position + bars * bar_length,
+        )
+    }
+    /// Start position of the zero based bar `bar`
+    pub fn ppq_at_bar(&self, bar: i64) -> TQuarterNotes {
+        let index = self.changes.partition_point(|change| change.bar <= bar);
+        let change = &self.changes[index.saturating_sub(1)];
+        change.ppq_position + (bar - change.bar) as f64 * change.bar_length()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_across_changes() {
+        let mut tempo_map = TempoMap::new(120.0);
+        tempo_map.add_change(4.0, 60.0);
+        tempo_map.add_change(2.0, 240.0);
+        // 2 quarters at 120, 2 at 240, then 60
+        assert_eq!(tempo_map.seconds_at(2.0), 1.0);
+        assert_eq!(tempo_map.seconds_at(4.0), 1.5);
+        assert_eq!(tempo_map.seconds_at(5.0), 2.5);
+        assert_eq!(tempo_map.ppq_at(1.25), 3.0);
+        assert_eq!(tempo_map.tempo_at(4.0), 60.0);
+        assert_eq!(tempo_map.samples_at(5.0, 48000.0), 120000.0);
+    }
+
+    #[test]
+    fn bars_across_time_signatures() {
+        let mut time_signatures = TimeSignatureMap::new(4, 4);
+        time_signatures.add_change(2, 6, 8);
+        time_signatures.add_change(3, 5, 4);
+        // Bars of 4, 4, 3 and then 5 quarter notes
+        assert_eq!(time_signatures.ppq_at_bar(3), 11.0);
+        assert_eq!(time_signatures.ppq_at_bar(4), 16.0);
+        assert_eq!(time_signatures.bar_at(9.5), (2, 8.0));
+        assert_eq!(time_signatures.bar_at(17.0), (4, 16.0));
+        assert_eq!(time_signatures.bar_at(-1.0), (-1, -4.0));
+        assert_eq!(time_signatures.time_signature_at(10.0), (6, 8));
+    }
+}
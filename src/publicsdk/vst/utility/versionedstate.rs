@@ -0,0 +1,273 @@
+//! Versioned component and controller states with migrations from older versions
+//!
+//! A state blob starts with a magic number identifying the plug-in, the version of
+//! its layout and the size of its payload. When the layout changes, the version is
+//! increased and a migration rewriting the previous payload is registered, so that
+//! states saved by any older version can still be loaded:
+//!
+//! ```ignore
+//! let format = VersionedState::new(*b"Gain", 3)
+//!     .with_migration(1, |payload| { /* add the bypass flag */ })
+//!     .with_migration(2, |payload| { /* store the gain as f64 */ });
+//! let (gain, bypass) = format.read_with(state, |reader| Ok((reader.read_f64()?, reader.read_bool()?)))?;
+//! ```
+use crate::{
+    plugininterfaces::base::ibstream::IBStream,
+    publicsdk::common::{
+        fstreamer::{StreamError, StreamReader, StreamWriter},
+        memorystream::MemoryStream,
+    },
+};
+use std::{collections::BTreeMap, fmt};
+
+/// Error while reading or writing a versioned state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The state doesn't start with the expected magic number
+    InvalidMagic([u8; 4]),
+    /// The state was written by a newer version than the one supported
+    FutureVersion { version: u32, supported: u32 },
+    /// There is no migration from the given version to the next one
+    MissingMigration(u32),
+    /// A migration from the given version rejected the payload
+    Migration { from: u32, message: String },
+    /// The stream failed or ended early
+    Stream(StreamError),
+}
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::InvalidMagic(magic) => write!(f, "Invalid state magic number {magic:?}"),
+            StateError::FutureVersion { version, supported } => write!(
+                f,
+                "State version {version} is newer than the supported version {supported}"
+            ),
+            StateError::MissingMigration(from) => {
+                write!(f, "No state migration from version {from}")
+            }
+            StateError::Migration { from, message } => {
+                write!(f, "State migration from version {from} failed: {message}")
+            }
+            StateError::Stream(error) => error.fmt(f),
+        }
+    }
+}
+impl std::error::Error for StateError {}
+impl From<StreamError> for StateError {
+    fn from(error: StreamError) -> Self {
+        StateError::Stream(error)
+    }
+}
+
+/// Rewrites the payload of one version into the layout of the next version
+pub type Migration = Box<dyn Fn(&[u8]) -> Result<Vec<u8>, StateError> + Send + Sync>;
+
+/// Header and payload of a state blob
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateBlob {
+    pub version: u32,
+    pub payload: Vec<u8>,
+}
+
+/// Layout of the states of a component or controller
+///
+/// Versions start at 1. States are written with the current version, older states
+/// are migrated step by step before they are decoded.
+pub struct VersionedState {
+    magic: [u8; 4],
+    version: u32,
+    migrations: BTreeMap<u32, Migration>,
+}
+impl VersionedState {
+    pub fn new(magic: [u8; 4], version: u32) -> Self {
+        Self {
+            magic,
+            version,
+            migrations: BTreeMap::new(),
+        }
+    }
+    /// Registers the migration from version `from` to `from + 1`, replacing a previous one
+    pub fn with_migration(
+        mut self,
+        from: u32,
+        migration: impl Fn(&[u8]) -> Result<Vec<u8>, StateError> + Send + Sync + 'static,
+    ) -> Self {
+        self.add_migration(from, migration);
+        self
+    }
+    pub fn add_migration(
+        &mut self,
+        from: u32,
+        migration: impl Fn(&[u8]) -> Result<Vec<u8>, StateError> + Send + Sync + 'static,
+    ) {
+        self.migrations.insert(from, Box::new(migration));
+    }
+    pub fn magic(&self) -> [u8; 4] {
+        self.magic
+    }
+    /// Current version, states are written with it
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+    /// Writes `payload` with the header of the current version
+    pub fn write(&self, stream: &IBStream, payload: &[u8]) -> Result<(), StateError> {
+        let mut writer = StreamWriter::new(stream);
+        writer.write_raw(&self.magic)?;
+        writer.write_u32(self.version)?;
+        writer.write_u64(payload.len() as u64)?;
+        writer.write_raw(payload)?;
+        Ok(())
+    }
+    /// Writes the payload encoded by `encode` with the header of the current version
+    pub fn write_with(
+        &self,
+        stream: &IBStream,
+        encode: impl FnOnce(&mut StreamWriter) -> Result<(), StreamError>,
+    ) -> Result<(), StateError> {
+        let payload = MemoryStream::new();
+        encode(&mut StreamWriter::new(&payload.as_interface()))?;
+        self.write(stream, &payload.into_inner())
+    }
+    /// Reads the header and payload of a state without migrating it
+    pub fn read_blob(&self, stream: &IBStream) -> Result<StateBlob, StateError> {
+        let mut reader = StreamReader::new(stream);
+        let mut magic = [0; 4];
+        reader.read_raw(&mut magic)?;
+        if magic != self.magic {
+            return Err(StateError::InvalidMagic(magic));
+        }
+        let version = reader.read_u32()?;
+        if version > self.version {
+            return Err(StateError::FutureVersion {
+                version,
+                supported: self.version,
+            });
+        }
+        let size = reader.read_u64()?;
+        let payload = reader.read_block(usize::try_from(size).unwrap_or(usize::MAX))?;
+        Ok(StateBlob { version, payload })
+    }
+    /// Upgrades `blob` to the current version
+    pub fn migrate(&self, blob: StateBlob) -> Result<Vec<u8>, StateError> {
+        if blob.version > self.version {
+            return Err(StateError::FutureVersion {
+                version: blob.version,
+                supported: self.version,
+            });
+        }
+        let mut payload = blob.payload;
+        for from in blob.version..self.version {
+            let migration = self
+                .migrations
+                .get(&from)
+                .ok_or(StateError::MissingMigration(from))?;
+            payload = migration(&payload)?;
+        }
+        Ok(payload)
+    }
+    /// Reads a state and returns its payload in the layout of the current version
+    pub fn read(&self, stream: &IBStream) -> Result<Vec<u8>, StateError> {
+        self.migrate(self.read_blob(stream)?)
+    }
+    /// Reads a state and decodes its payload in the layout of the current version
+    pub fn read_with<T>(
+        &self,
+        stream: &IBStream,
+        decode: impl FnOnce(&mut StreamReader) -> Result<T, StreamError>,
+    ) -> Result<T, StateError> {
+        let payload = MemoryStream::from_vec(self.read(stream)?);
+        Ok(decode(&mut StreamReader::new(&payload.as_interface()))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Re-encodes a payload with `transcode`
+    fn transcode(
+        payload: &[u8],
+        transcode: impl FnOnce(&mut StreamReader, &mut StreamWriter) -> Result<(), StreamError>,
+    ) -> Result<Vec<u8>, StateError> {
+        let input = MemoryStream::from_vec(payload.to_vec());
+        let output = MemoryStream::new();
+        transcode(
+            &mut StreamReader::new(&input.as_interface()),
+            &mut StreamWriter::new(&output.as_interface()),
+        )?;
+        Ok(output.into_inner())
+    }
+
+    fn format() -> VersionedState {
+        VersionedState::new(*b"Gain", 3)
+            .with_migration(1, |payload| {
+                transcode(payload, |reader, writer| {
+                    writer.write_f32(reader.read_f32()?)?;
+                    writer.write_bool(false)
+                })
+            })
+            .with_migration(2, |payload| {
+                transcode(payload, |reader, writer| {
+                    writer.write_f64(reader.read_f32()? as f64)?;
+                    writer.write_bool(reader.read_bool()?)
+                })
+            })
+    }
+
+    #[test]
+    fn migrates_old_versions() {
+        let old = MemoryStream::new();
+        VersionedState::new(*b"Gain", 1)
+            .write_with(&old.as_interface(), |writer| writer.write_f32(0.5))
+            .unwrap();
+        old.set_position(0);
+        let state = format()
+            .read_with(&old.as_interface(), |reader| {
+                Ok((reader.read_f64()?, reader.read_bool()?))
+            })
+            .unwrap();
+        assert_eq!(state, (0.5, false));
+
+        let current = MemoryStream::new();
+        format()
+            .write_with(&current.as_interface(), |writer| {
+                writer.write_f64(0.25)?;
+                writer.write_bool(true)
+            })
+            .unwrap();
+        current.set_position(0);
+        let blob = format().read_blob(&current.as_interface()).unwrap();
+        assert_eq!(blob.version, 3);
+        assert_eq!(blob.payload.len(), 10);
+    }
+
+    #[test]
+    fn rejects_unknown_states() {
+        let future = MemoryStream::new();
+        VersionedState::new(*b"Gain", 4)
+            .write(&future.as_interface(), &[])
+            .unwrap();
+        future.set_position(0);
+        assert_eq!(
+            format().read(&future.as_interface()),
+            Err(StateError::FutureVersion {
+                version: 4,
+                supported: 3
+            })
+        );
+
+        let other = MemoryStream::from_vec(b"Comp\x01\0\0\0".to_vec());
+        assert_eq!(
+            format().read(&other.as_interface()),
+            Err(StateError::InvalidMagic(*b"Comp"))
+        );
+
+        let missing =
+            VersionedState::new(*b"Gain", 3).with_migration(2, |payload| Ok(payload.to_vec()));
+        let blob = StateBlob {
+            version: 1,
+            payload: Vec::new(),
+        };
+        assert_eq!(missing.migrate(blob), Err(StateError::MissingMigration(1)));
+    }
+}
@@ -0,0 +1,377 @@
+//! Polyphonic voice allocation for instruments
+//!
+//! A [`VoiceManager`] assigns note-on events to a fixed pool of voices and routes
+//! the note-off, poly pressure and note expression events of a note to the voice
+//! playing it. Notes are identified by their note ID, or by their channel and pitch
+//! when the host doesn't provide note IDs:
+//!
+//! ```ignore
+//! for (event, kind) in data.input_events().events() {
+//!     self.voices.process_event(event.sample_offset, &kind);
+//! }
+//! ```
+use crate::plugininterfaces::vst::{
+    ivstevents::{EventKind, IEventList, NoteOffEvent, NoteOnEvent},
+    ivstnoteexpression::{NoteExpressionTypeID, NoteExpressionValue},
+};
+
+/// A voice of an instrument, driven by a [`VoiceManager`]
+pub trait Voice {
+    /// Starts playing a note
+    fn note_on(&mut self, event: &NoteOnEvent, sample_offset: i32);
+    /// Releases the note, the voice may keep sounding until [`Voice::is_active`] returns false
+    fn note_off(&mut self, event: &NoteOffEvent, sample_offset: i32);
+    /// Changes a note expression of the playing note
+    fn note_expression(
+        &mut self,
+        _type_id: NoteExpressionTypeID,
+        _value: NoteExpressionValue,
+        _sample_offset: i32,
+    ) {
+    }
+    /// Changes the pressure of the playing note
+    fn poly_pressure(&mut self, _pressure: f32, _sample_offset: i32) {}
+    /// Cuts the voice to play another note, the next call is [`Voice::note_on`]
+    fn steal(&mut self, _sample_offset: i32) {}
+    /// Whether the voice is still sounding
+    fn is_active(&self) -> bool;
+    /// Current output level, used by [`StealingPolicy::Quietest`]
+    fn level(&self) -> f32 {
+        0.0
+    }
+}
+
+/// Which voice a [`VoiceManager`] steals when all voices are in use
+///
+/// Released voices are always stolen before held ones.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StealingPolicy {
+    /// Voice that started first
+    Oldest,
+    /// Voice with the lowest [`Voice::level`]
+    Quietest,
+    /// Voice playing the same channel and pitch, otherwise the oldest one
+    SameNote,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Slot {
+    /// Note ID of the note, -1 if it is identified by channel and pitch
+    note_id: i32,
+    channel: i16,
+    pitch: i16,
+    /// Order in which the notes started
+    age: u64,
+    playing: bool,
+    released: bool,
+}
+impl Slot {
+    fn matches(&self, note_id: i32, channel: i16, pitch: i16) -> bool {
+        if note_id != -1 && self.note_id != -1 {
+            self.note_id == note_id
+        } else {
+            self.channel == channel && self.pitch == pitch
+        }
+    }
+}
+
+/// Fixed pool of voices allocated to notes
+pub struct VoiceManager<V: Voice> {
+    voices: Vec<V>,
+    slots: Vec<Slot>,
+    voice_limit: usize,
+    policy: StealingPolicy,
+    note_count: u64,
+}
+impl<V: Voice> VoiceManager<V> {
+    /// Manages `voices`, all of them can play at once
+    pub fn new(voices: Vec<V>, policy: StealingPolicy) -> Self {
+        Self {
+            slots: vec![Slot::default(); voices.len()],
+            voice_limit: voices.len(),
+            voices,
+            policy,
+            note_count: 0,
+        }
+    }
+    pub fn policy(&self) -> StealingPolicy {
+        self.policy
+    }
+    pub fn set_policy(&mut self, policy: StealingPolicy) {
+        self.policy = policy;
+    }
+    pub fn voice_limit(&self) -> usize {
+        self.voice_limit
+    }
+    /// Limits the number of voices playing at once, clamped to the size of the pool.
+    /// Voices above the limit are stolen.
+    pub fn set_voice_limit(&mut self, voice_limit: usize) {
+        self.voice_limit = voice_limit.min(self.voices.len());
+        for index in self.voice_limit..self.voices.len() {
+            if self.slots[index].playing {
+                self.voices[index].steal(0);
+                self.slots[index].playing = false;
+            }
+        }
+    }
+    pub fn voices(&self) -> &[V] {
+        &self.voices
+    }
+    pub fn voices_mut(&mut self) -> &mut [V] {
+        &mut self.voices
+    }
+    /// Voices that are still sounding, with their index
+    pub fn active_voices(&mut self) -> impl Iterator<Item = (usize, &mut V)> {
+        self.voices[..self.voice_limit]
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, voice)| voice.is_active())
+    }
+    /// Index of the voice playing the note, held notes are preferred over released ones
+    pub fn find_voice(&self, note_id: i32, channel: i16, pitch: i16) -> Option<usize> {
+        let mut released = None;
+        for (index, slot) in self.slots[..self.voice_limit].iter().enumerate() {
+            if !slot.playing || !slot.matches(note_id, channel, pitch) {
+                continue;
+            }
+            if !slot.released {
+                return Some(index);
+            }
+            released.get_or_insert(index);
+        }
+        released
+    }
+    /// Index of the voice playing the note `note_id`
+    fn find_note_id(&self, note_id: i32) -> Option<usize> {
+        if note_id == -1 {
+            return None;
+        }
+        self.slots[..self.voice_limit]
+            .iter()
+            .position(|slot| slot.playing && slot.note_id == note_id)
+    }
+    /// Forgets the voices that stopped sounding
+    fn update_slots(&mut self) {
+        for (slot, voice) in self.slots.iter_mut().zip(&self.voices) {
+            slot.playing &= voice.is_active();
+        }
+    }
+    /// Picks the voice for a new note, stealing one if needed.
+    /// Returns the index and whether the voice is stolen.
+    fn allocate(&mut self, channel: i16, pitch: i16) -> Option<(usize, bool)> {
+        self.update_slots();
+        let slots = &self.slots[..self.voice_limit];
+        if self.policy == StealingPolicy::SameNote {
+            if let Some(index) = slots
+                .iter()
+                .position(|slot| slot.playing && slot.channel == channel && slot.pitch == pitch)
+            {
+                return Some((index, true));
+            }
+        }
+        if let Some(index) = slots.iter().position(|slot| !slot.playing) {
+            return Some((index, false));
+        }
+        // Released voices first, then by policy
+        let index = (0..slots.len()).min_by(|&a, &b| {
+            let order = slots[b].released.cmp(&slots[a].released);
+            order.then_with(|| match self.policy {
+                StealingPolicy::Quietest => self.voices[a]
+                    .level()
+                    .total_cmp(&self.voices[b].level())
+                    .then(slots[a].age.cmp(&slots[b].age)),
+                StealingPolicy::Oldest | StealingPolicy::SameNote => {
+                    slots[a].age.cmp(&slots[b].age)
+                }
+            })
+        })?;
+        Some((index, true))
+    }
+    /// Starts a note, returns the index of the voice playing it
+    /// or `None` if the voice limit is 0
+    pub fn note_on(&mut self, event: &NoteOnEvent, sample_offset: i32) -> Option<usize> {
+        let (index, stolen) = self.allocate(event.channel, event.pitch)?;
+        if stolen {
+            self.voices[index].steal(sample_offset);
+        }
+        self.note_count += 1;
+        self.slots[index] = Slot {
+            note_id: event.note_id,
+            channel: event.channel,
+            pitch: event.pitch,
+            age: self.note_count,
+            playing: true,
+            released: false,
+        };
+        self.voices[index].note_on(event, sample_offset);
+        Some(index)
+    }
+    /// Releases a note, returns the index of the voice that played it
+    pub fn note_off(&mut self, event: &NoteOffEvent, sample_offset: i32) -> Option<usize> {
+        let index = self.find_voice(event.note_id, event.channel, event.pitch)?;
+        if self.slots[index].released {
+            return None;
+        }
+        self.slots[index].released = true;
+        self.voices[index].note_off(event, sample_offset);
+        Some(index)
+    }
+    /// Routes a note related event to its voice, returns the index of the voice
+    /// or `None` if the event wasn't routed
+    pub fn process_event(&mut self, sample_offset: i32, kind: &EventKind) -> Option<usize> {
+        match kind {
+            EventKind::NoteOn(event) => self.note_on(event, sample_offset),
+            EventKind::NoteOff(event) => self.note_off(event, sample_offset),
+            EventKind::PolyPressure(event) => {
+                let index = self.find_voice(event.note_id, event.channel, event.pitch)?;
+                self.voices[index].poly_pressure(event.pressure, sample_offset);
+                Some(index)
+            }
+            EventKind::NoteExpressionValue(event) => {
+                let index = self.find_note_id(event.note_id)?;
+                self.voices[index].note_expression(event.type_id, event.value, sample_offset);
+                Some(index)
+            }
+            _ => None,
+        }
+    }
+    /// Routes all events of `events`, they must be sorted by sample offset
+    pub fn process_events(&mut self, events: &IEventList) {
+        for (event, kind) in events.events() {
+            self.process_event(event.sample_offset, &kind);
+        }
+    }
+    /// Cuts all voices
+    pub fn reset(&mut self) {
+        for (slot, voice) in self.slots.iter_mut().zip(&mut self.voices) {
+            if slot.playing {
+                voice.steal(0);
+            }
+            *slot = Slot::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugininterfaces::vst::ivstnoteexpression::{
+        kTuningTypeID, NoteExpressionValueEvent,
+    };
+
+    #[derive(Default)]
+    struct TestVoice {
+        pitch: i16,
+        level: f32,
+        held: bool,
+        tuning: f64,
+        steals: usize,
+    }
+    impl Voice for TestVoice {
+        fn note_on(&mut self, event: &NoteOnEvent, _sample_offset: i32) {
+            self.pitch = event.pitch;
+            self.level = event.velocity;
+            self.held = true;
+        }
+        fn note_off(&mut self, _event: &NoteOffEvent, _sample_offset: i32) {
+            self.held = false;
+        }
+        fn note_expression(
+            &mut self,
+            type_id: NoteExpressionTypeID,
+            value: NoteExpressionValue,
+            _sample_offset: i32,
+        ) {
+            if type_id == kTuningTypeID {
+                self.tuning = value;
+            }
+        }
+        fn steal(&mut self, _sample_offset: i32) {
+            self.steals += 1;
+        }
+        // Voices keep sounding after their release
+        fn is_active(&self) -> bool {
+            self.level > 0.0
+        }
+        fn level(&self) -> f32 {
+            self.level
+        }
+    }
+
+    fn note_on(pitch: i16, velocity: f32, note_id: i32) -> EventKind<'static> {
+        EventKind::NoteOn(NoteOnEvent {
+            channel: 0,
+            pitch,
+            tuning: 0.0,
+            velocity,
+            length: 0,
+            note_id,
+        })
+    }
+
+    fn note_off(pitch: i16, note_id: i32) -> EventKind<'static> {
+        EventKind::NoteOff(NoteOffEvent {
+            channel: 0,
+            pitch,
+            velocity: 0.0,
+            note_id,
+            tuning: 0.0,
+        })
+    }
+
+    fn manager(policy: StealingPolicy) -> VoiceManager<TestVoice> {
+        VoiceManager::new((0..3).map(|_| TestVoice::default()).collect(), policy)
+    }
+
+    #[test]
+    fn routes_by_note_id_and_pitch() {
+        let mut voices = manager(StealingPolicy::Oldest);
+        assert_eq!(voices.process_event(0, &note_on(60, 0.5, 7)), Some(0));
+        assert_eq!(voices.process_event(0, &note_on(60, 0.5, 8)), Some(1));
+        assert_eq!(voices.process_event(0, &note_on(64, 0.5, -1)), Some(2));
+
+        let expression = EventKind::NoteExpressionValue(NoteExpressionValueEvent {
+            type_id: kTuningTypeID,
+            note_id: 8,
+            value: 0.75,
+        });
+        assert_eq!(voices.process_event(4, &expression), Some(1));
+        assert_eq!(voices.voices()[1].tuning, 0.75);
+
+        assert_eq!(voices.process_event(8, &note_off(60, 8)), Some(1));
+        assert_eq!(voices.process_event(8, &note_off(64, -1)), Some(2));
+        assert_eq!(voices.process_event(8, &note_off(64, -1)), None);
+        assert!(voices.voices()[0].held);
+        assert!(!voices.voices()[1].held);
+    }
+
+    #[test]
+    fn stealing_policies() {
+        // Released voices are stolen first
+        let mut voices = manager(StealingPolicy::Oldest);
+        for (pitch, velocity) in [(60, 0.5), (62, 0.2), (64, 0.9)] {
+            voices.process_event(0, &note_on(pitch, velocity, -1));
+        }
+        voices.process_event(0, &note_off(62, -1));
+        assert_eq!(voices.process_event(0, &note_on(65, 0.5, -1)), Some(1));
+        assert_eq!(voices.process_event(0, &note_on(67, 0.5, -1)), Some(0));
+        assert_eq!(voices.voices()[0].steals, 1);
+
+        let mut voices = manager(StealingPolicy::Quietest);
+        for (pitch, velocity) in [(60, 0.5), (62, 0.2), (64, 0.9)] {
+            voices.process_event(0, &note_on(pitch, velocity, -1));
+        }
+        assert_eq!(voices.process_event(0, &note_on(65, 0.5, -1)), Some(1));
+
+        let mut voices = manager(StealingPolicy::SameNote);
+        voices.process_event(0, &note_on(60, 0.5, -1));
+        voices.process_event(0, &note_on(62, 0.5, -1));
+        assert_eq!(voices.process_event(0, &note_on(60, 0.5, -1)), Some(0));
+
+        voices.set_voice_limit(1);
+        assert_eq!(voices.voices()[1].steals, 1);
+        assert_eq!(voices.process_event(0, &note_on(64, 0.5, -1)), Some(0));
+        voices.set_voice_limit(0);
+        assert_eq!(voices.process_event(0, &note_on(64, 0.5, -1)), None);
+    }
+}
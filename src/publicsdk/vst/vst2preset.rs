@@ -0,0 +1,566 @@
+//! Import of VST 2 program (`.fxp`) and bank (`.fxb`) files into [`VstPreset`]s
+//!
+//! Both files start with a `CcnK` chunk header followed by a format magic:
+//!
+//! | Magic  | Content |
+//! |--------|---------|
+//! | `FxCk` | program with a list of normalized parameter values |
+//! | `FPCh` | program with an opaque chunk |
+//! | `FxBk` | bank of `FxCk` or `FPCh` programs |
+//! | `FBCh` | bank with an opaque chunk |
+//!
+//! All numbers are big-endian. Parameter values are mapped to VST 3 parameters
+//! through a [`ParamIDTable`], which can be filled from the [`IRemapParamID`] of
+//! the VST 3 edit controller:
+//!
+//! ```ignore
+//! let class_id = class_id_from_vst2(unique_id, "my synth", false);
+//! let table = ParamIDTable::from_remap(&remap, &class_id, 128);
+//! let Vst2File::Bank(bank) = Vst2File::from_bytes(fs::read("factory.fxb")?)? else { ... };
+//! for preset in bank.to_vstpresets(class_id, &table, |values| encode_state(values)) {
+//!     library.save(&preset, &preset_name(&preset), Some("Imported"))?;
+//! }
+//! ```
+#![allow(non_upper_case_globals)]
+
+use crate::{
+    plugininterfaces::{
+        base::{ibstream::IBStream, FUID},
+        vst::{
+            ivstremapparamid::IRemapParamID,
+            vsttypes::{ParamID, ParamValue},
+        },
+    },
+    publicsdk::{
+        common::{
+            fstreamer::{ByteOrder, StreamError, StreamReader},
+            memorystream::MemoryStream,
+        },
+        vst::{
+            vstpresetfile::{class_id_from_string, VstPreset},
+            vstpresetmetainfo::PresetMetaInfo,
+        },
+    },
+};
+use std::{collections::HashMap, fmt};
+
+/// Magic of the chunk header
+pub const kChunkMagic: [u8; 4] = *b"CcnK";
+/// Program with parameter values
+pub const kProgramMagic: [u8; 4] = *b"FxCk";
+/// Program with an opaque chunk
+pub const kProgramChunkMagic: [u8; 4] = *b"FPCh";
+/// Bank of programs
+pub const kBankMagic: [u8; 4] = *b"FxBk";
+/// Bank with an opaque chunk
+pub const kBankChunkMagic: [u8; 4] = *b"FBCh";
+/// Size of the program name, including the terminating zero
+const kProgramNameSize: usize = 28;
+/// Size of the reserved bytes following the program count of a bank
+const kBankReservedSize: i64 = 128;
+
+/// Error while reading a VST 2 file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vst2PresetError {
+    /// The file doesn't start with `CcnK`
+    InvalidMagic([u8; 4]),
+    /// The format magic is none of the known ones
+    UnknownFormat([u8; 4]),
+    /// A parameter count, program count or chunk size is negative
+    InvalidSize(i32),
+    /// The stream failed or ended early
+    Stream(StreamError),
+}
+impl fmt::Display for Vst2PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Vst2PresetError::InvalidMagic(magic) => write!(
+                f,
+                "Invalid VST 2 chunk magic {}",
+                String::from_utf8_lossy(magic)
+            ),
+            Vst2PresetError::UnknownFormat(magic) => write!(
+                f,
+                "Unknown VST 2 preset format {}",
+                String::from_utf8_lossy(magic)
+            ),
+            Vst2PresetError::InvalidSize(size) => write!(f, "Invalid size {size}"),
+            Vst2PresetError::Stream(error) => error.fmt(f),
+        }
+    }
+}
+impl std::error::Error for Vst2PresetError {}
+impl From<StreamError> for Vst2PresetError {
+    fn from(error: StreamError) -> Self {
+        Vst2PresetError::Stream(error)
+    }
+}
+
+/// Class ID of a VST 3 plug-in which replaces the VST 2 plug-in with `unique_id` and
+/// `plugin_name`, built like `convertVST2UID_To_FUID` of the SDK
+///
+/// `plugin_name` has to be lower case, only its first 9 bytes are used.
+pub fn class_id_from_vst2(unique_id: i32, plugin_name: &str, for_controller: bool) -> FUID {
+    let mut string = String::from(if for_controller { "565345" } else { "565354" });
+    string.push_str(&format!("{:08X}", unique_id as u32));
+    let name = plugin_name.as_bytes();
+    for i in 0..9 {
+        string.push_str(&format!("{:02X}", name.get(i).copied().unwrap_or(0)));
+    }
+    // 32 hex digits always parse
+    class_id_from_string(&string).unwrap()
+}
+
+/// Maps VST 2 parameter indices to VST 3 parameter IDs
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParamIDTable {
+    ids: HashMap<u32, ParamID>,
+    keep_unmapped: bool,
+}
+impl ParamIDTable {
+    /// Table which drops all parameters without an entry
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Table which uses the index as ID for parameters without an entry
+    pub fn identity() -> Self {
+        Self {
+            keep_unmapped: true,
+            ..Self::default()
+        }
+    }
+    /// Asks `remap` for the IDs of the first `count` parameters of `plugin_to_replace`.
+    /// Parameters it doesn't know keep their index as ID, like hosts do.
+    pub fn from_remap(remap: &IRemapParamID, plugin_to_replace: &FUID, count: u32) -> Self {
+        let mut table = Self::identity();
+        for index in 0..count {
+            if let Ok(id) = remap.get_compatible_param_id(plugin_to_replace, index) {
+                table.insert(index, id);
+            }
+        }
+        table
+    }
+    pub fn insert(&mut self, index: u32, id: ParamID) {
+        self.ids.insert(index, id);
+    }
+    pub fn remove(&mut self, index: u32) -> Option<ParamID> {
+        self.ids.remove(&index)
+    }
+    /// ID of the parameter at `index`, `None` if it is dropped
+    pub fn get(&self, index: u32) -> Option<ParamID> {
+        self.ids
+            .get(&index)
+            .copied()
+            .or(self.keep_unmapped.then_some(index))
+    }
+}
+impl FromIterator<(u32, ParamID)> for ParamIDTable {
+    fn from_iter<T: IntoIterator<Item = (u32, ParamID)>>(iter: T) -> Self {
+        Self {
+            ids: iter.into_iter().collect(),
+            keep_unmapped: false,
+        }
+    }
+}
+
+/// Content of a VST 2 program
+#[derive(Clone, Debug, PartialEq)]
+pub enum Vst2ProgramContent {
+    /// Normalized values by parameter index
+    Parameters(Vec<f32>),
+    /// Opaque data of the plug-in
+    Chunk(Vec<u8>),
+}
+
+/// VST 2 program, read from an `.fxp` file or a bank
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vst2Program {
+    /// Unique ID of the plug-in
+    pub plugin_id: i32,
+    pub plugin_version: i32,
+    pub name: String,
+    pub content: Vst2ProgramContent,
+}
+impl Vst2Program {
+    /// Values of the parameters which `table` maps, by VST 3 parameter ID
+    pub fn parameters(&self, table: &ParamIDTable) -> Vec<(ParamID, ParamValue)> {
+        match &self.content {
+            Vst2ProgramContent::Parameters(values) => values
+                .iter()
+                .enumerate()
+                .filter_map(|(index, &value)| {
+                    let id = table.get(index as u32)?;
+                    Some((id, ParamValue::from(value).clamp(0., 1.)))
+                })
+                .collect(),
+            Vst2ProgramContent::Chunk(_) => Vec::new(),
+        }
+    }
+    /// Converts the program into a preset of the plug-in class `class_id`
+    ///
+    /// An opaque chunk becomes the component state as it is, parameter values are
+    /// mapped through `table` and turned into the component state by `encode`. The
+    /// program name is stored in the meta information.
+    pub fn to_vstpreset<F>(&self, class_id: FUID, table: &ParamIDTable, encode: F) -> VstPreset
+    where
+        F: FnOnce(&[(ParamID, ParamValue)]) -> Vec<u8>,
+    {
+        let mut preset = VstPreset::new(class_id);
+        preset.set_component_state(match &self.content {
+            Vst2ProgramContent::Parameters(_) => encode(&self.parameters(table)),
+            Vst2ProgramContent::Chunk(chunk) => chunk.clone(),
+        });
+        if !self.name.is_empty() {
+            let info = PresetMetaInfo {
+                name: Some(self.name.clone()),
+                ..Default::default()
+            };
+            info.store(&mut preset);
+        }
+        preset
+    }
+}
+
+/// Content of a VST 2 bank
+#[derive(Clone, Debug, PartialEq)]
+pub enum Vst2BankContent {
+    Programs(Vec<Vst2Program>),
+    /// Opaque data of the plug-in
+    Chunk(Vec<u8>),
+}
+
+/// VST 2 bank, read from an `.fxb` file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vst2Bank {
+    /// Unique ID of the plug-in
+    pub plugin_id: i32,
+    pub plugin_version: i32,
+    /// Index of the current program, 0 for version 1 banks
+    pub current_program: i32,
+    pub content: Vst2BankContent,
+}
+impl Vst2Bank {
+    /// Converts the programs of the bank like [`Vst2Program::to_vstpreset`]. A bank
+    /// chunk is converted into a single preset holding the chunk as component state.
+    pub fn to_vstpresets<F>(
+        &self,
+        class_id: FUID,
+        table: &ParamIDTable,
+        mut encode: F,
+    ) -> Vec<VstPreset>
+    where
+        F: FnMut(&[(ParamID, ParamValue)]) -> Vec<u8>,
+    {
+        match &self.content {
+            Vst2BankContent::Programs(programs) => programs
+                .iter()
+                .map(|program| program.to_vstpreset(class_id, table, &mut encode))
+                .collect(),
+            Vst2BankContent::Chunk(chunk) => {
+                let mut preset = VstPreset::new(class_id);
+                preset.set_component_state(chunk.clone());
+                vec![preset]
+            }
+        }
+    }
+}
+
+/// Content of an `.fxp` or `.fxb` file
+#[derive(Clone, Debug, PartialEq)]
+pub enum Vst2File {
+    Program(Vst2Program),
+    Bank(Vst2Bank),
+}
+impl Vst2File {
+    pub fn read(stream: &IBStream) -> Result<Self, Vst2PresetError> {
+        let mut reader = StreamReader::with_byte_order(stream, ByteOrder::BigEndian);
+        let magic = read_header(&mut reader)?;
+        match magic {
+            kProgramMagic | kProgramChunkMagic => {
+                read_program(&mut reader, magic).map(Vst2File::Program)
+            }
+            kBankMagic | kBankChunkMagic => read_bank(&mut reader, magic).map(Vst2File::Bank),
+            magic => Err(Vst2PresetError::UnknownFormat(magic)),
+        }
+    }
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, Vst2PresetError> {
+        Self::read(&MemoryStream::from_vec(bytes).as_interface())
+    }
+    /// Unique ID of the plug-in the file belongs to
+    pub fn plugin_id(&self) -> i32 {
+        match self {
+            Vst2File::Program(program) => program.plugin_id,
+            Vst2File::Bank(bank) => bank.plugin_id,
+        }
+    }
+}
+
+/// Reads the chunk header, returns the format magic
+fn read_header(reader: &mut StreamReader) -> Result<[u8; 4], Vst2PresetError> {
+    let mut magic = [0; 4];
+    reader.read_raw(&mut magic)?;
+    if magic != kChunkMagic {
+        return Err(Vst2PresetError::InvalidMagic(magic));
+    }
+    // The byte size isn't reliable, many plug-ins write wrong ones
+    reader.read_i32()?;
+    reader.read_raw(&mut magic)?;
+    Ok(magic)
+}
+
+fn read_size(reader: &mut StreamReader) -> Result<usize, Vst2PresetError> {
+    let size = reader.read_i32()?;
+    usize::try_from(size).map_err(|_| Vst2PresetError::InvalidSize(size))
+}
+
+fn read_program(reader: &mut StreamReader, magic: [u8; 4]) -> Result<Vst2Program, Vst2PresetError> {
+    let _format_version = reader.read_i32()?;
+    let plugin_id = reader.read_i32()?;
+    let plugin_version = reader.read_i32()?;
+    let parameter_count = read_size(reader)?;
+    let mut name = [0; kProgramNameSize];
+    reader.read_raw(&mut name)?;
+    let name_length = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    let content = if magic == kProgramChunkMagic {
+        let size = read_size(reader)?;
+        Vst2ProgramContent::Chunk(reader.read_block(size)?)
+    } else {
+        let mut values = Vec::new();
+        for _ in 0..parameter_count {
+            values.push(reader.read_f32()?);
+        }
+        Vst2ProgramContent::Parameters(values)
+    };
+    Ok(Vst2Program {
+        plugin_id,
+        plugin_version,
+        name: String::from_utf8_lossy(&name[..name_length]).into_owned(),
+        content,
+    })
+}
+
+fn read_bank(reader: &mut StreamReader, magic: [u8; 4]) -> Result<Vst2Bank, Vst2PresetError> {
+    let format_version = reader.read_i32()?;
+    let plugin_id = reader.read_i32()?;
+    let plugin_version = reader.read_i32()?;
+    let program_count = read_size(reader)?;
+    let mut current_program = 0;
+    let mut reserved = kBankReservedSize;
+    if format_version >= 2 {
+        current_program = reader.read_i32()?;
+        reserved -= 4;
+    }
+    reader.skip(reserved)?;
+    let content = if magic == kBankChunkMagic {
+        let size = read_size(reader)?;
+        Vst2BankContent::Chunk(reader.read_block(size)?)
+    } else {
+        let mut programs = Vec::new();
+        for _ in 0..program_count {
+            let magic = read_header(reader)?;
+            if magic != kProgramMagic && magic != kProgramChunkMagic {
+                return Err(Vst2PresetError::UnknownFormat(magic));
+            }
+            programs.push(read_program(reader, magic)?);
+        }
+        Vst2BankContent::Programs(programs)
+    };
+    Ok(Vst2Bank {
+        plugin_id,
+        plugin_version,
+        current_program,
+        content,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        plugininterfaces::{
+            base::{pluginreexports::*, EInterface, InterfaceRef, TUID},
+            vst::ivstremapparamid::{IRemapParamIDImpl, IRemapParamIDVtable},
+        },
+        publicsdk::{common::fstreamer::StreamWriter, vst::vstpresetfile::class_id_to_string},
+    };
+    use std::os::raw::c_void;
+
+    const PLUGIN_ID: i32 = i32::from_be_bytes(*b"Syn1");
+
+    fn write_program(writer: &mut StreamWriter, name: &str, content: &Vst2ProgramContent) {
+        writer.write_raw(&kChunkMagic).unwrap();
+        writer.write_i32(0).unwrap();
+        let (magic, count) = match content {
+            Vst2ProgramContent::Parameters(values) => (kProgramMagic, values.len()),
+            Vst2ProgramContent::Chunk(_) => (kProgramChunkMagic, 0),
+        };
+        writer.write_raw(&magic).unwrap();
+        for value in [1, PLUGIN_ID, 3, count as i32] {
+            writer.write_i32(value).unwrap();
+        }
+        let mut name_bytes = [0; kProgramNameSize];
+        name_bytes[..name.len()].copy_from_slice(name.as_bytes());
+        writer.write_raw(&name_bytes).unwrap();
+        match content {
+            Vst2ProgramContent::Parameters(values) => {
+                values.iter().for_each(|&v| writer.write_f32(v).unwrap())
+            }
+            Vst2ProgramContent::Chunk(chunk) => {
+                writer.write_i32(chunk.len() as i32).unwrap();
+                writer.write_raw(chunk).unwrap();
+            }
+        }
+    }
+
+    fn big_endian(write: impl FnOnce(&mut StreamWriter)) -> Vec<u8> {
+        let memory = MemoryStream::new();
+        write(&mut StreamWriter::with_byte_order(
+            &memory.as_interface(),
+            ByteOrder::BigEndian,
+        ));
+        memory.into_inner()
+    }
+
+    #[repr(C)]
+    struct Remap {
+        vtable: &'static IRemapParamIDVtable,
+    }
+    static REMAP_VTABLE: IRemapParamIDVtable = IRemapParamIDVtable::new::<Remap, 0>();
+    impl FUnknownImpl for Remap {
+        type Impl = Self;
+
+        fn get_impl(&self) -> &Self::Impl {
+            self
+        }
+        unsafe fn query_interface(
+            &self,
+            _iid: *const FUID,
+            _interface: *mut *mut c_void,
+        ) -> Result<(), EInterface> {
+            Err(EInterface::BadQuery)
+        }
+        fn add_ref(&self) -> u32 {
+            1
+        }
+        unsafe fn release(_self: *mut Self) -> u32 {
+            1
+        }
+
+        const INNER_OFFSET_IN_POINTERS: usize = 0;
+    }
+    impl IRemapParamIDImpl for Remap {
+        unsafe fn get_compatible_param_id(
+            &self,
+            plugin_to_replace_uid: &TUID,
+            old_param_id: ParamID,
+        ) -> Result<ParamID, EInterface> {
+            let expected = class_id_from_vst2(PLUGIN_ID, "synth", false);
+            match old_param_id {
+                0 if FUID::from(*plugin_to_replace_uid) == expected => Ok(1000),
+                _ => Err(EInterface::ResultFalse),
+            }
+        }
+
+        const INNER_OFFSET_IN_POINTERS: usize = 0;
+    }
+
+    #[test]
+    fn vst2_class_ids() {
+        assert_eq!(
+            class_id_to_string(&class_id_from_vst2(PLUGIN_ID, "synth", false)),
+            "56535453796E3173796E746800000000"
+        );
+        assert_eq!(
+            class_id_to_string(&class_id_from_vst2(PLUGIN_ID, "synthesizer", true)),
+            "56534553796E3173796E74686573697A"
+        );
+        assert_eq!(
+            class_id_to_string(&class_id_from_vst2(PLUGIN_ID, "Synth", false)),
+            "56535453796E3153796E746800000000"
+        );
+    }
+
+    #[test]
+    fn imports_programs_and_banks() {
+        let class_id = class_id_from_vst2(PLUGIN_ID, "synth", false);
+        let remap = Remap {
+            vtable: &REMAP_VTABLE,
+        };
+        let remap = unsafe {
+            InterfaceRef::<IRemapParamID>::from_raw(&remap as *const Remap as *mut c_void).unwrap()
+        };
+        let table = ParamIDTable::from_remap(&remap, &class_id, 3);
+        assert_eq!((table.get(0), table.get(2)), (Some(1000), Some(2)));
+
+        let fxp = big_endian(|writer| {
+            write_program(
+                writer,
+                "Lead",
+                &Vst2ProgramContent::Parameters(vec![0.25, 1.5, 0.]),
+            )
+        });
+        let Ok(Vst2File::Program(program)) = Vst2File::from_bytes(fxp) else {
+            panic!("program expected");
+        };
+        assert_eq!(
+            (program.plugin_id, program.name.as_str()),
+            (PLUGIN_ID, "Lead")
+        );
+        assert_eq!(program.parameters(&table), [(1000, 0.25), (1, 1.), (2, 0.)]);
+        let dropping: ParamIDTable = [(1, 7)].into_iter().collect();
+        let preset = program.to_vstpreset(class_id, &dropping, |values| {
+            assert_eq!(values, [(7, 1.)]);
+            vec![42]
+        });
+        assert_eq!(preset.component_state(), Some(&[42][..]));
+        let info = PresetMetaInfo::from_preset(&preset).unwrap().unwrap();
+        assert_eq!(info.name.as_deref(), Some("Lead"));
+
+        let fxb = big_endian(|writer| {
+            writer.write_raw(&kChunkMagic).unwrap();
+            writer.write_i32(0).unwrap();
+            writer.write_raw(&kBankMagic).unwrap();
+            for value in [2, PLUGIN_ID, 3, 2, 1] {
+                writer.write_i32(value).unwrap();
+            }
+            writer.write_raw(&[0; 124]).unwrap();
+            write_program(writer, "Pad", &Vst2ProgramContent::Chunk(vec![1, 2, 3]));
+            write_program(writer, "Bass", &Vst2ProgramContent::Parameters(vec![0.5]));
+        });
+        let Ok(Vst2File::Bank(bank)) = Vst2File::from_bytes(fxb) else {
+            panic!("bank expected");
+        };
+        assert_eq!(bank.current_program, 1);
+        let presets = bank.to_vstpresets(class_id, &table, |values| {
+            values.iter().map(|&(id, _)| id as u8).collect()
+        });
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[0].component_state(), Some(&[1, 2, 3][..]));
+        assert_eq!(presets[1].component_state(), Some(&[232][..]));
+        assert_eq!(
+            class_id_to_string(&presets[1].class_id()),
+            class_id_to_string(&class_id)
+        );
+
+        let chunk_bank = big_endian(|writer| {
+            writer.write_raw(&kChunkMagic).unwrap();
+            writer.write_i32(0).unwrap();
+            writer.write_raw(&kBankChunkMagic).unwrap();
+            for value in [1, PLUGIN_ID, 3, 8] {
+                writer.write_i32(value).unwrap();
+            }
+            writer.write_raw(&[0; 128]).unwrap();
+            writer.write_i32(2).unwrap();
+            writer.write_raw(&[9, 9]).unwrap();
+        });
+        let Ok(Vst2File::Bank(bank)) = Vst2File::from_bytes(chunk_bank) else {
+            panic!("bank expected");
+        };
+        assert_eq!(bank.content, Vst2BankContent::Chunk(vec![9, 9]));
+
+        assert_eq!(
+            Vst2File::from_bytes(b"RIFF".to_vec()),
+            Err(Vst2PresetError::InvalidMagic(*b"RIFF"))
+        );
+    }
+}
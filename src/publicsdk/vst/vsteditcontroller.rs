@@ -0,0 +1,747 @@
+//! Default edit controller, see `public.sdk/source/vst/vsteditcontroller.h`
+use super::vstparameters::{Parameter, ParameterContainer, RangeParameter};
+use crate::plugininterfaces::{
+    base::{
+        funknown::FUnknown, ibstream::IBStream, ipluginbase::IPluginBaseImpl,
+        ustring::string_from_utf16_ptr, EInterface,
+    },
+    vst::{
+        ivstcomponent::{BusDirection, MediaType},
+        ivsteditcontroller::{
+            from_normalized, IComponentHandler, IEditControllerImpl, ParameterFlags, ParameterInfo,
+        },
+        ivstunits::{kNoParentUnitId, IUnitInfoImpl, ProgramListID, ProgramListInfo, UnitInfo},
+        vsttypes::{
+            kRootUnitId, string128_from_str, ParamID, ParamValue, String128, TChar, UnitID,
+        },
+    },
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    ffi::CStr,
+    ops::{Deref, DerefMut},
+    os::raw::c_void,
+    sync::{Arc, RwLock},
+};
+
+/// Edit controller backed by a [`ParameterContainer`]
+///
+/// Implements the parameter related parts of [`IEditControllerImpl`],
+/// state handling and views are left to the plug-in.
+pub struct EditController {
+    parameters: ParameterContainer,
+    component_handler: RefCell<Option<IComponentHandler>>,
+}
+impl EditController {
+    pub fn new(parameters: ParameterContainer) -> Self {
+        Self {
+            parameters,
+            component_handler: RefCell::new(None),
+        }
+    }
+    pub fn parameters(&self) -> &ParameterContainer {
+        &self.parameters
+    }
+    pub fn parameters_mut(&mut self) -> &mut ParameterContainer {
+        &mut self.parameters
+    }
+    pub fn get_parameter(&self, id: ParamID) -> Option<&dyn Parameter> {
+        self.parameters.get_parameter(id)
+    }
+    pub fn component_handler(&self) -> Option<IComponentHandler> {
+        self.component_handler.borrow().clone()
+    }
+    /// Informs the host that the user started editing a parameter
+    pub fn begin_edit(&self, id: ParamID) -> Result<(), EInterface> {
+        match &*self.component_handler.borrow() {
+            Some(handler) => handler.begin_edit(id),
+            None => Err(EInterface::ResultFalse),
+        }
+    }
+    /// Informs the host about a new normalized value of an edited parameter
+    pub fn perform_edit(
+        &self,
+        id: ParamID,
+        value_normalized: ParamValue,
+    ) -> Result<(), EInterface> {
+        match &*self.component_handler.borrow() {
+            Some(handler) => handler.perform_edit(id, value_normalized),
+            None => Err(EInterface::ResultFalse),
+        }
+    }
+    /// Informs the host that the user finished editing a parameter
+    pub fn end_edit(&self, id: ParamID) -> Result<(), EInterface> {
+        match &*self.component_handler.borrow() {
+            Some(handler) => handler.end_edit(id),
+            None => Err(EInterface::ResultFalse),
+        }
+    }
+    /// Asks the host to restart the component, see `RestartFlags`
+    pub fn restart_component(&self, flags: i32) -> Result<(), EInterface> {
+        match &*self.component_handler.borrow() {
+            Some(handler) => handler.restart_component(flags),
+            None => Err(EInterface::ResultFalse),
+        }
+    }
+}
+impl Default for EditController {
+    fn default() -> Self {
+        Self::new(ParameterContainer::new())
+    }
+}
+impl IPluginBaseImpl for EditController {
+    unsafe fn initialize(&self, _context: *mut c_void) -> Result<(), EInterface> {
+        Ok(())
+    }
+    unsafe fn terminate(&self) {
+        self.component_handler.replace(None);
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IEditControllerImpl for EditController {
+    unsafe fn set_component_state(&self, _state: &IBStream) -> Result<(), EInterface> {
+        Err(EInterface::NotImplemented)
+    }
+    unsafe fn set_state(&self, _state: &IBStream) -> Result<(), EInterface> {
+        Err(EInterface::NotImplemented)
+    }
+    unsafe fn get_state(&self, _state: &IBStream) -> Result<(), EInterface> {
+        Err(EInterface::NotImplemented)
+    }
+    unsafe fn get_parameter_count(&self) -> i32 {
+        self.parameters.get_parameter_count() as i32
+    }
+    unsafe fn get_parameter_info(&self, param_index: i32) -> Result<ParameterInfo, EInterface> {
+        let index = usize::try_from(param_index).map_err(|_| EInterface::InvalidArgument)?;
+        self.parameters
+            .get_parameter_by_index(index)
+            .map(|parameter| *parameter.info())
+            .ok_or(EInterface::InvalidArgument)
+    }
+    unsafe fn get_param_string_by_value(
+        &self,
+        id: ParamID,
+        value_normalized: ParamValue,
+    ) -> Result<String128, EInterface> {
+        let parameter = self.get_parameter(id).ok_or(EInterface::ResultFalse)?;
+        Ok(string128_from_str(
+            &parameter.value_to_string(value_normalized),
+        ))
+    }
+    unsafe fn get_param_value_by_string(
+        &self,
+        id: ParamID,
+        string: *const TChar,
+    ) -> Result<ParamValue, EInterface> {
+        let parameter = self.get_parameter(id).ok_or(EInterface::ResultFalse)?;
+        parameter
+            .value_from_string(&string_from_utf16_ptr(string))
+            .ok_or(EInterface::ResultFalse)
+    }
+    unsafe fn normalized_param_to_plain(
+        &self,
+        id: ParamID,
+        value_normalized: ParamValue,
+    ) -> ParamValue {
+        match self.get_parameter(id) {
+            Some(parameter) => parameter.to_plain(value_normalized),
+            None => value_normalized,
+        }
+    }
+    unsafe fn plain_param_to_normalized(&self, id: ParamID, plain_value: ParamValue) -> ParamValue {
+        match self.get_parameter(id) {
+            Some(parameter) => parameter.to_normalized(plain_value),
+            None => plain_value,
+        }
+    }
+    unsafe fn get_param_normalized(&self, id: ParamID) -> ParamValue {
+        match self.get_parameter(id) {
+            Some(parameter) => parameter.normalized(),
+            None => 0.0,
+        }
+    }
+    unsafe fn set_param_normalized(
+        &self,
+        id: ParamID,
+        value: ParamValue,
+    ) -> Result<(), EInterface> {
+        let parameter = self.get_parameter(id).ok_or(EInterface::ResultFalse)?;
+        parameter.set_normalized(value);
+        Ok(())
+    }
+    unsafe fn set_component_handler(
+        &self,
+        handler: Option<&IComponentHandler>,
+    ) -> Result<(), EInterface> {
+        self.component_handler.replace(handler.cloned());
+        Ok(())
+    }
+    unsafe fn create_view(&self, _name: &CStr) -> Option<FUnknown> {
+        None
+    }
+}
+
+/// Program list of a unit, selected through a program change parameter
+///
+/// Programs are added before the list is handed to [`EditControllerEx1::add_program_list`],
+/// which creates the parameter. Afterwards, programs can be renamed but not added.
+pub struct ProgramList {
+    info: ProgramListInfo,
+    unit_id: UnitID,
+    parameter_id: ParamID,
+    /// Shared with the formatter of the program change parameter
+    names: Arc<RwLock<Vec<String>>>,
+    attributes: Vec<HashMap<String, String>>,
+    pitch_names: Vec<HashMap<i16, String>>,
+}
+impl ProgramList {
+    /// Empty program list of the unit `unit_id`, selected by the parameter `parameter_id`
+    pub fn new(id: ProgramListID, name: &str, unit_id: UnitID, parameter_id: ParamID) -> Self {
+        Self {
+            info: ProgramListInfo::new(id, name, 0),
+            unit_id,
+            parameter_id,
+            names: Arc::default(),
+            attributes: Vec::new(),
+            pitch_names: Vec::new(),
+        }
+    }
+    /// Appends a program, returns its index
+    pub fn add_program(&mut self, name: &str) -> usize {
+        let mut names = self.names.write().unwrap();
+        names.push(name.to_owned());
+        self.attributes.push(HashMap::new());
+        self.pitch_names.push(HashMap::new());
+        self.info.program_count = names.len() as i32;
+        names.len() - 1
+    }
+    pub fn with_programs<S: AsRef<str>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        for name in names {
+            self.add_program(name.as_ref());
+        }
+        self
+    }
+    pub fn info(&self) -> &ProgramListInfo {
+        &self.info
+    }
+    pub fn id(&self) -> ProgramListID {
+        self.info.id
+    }
+    pub fn unit_id(&self) -> UnitID {
+        self.unit_id
+    }
+    pub fn parameter_id(&self) -> ParamID {
+        self.parameter_id
+    }
+    pub fn program_count(&self) -> usize {
+        self.attributes.len()
+    }
+    pub fn program_name(&self, index: usize) -> Option<String> {
+        self.names.read().unwrap().get(index).cloned()
+    }
+    /// Renames a program, returns false if `index` is out of range
+    pub fn set_program_name(&self, index: usize, name: &str) -> bool {
+        match self.names.write().unwrap().get_mut(index) {
+            Some(entry) => {
+                *entry = name.to_owned();
+                true
+            }
+            None => false,
+        }
+    }
+    /// Attribute of a program, `attribute` is one of the preset attribute keys
+    pub fn program_info(&self, index: usize, attribute: &str) -> Option<&str> {
+        Some(self.attributes.get(index)?.get(attribute)?.as_str())
+    }
+    /// Sets an attribute of a program, returns false if `index` is out of range
+    pub fn set_program_info(&mut self, index: usize, attribute: &str, value: &str) -> bool {
+        match self.attributes.get_mut(index) {
+            Some(attributes) => {
+                attributes.insert(attribute.to_owned(), value.to_owned());
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn has_pitch_names(&self, index: usize) -> bool {
+        self.pitch_names
+            .get(index)
+            .is_some_and(|pitch_names| !pitch_names.is_empty())
+    }
+    pub fn pitch_name(&self, index: usize, pitch: i16) -> Option<&str> {
+        Some(self.pitch_names.get(index)?.get(&pitch)?.as_str())
+    }
+    /// Names the MIDI pitch `pitch` of a program, returns false if `index` is out of range
+    pub fn set_pitch_name(&mut self, index: usize, pitch: i16, name: &str) -> bool {
+        match self.pitch_names.get_mut(index) {
+            Some(pitch_names) => {
+                pitch_names.insert(pitch, name.to_owned());
+                true
+            }
+            None => false,
+        }
+    }
+    /// Program selected by a normalized value of the program change parameter
+    pub fn program_index(&self, value_normalized: ParamValue) -> usize {
+        let step_count = self.program_count().saturating_sub(1) as i32;
+        from_normalized(value_normalized, step_count) as usize
+    }
+    /// Program change parameter displaying the program names
+    fn create_parameter(&self) -> RangeParameter {
+        let last = self.program_count().saturating_sub(1);
+        let names = self.names.clone();
+        let parser_names = self.names.clone();
+        RangeParameter::new(
+            self.parameter_id,
+            &self.info.name(),
+            "",
+            0.0,
+            last as f64,
+            0.0,
+        )
+        .with_step_count(last as i32)
+        .with_flags(
+            ParameterFlags::CanAutomate | ParameterFlags::IsList | ParameterFlags::IsProgramChange,
+        )
+        .with_unit_id(self.unit_id)
+        .with_formatter(move |plain_value| {
+            let names = names.read().unwrap();
+            names.get(plain_value as usize).cloned().unwrap_or_default()
+        })
+        .with_parser(move |string| {
+            let names = parser_names.read().unwrap();
+            names
+                .iter()
+                .position(|name| name == string)
+                .map(|index| index as ParamValue)
+        })
+    }
+}
+
+/// Edit controller with units and program lists, see `EditControllerEx1` of the SDK
+///
+/// Derefs to the wrapped [`EditController`] and implements [`IUnitInfoImpl`] on top
+/// of its [`IEditControllerImpl`]. Program lists add their program change parameter
+/// to the parameters of the controller.
+pub struct EditControllerEx1 {
+    controller: EditController,
+    units: Vec<UnitInfo>,
+    program_lists: Vec<ProgramList>,
+    selected_unit: Cell<UnitID>,
+    unit_by_bus: HashMap<(MediaType, BusDirection, i32, Option<i32>), UnitID>,
+}
+impl EditControllerEx1 {
+    pub fn new(controller: EditController) -> Self {
+        Self {
+            controller,
+            units: Vec::new(),
+            program_lists: Vec::new(),
+            selected_unit: Cell::new(kRootUnitId),
+            unit_by_bus: HashMap::new(),
+        }
+    }
+    /// Adds a unit, the root unit has the ID [`kRootUnitId`] and no parent.
+    /// Fails with [`EInterface::InvalidArgument`] if the ID is in use or the parent is unknown.
+    pub fn add_unit(&mut self, unit: UnitInfo) -> Result<(), EInterface> {
+        let parent_known =
+            unit.parent_unit_id == kNoParentUnitId || self.get_unit(unit.parent_unit_id).is_some();
+        if !parent_known || self.get_unit(unit.id).is_some() {
+            return Err(EInterface::InvalidArgument);
+        }
+        self.units.push(unit);
+        Ok(())
+    }
+    pub fn get_unit(&self, id: UnitID) -> Option<&UnitInfo> {
+        self.units.iter().find(|unit| unit.id == id)
+    }
+    pub fn units(&self) -> &[UnitInfo] {
+        &self.units
+    }
+    /// Adds a program list and its program change parameter, and assigns the list to its unit.
+    /// Fails with [`EInterface::InvalidArgument`] if the list is empty, its ID is in use
+    /// or its unit is unknown.
+    pub fn add_program_list(&mut self, program_list: ProgramList) -> Result<(), EInterface> {
+        if program_list.program_count() == 0 || self.get_program_list(program_list.id()).is_some() {
+            return Err(EInterface::InvalidArgument);
+        }
+        let unit = self
+            .units
+            .iter_mut()
+            .find(|unit| unit.id == program_list.unit_id())
+            .ok_or(EInterface::InvalidArgument)?;
+        self.controller
+            .parameters_mut()
+            .add_parameter(program_list.create_parameter())?;
+        unit.program_list_id = program_list.id();
+        self.program_lists.push(program_list);
+        Ok(())
+    }
+    pub fn get_program_list(&self, id: ProgramListID) -> Option<&ProgramList> {
+        self.program_lists.iter().find(|list| list.id() == id)
+    }
+    fn get_program_list_mut(&mut self, id: ProgramListID) -> Option<&mut ProgramList> {
+        self.program_lists.iter_mut().find(|list| list.id() == id)
+    }
+    pub fn program_lists(&self) -> &[ProgramList] {
+        &self.program_lists
+    }
+    /// Renames a program, returns false if the list or program doesn't exist
+    pub fn set_program_name(&self, list_id: ProgramListID, index: usize, name: &str) -> bool {
+        self.get_program_list(list_id)
+            .is_some_and(|list| list.set_program_name(index, name))
+    }
+    /// Sets an attribute of a program, returns false if the list or program doesn't exist
+    pub fn set_program_info(
+        &mut self,
+        list_id: ProgramListID,
+        index: usize,
+        attribute: &str,
+        value: &str,
+    ) -> bool {
+        self.get_program_list_mut(list_id)
+            .is_some_and(|list| list.set_program_info(index, attribute, value))
+    }
+    /// Names a MIDI pitch of a program, returns false if the list or program doesn't exist
+    pub fn set_program_pitch_name(
+        &mut self,
+        list_id: ProgramListID,
+        index: usize,
+        pitch: i16,
+        name: &str,
+    ) -> bool {
+        self.get_program_list_mut(list_id)
+            .is_some_and(|list| list.set_pitch_name(index, pitch, name))
+    }
+    /// Program currently selected by the program change parameter of a list
+    pub fn selected_program(&self, list_id: ProgramListID) -> Option<usize> {
+        let list = self.get_program_list(list_id)?;
+        let parameter = self.controller.get_parameter(list.parameter_id())?;
+        Some(list.program_index(parameter.normalized()))
+    }
+    /// Routes a channel of a bus to a unit, `channel` `None` applies to all channels
+    pub fn set_unit_by_bus(
+        &mut self,
+        type_: MediaType,
+        dir: BusDirection,
+        bus_index: i32,
+        channel: Option<i32>,
+        unit_id: UnitID,
+    ) {
+        self.unit_by_bus
+            .insert((type_, dir, bus_index, channel), unit_id);
+    }
+    pub fn selected_unit(&self) -> UnitID {
+        self.selected_unit.get()
+    }
+}
+impl Default for EditControllerEx1 {
+    fn default() -> Self {
+        Self::new(EditController::default())
+    }
+}
+impl Deref for EditControllerEx1 {
+    type Target = EditController;
+
+    fn deref(&self) -> &Self::Target {
+        &self.controller
+    }
+}
+impl DerefMut for EditControllerEx1 {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.controller
+    }
+}
+impl IPluginBaseImpl for EditControllerEx1 {
+    unsafe fn initialize(&self, context: *mut c_void) -> Result<(), EInterface> {
+        self.controller.initialize(context)
+    }
+    unsafe fn terminate(&self) {
+        self.controller.terminate()
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IEditControllerImpl for EditControllerEx1 {
+    unsafe fn set_component_state(&self, state: &IBStream) -> Result<(), EInterface> {
+        self.controller.set_component_state(state)
+    }
+    unsafe fn set_state(&self, state: &IBStream) -> Result<(), EInterface> {
+        self.controller.set_state(state)
+    }
+    unsafe fn get_state(&self, state: &IBStream) -> Result<(), EInterface> {
+        self.controller.get_state(state)
+    }
+    unsafe fn get_parameter_count(&self) -> i32 {
+        self.controller.get_parameter_count()
+    }
+    unsafe fn get_parameter_info(&self, param_index: i32) -> Result<ParameterInfo, EInterface> {
+        self.controller.get_parameter_info(param_index)
+    }
+    unsafe fn get_param_string_by_value(
+        &self,
+        id: ParamID,
+        value_normalized: ParamValue,
+    ) -> Result<String128, EInterface> {
+        self.controller
+            .get_param_string_by_value(id, value_normalized)
+    }
+    unsafe fn get_param_value_by_string(
+        &self,
+        id: ParamID,
+        string: *const TChar,
+    ) -> Result<ParamValue, EInterface> {
+        self.controller.get_param_value_by_string(id, string)
+    }
+    unsafe fn normalized_param_to_plain(
+        &self,
+        id: ParamID,
+        value_normalized: ParamValue,
+    ) -> ParamValue {
+        self.controller
+            .normalized_param_to_plain(id, value_normalized)
+    }
+    unsafe fn plain_param_to_normalized(&self, id: ParamID, plain_value: ParamValue) -> ParamValue {
+        self.controller.plain_param_to_normalized(id, plain_value)
+    }
+    unsafe fn get_param_normalized(&self, id: ParamID) -> ParamValue {
+        self.controller.get_param_normalized(id)
+    }
+    unsafe fn set_param_normalized(
+        &self,
+        id: ParamID,
+        value: ParamValue,
+    ) -> Result<(), EInterface> {
+        self.controller.set_param_normalized(id, value)
+    }
+    unsafe fn set_component_handler(
+        &self,
+        handler: Option<&IComponentHandler>,
+    ) -> Result<(), EInterface> {
+        self.controller.set_component_handler(handler)
+    }
+    unsafe fn create_view(&self, name: &CStr) -> Option<FUnknown> {
+        self.controller.create_view(name)
+    }
+}
+impl IUnitInfoImpl for EditControllerEx1 {
+    unsafe fn get_unit_count(&self) -> i32 {
+        self.units.len() as i32
+    }
+    unsafe fn get_unit_info(&self, unit_index: i32) -> Result<UnitInfo, EInterface> {
+        let index = usize::try_from(unit_index).map_err(|_| EInterface::InvalidArgument)?;
+        self.units
+            .get(index)
+            .copied()
+            .ok_or(EInterface::InvalidArgument)
+    }
+    unsafe fn get_program_list_count(&self) -> i32 {
+        self.program_lists.len() as i32
+    }
+    unsafe fn get_program_list_info(&self, list_index: i32) -> Result<ProgramListInfo, EInterface> {
+        let index = usize::try_from(list_index).map_err(|_| EInterface::InvalidArgument)?;
+        self.program_lists
+            .get(index)
+            .map(|list| *list.info())
+            .ok_or(EInterface::InvalidArgument)
+    }
+    unsafe fn get_program_name(
+        &self,
+        list_id: ProgramListID,
+        program_index: i32,
+    ) -> Result<String128, EInterface> {
+        let list = self
+            .get_program_list(list_id)
+            .ok_or(EInterface::InvalidArgument)?;
+        let index = usize::try_from(program_index).map_err(|_| EInterface::InvalidArgument)?;
+        let name = list
+            .program_name(index)
+            .ok_or(EInterface::InvalidArgument)?;
+        Ok(string128_from_str(&name))
+    }
+    unsafe fn get_program_info(
+        &self,
+        list_id: ProgramListID,
+        program_index: i32,
+        attribute_id: &CStr,
+    ) -> Result<String128, EInterface> {
+        let list = self
+            .get_program_list(list_id)
+            .ok_or(EInterface::InvalidArgument)?;
+        let index = usize::try_from(program_index).map_err(|_| EInterface::InvalidArgument)?;
+        let attribute = attribute_id
+            .to_str()
+            .map_err(|_| EInterface::InvalidArgument)?;
+        let value = list
+            .program_info(index, attribute)
+            .ok_or(EInterface::ResultFalse)?;
+        Ok(string128_from_str(value))
+    }
+    unsafe fn has_program_pitch_names(
+        &self,
+        list_id: ProgramListID,
+        program_index: i32,
+    ) -> Result<(), EInterface> {
+        let list = self
+            .get_program_list(list_id)
+            .ok_or(EInterface::InvalidArgument)?;
+        let index = usize::try_from(program_index).map_err(|_| EInterface::InvalidArgument)?;
+        if !list.has_pitch_names(index) {
+            return Err(EInterface::ResultFalse);
+        }
+        Ok(())
+    }
+    unsafe fn get_program_pitch_name(
+        &self,
+        list_id: ProgramListID,
+        program_index: i32,
+        midi_pitch: i16,
+    ) -> Result<String128, EInterface> {
+        let list = self
+            .get_program_list(list_id)
+            .ok_or(EInterface::InvalidArgument)?;
+        let index = usize::try_from(program_index).map_err(|_| EInterface::InvalidArgument)?;
+        let name = list
+            .pitch_name(index, midi_pitch)
+            .ok_or(EInterface::ResultFalse)?;
+        Ok(string128_from_str(name))
+    }
+    unsafe fn get_selected_unit(&self) -> UnitID {
+        self.selected_unit.get()
+    }
+    unsafe fn select_unit(&self, unit_id: UnitID) -> Result<(), EInterface> {
+        if self.get_unit(unit_id).is_none() {
+            return Err(EInterface::InvalidArgument);
+        }
+        self.selected_unit.set(unit_id);
+        Ok(())
+    }
+    unsafe fn get_unit_by_bus(
+        &self,
+        type_: MediaType,
+        dir: BusDirection,
+        bus_index: i32,
+        channel: i32,
+    ) -> Result<UnitID, EInterface> {
+        self.unit_by_bus
+            .get(&(type_, dir, bus_index, Some(channel)))
+            .or_else(|| self.unit_by_bus.get(&(type_, dir, bus_index, None)))
+            .copied()
+            .ok_or(EInterface::ResultFalse)
+    }
+    unsafe fn set_unit_program_data(
+        &self,
+        _list_or_unit_id: i32,
+        _program_index: i32,
+        _data: &IBStream,
+    ) -> Result<(), EInterface> {
+        Err(EInterface::NotImplemented)
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugininterfaces::vst::{
+        ivstcomponent::{BusDirections, MediaTypes},
+        vsttypes::string128_to_string,
+    };
+    use crate::publicsdk::vst::vstparameters::BoolParameter;
+
+    #[test]
+    fn parameters() {
+        let mut parameters = ParameterContainer::new();
+        parameters
+            .add_parameter(RangeParameter::new(1, "Gain", "dB", -60.0, 12.0, 0.0))
+            .unwrap();
+        parameters
+            .add_parameter(BoolParameter::new(2, "Bypass", false))
+            .unwrap();
+        assert!(parameters
+            .add_parameter(BoolParameter::new(2, "Mute", false))
+            .is_err());
+        let controller = EditController::new(parameters);
+
+        unsafe {
+            assert_eq!(controller.get_parameter_count(), 2);
+            let info = controller.get_parameter_info(0).unwrap();
+            assert_eq!((info.id, info.title()), (1, "Gain".to_owned()));
+            assert!(info.has_flag(ParameterFlags::CanAutomate));
+            assert_eq!(controller.get_parameter_info(1).unwrap().id, 2);
+            assert!(controller.get_parameter_info(2).is_err());
+
+            assert_eq!(controller.get_param_normalized(1), 60.0 / 72.0);
+            controller.set_param_normalized(1, 0.25).unwrap();
+            assert_eq!(controller.get_param_normalized(1), 0.25);
+            assert_eq!(controller.normalized_param_to_plain(1, 0.25), -42.0);
+            controller.set_param_normalized(2, 1.0).unwrap();
+            assert_eq!(controller.get_param_normalized(2), 1.0);
+            assert!(controller.set_param_normalized(3, 1.0).is_err());
+        }
+    }
+
+    #[test]
+    fn units_and_program_lists() {
+        let mut controller = EditControllerEx1::default();
+        controller
+            .add_unit(UnitInfo::new(kRootUnitId, kNoParentUnitId, "Root"))
+            .unwrap();
+        controller
+            .add_unit(UnitInfo::new(1, kRootUnitId, "Part 1"))
+            .unwrap();
+        assert!(controller.add_unit(UnitInfo::new(2, 7, "Orphan")).is_err());
+        let mut drums =
+            ProgramList::new(10, "Kits", 1, 100).with_programs(["Rock", "Jazz", "Electro"]);
+        drums.set_pitch_name(1, 38, "Brush Snare");
+        controller.add_program_list(drums).unwrap();
+        assert!(controller
+            .add_program_list(ProgramList::new(11, "Empty", 1, 101))
+            .is_err());
+        controller.set_program_info(10, 2, "MediaType", "VstPreset");
+        controller.set_unit_by_bus(
+            MediaTypes::Event as i32,
+            BusDirections::Input as i32,
+            0,
+            None,
+            1,
+        );
+
+        unsafe {
+            assert_eq!(controller.get_unit_count(), 2);
+            assert_eq!(controller.get_unit_info(1).unwrap().program_list_id, 10);
+            assert_eq!(
+                controller.get_program_list_info(0).unwrap().program_count,
+                3
+            );
+            assert!(controller.has_program_pitch_names(10, 1).is_ok());
+            assert!(controller.has_program_pitch_names(10, 0).is_err());
+            let name = controller.get_program_pitch_name(10, 1, 38).unwrap();
+            assert_eq!(string128_to_string(&name), "Brush Snare");
+            assert!(controller.get_program_info(10, 2, c"MediaType").is_ok());
+            assert_eq!(
+                controller.get_unit_by_bus(
+                    MediaTypes::Event as i32,
+                    BusDirections::Input as i32,
+                    0,
+                    5
+                ),
+                Ok(1)
+            );
+            assert!(controller.select_unit(3).is_err());
+
+            // The program change parameter follows renames
+            let info = controller.get_parameter_info(0).unwrap();
+            assert!(info.has_flag(ParameterFlags::IsProgramChange));
+            assert_eq!(info.step_count, 2);
+            controller.set_param_normalized(100, 1.0).unwrap();
+        }
+        assert_eq!(controller.selected_program(10), Some(2));
+        assert!(controller.set_program_name(10, 2, "Techno"));
+        let parameter = controller.get_parameter(100).unwrap();
+        assert_eq!(parameter.value_to_string(1.0), "Techno");
+        assert_eq!(parameter.value_from_string("Jazz"), Some(0.5));
+    }
+}
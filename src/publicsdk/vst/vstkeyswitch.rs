@@ -0,0 +1,126 @@
+//! Key switches of a plug-in, declared per event bus and channel
+use crate::plugininterfaces::{
+    base::EInterface,
+    vst::ivstnoteexpression::{IKeyswitchControllerImpl, KeyswitchInfo, KeyswitchTypeID},
+};
+
+#[derive(Copy, Clone)]
+struct Entry {
+    bus_index: i32,
+    /// `None` if the key switch applies to all channels of the bus
+    channel: Option<i16>,
+    info: KeyswitchInfo,
+}
+impl Entry {
+    fn applies_to(&self, bus_index: i32, channel: i16) -> bool {
+        self.bus_index == bus_index && self.channel.is_none_or(|own| own == channel)
+    }
+}
+
+/// Table of the key switches and key ranges of a plug-in
+///
+/// Implements [`IKeyswitchControllerImpl`], so an edit controller can forward
+/// the interface to it. Key switches are reported in the order they were added.
+#[derive(Default)]
+pub struct KeyswitchContainer {
+    entries: Vec<Entry>,
+}
+impl KeyswitchContainer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds a key switch to a channel of an event bus, or to all its channels if `channel` is `None`
+    pub fn add_keyswitch(&mut self, bus_index: i32, channel: Option<i16>, info: KeyswitchInfo) {
+        self.entries.push(Entry {
+            bus_index,
+            channel,
+            info,
+        });
+    }
+    /// Adds a range of keys `min..=max` of type `type_id`, returns its info for further changes
+    pub fn add_range(
+        &mut self,
+        bus_index: i32,
+        channel: Option<i16>,
+        type_id: KeyswitchTypeID,
+        title: &str,
+        min: i32,
+        max: i32,
+    ) -> &mut KeyswitchInfo {
+        self.add_keyswitch(
+            bus_index,
+            channel,
+            KeyswitchInfo::new(type_id, title, min.min(max), max.max(min)),
+        );
+        &mut self.entries.last_mut().unwrap().info
+    }
+    /// Key switches of a channel of an event bus
+    pub fn keyswitches(
+        &self,
+        bus_index: i32,
+        channel: i16,
+    ) -> impl Iterator<Item = &KeyswitchInfo> + '_ {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.applies_to(bus_index, channel))
+            .map(|entry| &entry.info)
+    }
+    /// First key switch of a channel of an event bus whose range contains `pitch`
+    pub fn find(&self, bus_index: i32, channel: i16, pitch: i32) -> Option<&KeyswitchInfo> {
+        self.keyswitches(bus_index, channel)
+            .find(|info| info.contains(pitch))
+    }
+    pub fn remove_all(&mut self) {
+        self.entries.clear();
+    }
+}
+impl IKeyswitchControllerImpl for KeyswitchContainer {
+    unsafe fn get_keyswitch_count(&self, bus_index: i32, channel: i16) -> i32 {
+        self.keyswitches(bus_index, channel).count() as i32
+    }
+    unsafe fn get_keyswitch_info(
+        &self,
+        bus_index: i32,
+        channel: i16,
+        keyswitch_index: i32,
+    ) -> Result<KeyswitchInfo, EInterface> {
+        let index = usize::try_from(keyswitch_index).map_err(|_| EInterface::InvalidArgument)?;
+        self.keyswitches(bus_index, channel)
+            .nth(index)
+            .copied()
+            .ok_or(EInterface::InvalidArgument)
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugininterfaces::vst::ivstnoteexpression::{
+        kKeyRangeTypeID, kNoteOnKeyswitchTypeID,
+    };
+
+    #[test]
+    fn ranges_per_bus_and_channel() {
+        let mut keyswitches = KeyswitchContainer::new();
+        keyswitches
+            .add_range(0, None, kNoteOnKeyswitchTypeID, "Legato", 24, 24)
+            .set_short_title("Leg");
+        keyswitches.add_range(0, None, kNoteOnKeyswitchTypeID, "Staccato", 25, 25);
+        keyswitches.add_range(0, Some(1), kKeyRangeTypeID, "Playable", 96, 36);
+
+        assert_eq!(keyswitches.find(0, 0, 25).unwrap().title(), "Staccato");
+        assert!(keyswitches.find(0, 0, 60).is_none());
+        assert_eq!(keyswitches.find(0, 1, 60).unwrap().keyswitch_max, 96);
+        unsafe {
+            assert_eq!(keyswitches.get_keyswitch_count(0, 0), 2);
+            assert_eq!(keyswitches.get_keyswitch_count(0, 1), 3);
+            assert_eq!(keyswitches.get_keyswitch_count(1, 0), 0);
+            let info = keyswitches.get_keyswitch_info(0, 1, 0).unwrap();
+            assert_eq!(info.short_title(), "Leg");
+            assert_eq!(info.key_remapped, -1);
+            assert!(keyswitches.get_keyswitch_info(0, 0, 2).is_err());
+        }
+    }
+}
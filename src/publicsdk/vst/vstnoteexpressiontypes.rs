@@ -0,0 +1,399 @@
+//! Note expression types of a plug-in, see `public.sdk/source/vst/vstnoteexpressiontypes.h`
+use crate::plugininterfaces::{
+    base::{ustring::string_from_utf16_ptr, EInterface},
+    vst::{
+        ivsteditcontroller::{from_normalized, to_normalized},
+        ivstnoteexpression::{
+            kInvalidTypeID, INoteExpressionControllerImpl, INoteExpressionPhysicalUIMappingImpl,
+            IVst3WrapperMPESupportImpl, NoteExpressionTypeFlags, NoteExpressionTypeID,
+            NoteExpressionTypeInfo, NoteExpressionValue, PhysicalUIMap, PhysicalUIMapList,
+            PhysicalUITypeID,
+        },
+        vsttypes::{string128_from_str, ParamID, String128, TChar, UnitID},
+    },
+};
+use std::{cell::Cell, collections::HashMap};
+
+/// Note expression type with an optional plain range of `min..=max`
+#[derive(Clone)]
+pub struct NoteExpressionType {
+    info: NoteExpressionTypeInfo,
+    min_plain: NoteExpressionValue,
+    max_plain: NoteExpressionValue,
+    precision: usize,
+}
+impl NoteExpressionType {
+    /// Type whose plain value is its normalized value
+    pub fn new(type_id: NoteExpressionTypeID, title: &str, units: &str) -> Self {
+        Self {
+            info: NoteExpressionTypeInfo::new(type_id, title, units),
+            min_plain: 0.0,
+            max_plain: 1.0,
+            precision: 4,
+        }
+    }
+    /// Type displayed with a plain range of `min..=max`
+    pub fn with_range(
+        mut self,
+        min_plain: NoteExpressionValue,
+        max_plain: NoteExpressionValue,
+    ) -> Self {
+        self.min_plain = min_plain;
+        self.max_plain = max_plain;
+        self
+    }
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.info.flags = flags;
+        self
+    }
+    pub fn with_short_title(mut self, short_title: &str) -> Self {
+        self.info.set_short_title(short_title);
+        self
+    }
+    pub fn with_unit_id(mut self, unit_id: UnitID) -> Self {
+        self.info.unit_id = unit_id;
+        self
+    }
+    pub fn with_default_normalized(mut self, value_normalized: NoteExpressionValue) -> Self {
+        self.info.value_desc.default_value = value_normalized.clamp(0.0, 1.0);
+        self
+    }
+    /// Number of steps between `min` and `max`, 0 for a continuous type
+    pub fn with_step_count(mut self, step_count: i32) -> Self {
+        self.info.value_desc.step_count = step_count;
+        self
+    }
+    /// Applies the note expression to the parameter `id`
+    pub fn with_associated_parameter(mut self, id: ParamID) -> Self {
+        self.info.associated_parameter_id = id;
+        self.info.flags |= NoteExpressionTypeFlags::AssociatedParameterIDValid as i32;
+        self
+    }
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+    pub fn info(&self) -> &NoteExpressionTypeInfo {
+        &self.info
+    }
+    pub fn type_id(&self) -> NoteExpressionTypeID {
+        self.info.type_id
+    }
+    /// Converts a normalized value to its plain representation
+    pub fn to_plain(&self, value_normalized: NoteExpressionValue) -> NoteExpressionValue {
+        let step_count = self.info.value_desc.step_count;
+        if step_count > 0 {
+            return from_normalized(value_normalized, step_count) as f64 + self.min_plain;
+        }
+        value_normalized * (self.max_plain - self.min_plain) + self.min_plain
+    }
+    /// Converts a plain value to its normalized representation
+    pub fn to_normalized(&self, plain_value: NoteExpressionValue) -> NoteExpressionValue {
+        let step_count = self.info.value_desc.step_count;
+        if step_count > 0 {
+            return to_normalized((plain_value - self.min_plain).round(), step_count);
+        }
+        if self.max_plain == self.min_plain {
+            return 0.0;
+        }
+        (plain_value - self.min_plain) / (self.max_plain - self.min_plain)
+    }
+    /// String representation of a normalized value
+    pub fn value_to_string(&self, value_normalized: NoteExpressionValue) -> String {
+        let plain_value = self.to_plain(value_normalized);
+        if self.info.value_desc.step_count > 0 {
+            format!("{}", plain_value as i64)
+        } else {
+            format!("{:.*}", self.precision, plain_value)
+        }
+    }
+    /// Normalized value of a string, ignores the units suffix if present
+    pub fn value_from_string(&self, string: &str) -> Option<NoteExpressionValue> {
+        let string = string.trim();
+        let units = self.info.units();
+        let plain_value = string
+            .strip_suffix(units.as_str())
+            .unwrap_or(string)
+            .trim()
+            .parse()
+            .ok()?;
+        Some(self.to_normalized(plain_value).clamp(0.0, 1.0))
+    }
+}
+
+/// MPE zone set by a wrapper through [`IVst3WrapperMPESupportImpl`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MpeInputDeviceSettings {
+    pub master_channel: i32,
+    pub member_begin_channel: i32,
+    pub member_end_channel: i32,
+}
+impl Default for MpeInputDeviceSettings {
+    /// Lower zone: master channel 0, members on channels 1 to 14
+    fn default() -> Self {
+        Self {
+            master_channel: 0,
+            member_begin_channel: 1,
+            member_end_channel: 14,
+        }
+    }
+}
+
+/// Table of the note expression types and physical UI mappings of a plug-in
+///
+/// The same table is reported for every event bus and channel. Implements
+/// [`INoteExpressionControllerImpl`], [`INoteExpressionPhysicalUIMappingImpl`]
+/// and [`IVst3WrapperMPESupportImpl`], so an edit controller can forward these
+/// interfaces to it.
+#[derive(Default)]
+pub struct NoteExpressionTypeContainer {
+    types: Vec<NoteExpressionType>,
+    id_to_index: HashMap<NoteExpressionTypeID, usize>,
+    physical_ui_mapping: Vec<PhysicalUIMap>,
+    mpe_enabled: Cell<bool>,
+    mpe_settings: Cell<MpeInputDeviceSettings>,
+}
+impl NoteExpressionTypeContainer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds a type, fails with [`EInterface::InvalidArgument`] if its ID is already in use
+    pub fn add_note_expression_type(
+        &mut self,
+        note_expression_type: NoteExpressionType,
+    ) -> Result<&NoteExpressionType, EInterface> {
+        let type_id = note_expression_type.type_id();
+        if type_id == kInvalidTypeID || self.id_to_index.contains_key(&type_id) {
+            return Err(EInterface::InvalidArgument);
+        }
+        self.id_to_index.insert(type_id, self.types.len());
+        self.types.push(note_expression_type);
+        Ok(self.types.last().unwrap())
+    }
+    /// Maps the physical UI type `physical_ui_type_id` to the note expression type `type_id`,
+    /// replacing a previous mapping
+    pub fn map_physical_ui(
+        &mut self,
+        physical_ui_type_id: PhysicalUITypeID,
+        type_id: NoteExpressionTypeID,
+    ) {
+        let map = PhysicalUIMap {
+            physical_ui_type_id,
+            note_expression_type_id: type_id,
+        };
+        match self
+            .physical_ui_mapping
+            .iter_mut()
+            .find(|entry| entry.physical_ui_type_id == physical_ui_type_id)
+        {
+            Some(entry) => *entry = map,
+            None => self.physical_ui_mapping.push(map),
+        }
+    }
+    pub fn get_note_expression_type(
+        &self,
+        type_id: NoteExpressionTypeID,
+    ) -> Option<&NoteExpressionType> {
+        self.types.get(*self.id_to_index.get(&type_id)?)
+    }
+    pub fn get_note_expression_type_by_index(&self, index: usize) -> Option<&NoteExpressionType> {
+        self.types.get(index)
+    }
+    pub fn get_note_expression_type_count(&self) -> usize {
+        self.types.len()
+    }
+    /// Note expression type mapped to `physical_ui_type_id`, if any
+    pub fn physical_ui_mapping(
+        &self,
+        physical_ui_type_id: PhysicalUITypeID,
+    ) -> Option<NoteExpressionTypeID> {
+        self.physical_ui_mapping
+            .iter()
+            .find(|entry| entry.physical_ui_type_id == physical_ui_type_id)
+            .map(|entry| entry.note_expression_type_id)
+    }
+    pub fn remove_all(&mut self) {
+        self.types.clear();
+        self.id_to_index.clear();
+        self.physical_ui_mapping.clear();
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &NoteExpressionType> {
+        self.types.iter()
+    }
+    /// Whether a wrapper enabled its MPE input processing
+    pub fn mpe_enabled(&self) -> bool {
+        self.mpe_enabled.get()
+    }
+    pub fn mpe_settings(&self) -> MpeInputDeviceSettings {
+        self.mpe_settings.get()
+    }
+}
+impl INoteExpressionControllerImpl for NoteExpressionTypeContainer {
+    unsafe fn get_note_expression_count(&self, _bus_index: i32, _channel: i16) -> i32 {
+        self.types.len() as i32
+    }
+    unsafe fn get_note_expression_info(
+        &self,
+        _bus_index: i32,
+        _channel: i16,
+        note_expression_index: i32,
+    ) -> Result<NoteExpressionTypeInfo, EInterface> {
+        let index =
+            usize::try_from(note_expression_index).map_err(|_| EInterface::InvalidArgument)?;
+        self.get_note_expression_type_by_index(index)
+            .map(|note_expression_type| *note_expression_type.info())
+            .ok_or(EInterface::InvalidArgument)
+    }
+    unsafe fn get_note_expression_string_by_value(
+        &self,
+        _bus_index: i32,
+        _channel: i16,
+        id: NoteExpressionTypeID,
+        value_normalized: NoteExpressionValue,
+    ) -> Result<String128, EInterface> {
+        let note_expression_type = self
+            .get_note_expression_type(id)
+            .ok_or(EInterface::ResultFalse)?;
+        Ok(string128_from_str(
+            &note_expression_type.value_to_string(value_normalized),
+        ))
+    }
+    unsafe fn get_note_expression_value_by_string(
+        &self,
+        _bus_index: i32,
+        _channel: i16,
+        id: NoteExpressionTypeID,
+        string: *const TChar,
+    ) -> Result<NoteExpressionValue, EInterface> {
+        let note_expression_type = self
+            .get_note_expression_type(id)
+            .ok_or(EInterface::ResultFalse)?;
+        note_expression_type
+            .value_from_string(&string_from_utf16_ptr(string))
+            .ok_or(EInterface::ResultFalse)
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl INoteExpressionPhysicalUIMappingImpl for NoteExpressionTypeContainer {
+    unsafe fn get_physical_ui_mapping(
+        &self,
+        _bus_index: i32,
+        _channel: i16,
+        list: *mut PhysicalUIMapList,
+    ) -> Result<(), EInterface> {
+        let list = list.as_mut().ok_or(EInterface::Pointer)?;
+        for entry in list.as_mut_slice() {
+            entry.note_expression_type_id = self
+                .physical_ui_mapping(entry.physical_ui_type_id)
+                .unwrap_or(kInvalidTypeID);
+        }
+        Ok(())
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+impl IVst3WrapperMPESupportImpl for NoteExpressionTypeContainer {
+    unsafe fn enable_mpe_input_processing(&self, state: bool) -> Result<(), EInterface> {
+        self.mpe_enabled.set(state);
+        Ok(())
+    }
+    unsafe fn set_mpe_input_device_settings(
+        &self,
+        master_channel: i32,
+        member_begin_channel: i32,
+        member_end_channel: i32,
+    ) -> Result<(), EInterface> {
+        if !(0..16).contains(&master_channel)
+            || !(0..16).contains(&member_begin_channel)
+            || !(member_begin_channel..16).contains(&member_end_channel)
+        {
+            return Err(EInterface::InvalidArgument);
+        }
+        self.mpe_settings.set(MpeInputDeviceSettings {
+            master_channel,
+            member_begin_channel,
+            member_end_channel,
+        });
+        Ok(())
+    }
+
+    const INNER_OFFSET_IN_POINTERS: usize = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugininterfaces::{
+        base::ustring::to_utf16_vec,
+        vst::{
+            ivstnoteexpression::{
+                kPUIPressure, kPUIXMovement, kPUIYMovement, kTuningTypeID, kVolumeTypeID,
+            },
+            vsttypes::string128_to_string,
+        },
+    };
+
+    #[test]
+    fn table_answers_controller_queries() {
+        let mut container = NoteExpressionTypeContainer::new();
+        container
+            .add_note_expression_type(
+                NoteExpressionType::new(kTuningTypeID, "Tuning", "Semitones")
+                    .with_range(-120.0, 120.0)
+                    .with_default_normalized(0.5)
+                    .with_flags(NoteExpressionTypeFlags::IsBipolar as i32)
+                    .with_precision(1),
+            )
+            .unwrap();
+        container
+            .add_note_expression_type(NoteExpressionType::new(kVolumeTypeID, "Volume", ""))
+            .unwrap();
+        assert!(container
+            .add_note_expression_type(NoteExpressionType::new(kVolumeTypeID, "Gain", ""))
+            .is_err());
+        container.map_physical_ui(kPUIXMovement, kTuningTypeID);
+        container.map_physical_ui(kPUIPressure, kVolumeTypeID);
+
+        unsafe {
+            assert_eq!(container.get_note_expression_count(0, 3), 2);
+            let info = container.get_note_expression_info(0, 3, 0).unwrap();
+            assert_eq!(info.title(), "Tuning");
+            assert!(info.has_flag(NoteExpressionTypeFlags::IsBipolar));
+            assert_eq!(info.value_desc.default_value, 0.5);
+            assert!(container.get_note_expression_info(0, 3, 2).is_err());
+
+            let string = container
+                .get_note_expression_string_by_value(0, 0, kTuningTypeID, 0.75)
+                .unwrap();
+            assert_eq!(string128_to_string(&string), "60.0");
+            let text = to_utf16_vec("-120 Semitones");
+            assert_eq!(
+                container.get_note_expression_value_by_string(0, 0, kTuningTypeID, text.as_ptr()),
+                Ok(0.0)
+            );
+
+            let mut map = [
+                PhysicalUIMap::new(kPUIXMovement),
+                PhysicalUIMap::new(kPUIYMovement),
+                PhysicalUIMap::new(kPUIPressure),
+            ];
+            let mut list = PhysicalUIMapList {
+                count: map.len() as u32,
+                map: map.as_mut_ptr(),
+            };
+            container.get_physical_ui_mapping(0, 0, &mut list).unwrap();
+            let mapped: Vec<_> = map
+                .iter()
+                .map(|entry| entry.note_expression_type_id)
+                .collect();
+            assert_eq!(mapped, [kTuningTypeID, kInvalidTypeID, kVolumeTypeID]);
+
+            container.enable_mpe_input_processing(true).unwrap();
+            assert!(container.set_mpe_input_device_settings(15, 14, 1).is_err());
+            container.set_mpe_input_device_settings(15, 1, 14).unwrap();
+        }
+        assert!(container.mpe_enabled());
+        assert_eq!(container.mpe_settings().master_channel, 15);
+    }
+}
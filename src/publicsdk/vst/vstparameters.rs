@@ -0,0 +1,492 @@
+//! Runtime parameter model, see `public.sdk/source/vst/vstparameters.h`
+use crate::plugininterfaces::{
+    base::EInterface,
+    vst::{
+        ivsteditcontroller::{from_normalized, to_normalized, ParameterFlags, ParameterInfo},
+        vsttypes::{ParamID, ParamValue, UnitID},
+    },
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Custom conversion of a plain value to its string representation
+pub type ValueFormatter = Box<dyn Fn(ParamValue) -> String + Send + Sync>;
+/// Custom conversion of a string to a plain value
+pub type ValueParser = Box<dyn Fn(&str) -> Option<ParamValue> + Send + Sync>;
+
+/// A parameter exported by an edit controller
+///
+/// Implementors only have to provide access to their [`BasicParameter`],
+/// which holds the info and current normalized value, and override the
+/// plain value conversions and formatting they need.
+pub trait Parameter: Send + Sync {
+    fn base(&self) -> &BasicParameter;
+
+    fn base_mut(&mut self) -> &mut BasicParameter;
+
+    fn info(&self) -> &ParameterInfo {
+        &self.base().info
+    }
+    fn id(&self) -> ParamID {
+        self.info().id
+    }
+    /// Current normalized value
+    fn normalized(&self) -> ParamValue {
+        f64::from_bits(self.base().value_normalized.load(Ordering::Relaxed))
+    }
+    /// Sets the normalized value, clamped to [0, 1].
+    /// Returns true if the value changed.
+    fn set_normalized(&self, value: ParamValue) -> bool {
+        let value = value.clamp(0.0, 1.0);
+        let previous = self
+            .base()
+            .value_normalized
+            .swap(value.to_bits(), Ordering::Relaxed);
+        f64::from_bits(previous) != value
+    }
+    /// Number of digits after the decimal point used by the default formatting
+    fn precision(&self) -> usize {
+        self.base().precision
+    }
+    /// Converts a normalized value to its plain representation
+    fn to_plain(&self, value_normalized: ParamValue) -> ParamValue {
+        value_normalized
+    }
+    /// Converts a plain value to its normalized representation
+    fn to_normalized(&self, plain_value: ParamValue) -> ParamValue {
+        plain_value
+    }
+    /// Default string representation of a plain value
+    fn format_plain(&self, plain_value: ParamValue) -> String {
+        format!("{:.*}", self.precision(), plain_value)
+    }
+    /// Default parsing of a plain value, ignores the units suffix if present
+    fn parse_plain(&self, string: &str) -> Option<ParamValue> {
+        let string = string.trim();
+        let units = self.info().units();
+        string
+            .strip_suffix(units.as_str())
+            .unwrap_or(string)
+            .trim()
+            .parse()
+            .ok()
+    }
+    /// String representation of a normalized value, using the custom formatter if one is set
+    fn value_to_string(&self, value_normalized: ParamValue) -> String {
+        let plain_value = self.to_plain(value_normalized);
+        match &self.base().formatter {
+            Some(formatter) => formatter(plain_value),
+            None => self.format_plain(plain_value),
+        }
+    }
+    /// Normalized value of a string, using the custom parser if one is set
+    fn value_from_string(&self, string: &str) -> Option<ParamValue> {
+        let plain_value = match &self.base().parser {
+            Some(parser) => parser(string),
+            None => self.parse_plain(string),
+        }?;
+        Some(self.to_normalized(plain_value).clamp(0.0, 1.0))
+    }
+
+    fn with_flags(mut self, flags: i32) -> Self
+    where
+        Self: Sized,
+    {
+        self.base_mut().info.flags = flags;
+        self
+    }
+    fn with_unit_id(mut self, unit_id: UnitID) -> Self
+    where
+        Self: Sized,
+    {
+        self.base_mut().info.unit_id = unit_id;
+        self
+    }
+    fn with_short_title(mut self, short_title: &str) -> Self
+    where
+        Self: Sized,
+    {
+        self.base_mut().info.set_short_title(short_title);
+        self
+    }
+    /// Sets the default normalized value and resets the current value to it
+    fn with_default_normalized(mut self, value_normalized: ParamValue) -> Self
+    where
+        Self: Sized,
+    {
+        let value_normalized = value_normalized.clamp(0.0, 1.0);
+        self.base_mut().info.default_normalized_value = value_normalized;
+        self.set_normalized(value_normalized);
+        self
+    }
+    fn with_precision(mut self, precision: usize) -> Self
+    where
+        Self: Sized,
+    {
+        self.base_mut().precision = precision;
+        self
+    }
+    /// Replaces the default formatting, `formatter` receives plain values
+    fn with_formatter(
+        mut self,
+        formatter: impl Fn(ParamValue) -> String + Send + Sync + 'static,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        self.base_mut().formatter = Some(Box::new(formatter));
+        self
+    }
+    /// Replaces the default parsing, `parser` returns plain values
+    fn with_parser(
+        mut self,
+        parser: impl Fn(&str) -> Option<ParamValue> + Send + Sync + 'static,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        self.base_mut().parser = Some(Box::new(parser));
+        self
+    }
+}
+
+/// Parameter whose plain value is its normalized value
+pub struct BasicParameter {
+    info: ParameterInfo,
+    value_normalized: AtomicU64,
+    precision: usize,
+    formatter: Option<ValueFormatter>,
+    parser: Option<ValueParser>,
+}
+impl BasicParameter {
+    pub fn new(info: ParameterInfo) -> Self {
+        let value_normalized = AtomicU64::new(info.default_normalized_value.to_bits());
+        Self {
+            info,
+            value_normalized,
+            precision: 4,
+            formatter: None,
+            parser: None,
+        }
+    }
+}
+impl Parameter for BasicParameter {
+    fn base(&self) -> &BasicParameter {
+        self
+    }
+    fn base_mut(&mut self) -> &mut BasicParameter {
+        self
+    }
+    fn format_plain(&self, plain_value: ParamValue) -> String {
+        if self.info.step_count == 1 {
+            BoolParameter::format(plain_value)
+        } else {
+            format!("{:.*}", self.precision, plain_value)
+        }
+    }
+}
+impl fmt::Debug for BasicParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicParameter")
+            .field("id", &self.info.id)
+            .field("title", &self.info.title())
+            .field("normalized", &self.normalized())
+            .finish()
+    }
+}
+
+/// Parameter with a plain range of `min..=max`, continuous or stepped
+pub struct RangeParameter {
+    base: BasicParameter,
+    min_plain: ParamValue,
+    max_plain: ParamValue,
+}
+impl RangeParameter {
+    pub fn new(
+        id: ParamID,
+        title: &str,
+        units: &str,
+        min_plain: ParamValue,
+        max_plain: ParamValue,
+        default_plain: ParamValue,
+    ) -> Self {
+        let mut info = ParameterInfo::new(id, title, units);
+        info.flags = ParameterFlags::CanAutomate as i32;
+        let mut parameter = Self {
+            base: BasicParameter::new(info),
+            min_plain,
+            max_plain,
+        };
+        parameter.set_default_plain(default_plain);
+        parameter
+    }
+    /// Number of steps between `min` and `max`, 0 for a continuous parameter
+    pub fn with_step_count(mut self, step_count: i32) -> Self {
+        let default_plain = self.to_plain(self.info().default_normalized_value);
+        self.base.info.step_count = step_count;
+        self.set_default_plain(default_plain);
+        self
+    }
+    pub fn min(&self) -> ParamValue {
+        self.min_plain
+    }
+    pub fn max(&self) -> ParamValue {
+        self.max_plain
+    }
+    fn set_default_plain(&mut self, default_plain: ParamValue) {
+        let default_normalized = self.to_normalized(default_plain).clamp(0.0, 1.0);
+        self.base.info.default_normalized_value = default_normalized;
+        self.set_normalized(default_normalized);
+    }
+}
+impl Parameter for RangeParameter {
+    fn base(&self) -> &BasicParameter {
+        &self.base
+    }
+    fn base_mut(&mut self) -> &mut BasicParameter {
+        &mut self.base
+    }
+    fn to_plain(&self, value_normalized: ParamValue) -> ParamValue {
+        let step_count = self.info().step_count;
+        if step_count > 0 {
+            return from_normalized(value_normalized, step_count) as f64 + self.min_plain;
+        }
+        value_normalized * (self.max_plain - self.min_plain) + self.min_plain
+    }
+    fn to_normalized(&self, plain_value: ParamValue) -> ParamValue {
+        let step_count = self.info().step_count;
+        if step_count > 0 {
+            return to_normalized((plain_value - self.min_plain).round(), step_count);
+        }
+        if self.max_plain == self.min_plain {
+            return 0.0;
+        }
+        (plain_value - self.min_plain) / (self.max_plain - self.min_plain)
+    }
+    fn format_plain(&self, plain_value: ParamValue) -> String {
+        if self.info().step_count > 0 {
+            format!("{}", plain_value as i64)
+        } else {
+            format!("{:.*}", self.precision(), plain_value)
+        }
+    }
+}
+
+/// Stepped parameter displaying one string per step
+pub struct StringListParameter {
+    base: BasicParameter,
+    strings: Vec<String>,
+}
+impl StringListParameter {
+    pub fn new(id: ParamID, title: &str) -> Self {
+        let flags = ParameterFlags::CanAutomate | ParameterFlags::IsList;
+        Self {
+            base: BasicParameter::new(ParameterInfo::new(id, title, "")),
+            strings: Vec::new(),
+        }
+        .with_flags(flags)
+    }
+    /// Appends an entry and updates the step count
+    pub fn append_string(&mut self, string: &str) {
+        self.strings.push(string.to_owned());
+        self.base.info.step_count = self.strings.len() as i32 - 1;
+    }
+    pub fn with_strings<S: AsRef<str>>(mut self, strings: impl IntoIterator<Item = S>) -> Self {
+        for string in strings {
+            self.append_string(string.as_ref());
+        }
+        self
+    }
+    /// Replaces the entry at `index`, returns false if `index` is out of range
+    pub fn replace_string(&mut self, index: usize, string: &str) -> bool {
+        match self.strings.get_mut(index) {
+            Some(entry) => {
+                *entry = string.to_owned();
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn strings(&self) -> &[String] {
+        &self.strings
+    }
+    /// Index of the currently selected entry
+    pub fn selected_index(&self) -> usize {
+        self.to_plain(self.normalized()) as usize
+    }
+}
+impl Parameter for StringListParameter {
+    fn base(&self) -> &BasicParameter {
+        &self.base
+    }
+    fn base_mut(&mut self) -> &mut BasicParameter {
+        &mut self.base
+    }
+    fn to_plain(&self, value_normalized: ParamValue) -> ParamValue {
+        let step_count = self.info().step_count;
+        if step_count <= 0 {
+            return 0.0;
+        }
+        from_normalized(value_normalized, step_count) as f64
+    }
+    fn to_normalized(&self, plain_value: ParamValue) -> ParamValue {
+        let step_count = self.info().step_count;
+        if step_count <= 0 {
+            return 0.0;
+        }
+        to_normalized(plain_value.round(), step_count)
+    }
+    fn format_plain(&self, plain_value: ParamValue) -> String {
+        self.strings
+            .get(plain_value as usize)
+            .cloned()
+            .unwrap_or_default()
+    }
+    fn parse_plain(&self, string: &str) -> Option<ParamValue> {
+        let string = string.trim();
+        self.strings
+            .iter()
+            .position(|entry| entry == string)
+            .map(|index| index as f64)
+    }
+}
+
+/// On/off parameter with a single step
+pub struct BoolParameter {
+    base: BasicParameter,
+}
+impl BoolParameter {
+    pub fn new(id: ParamID, title: &str, default_value: bool) -> Self {
+        let mut info = ParameterInfo::new(id, title, "");
+        info.step_count = 1;
+        info.flags = ParameterFlags::CanAutomate as i32;
+        info.default_normalized_value = if default_value { 1.0 } else { 0.0 };
+        Self {
+            base: BasicParameter::new(info),
+        }
+    }
+    pub fn value(&self) -> bool {
+        self.normalized() > 0.5
+    }
+    fn format(plain_value: ParamValue) -> String {
+        if plain_value > 0.5 { "On" } else { "Off" }.to_owned()
+    }
+}
+impl Parameter for BoolParameter {
+    fn base(&self) -> &BasicParameter {
+        &self.base
+    }
+    fn base_mut(&mut self) -> &mut BasicParameter {
+        &mut self.base
+    }
+    fn to_plain(&self, value_normalized: ParamValue) -> ParamValue {
+        if value_normalized > 0.5 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+    fn format_plain(&self, plain_value: ParamValue) -> String {
+        BoolParameter::format(plain_value)
+    }
+    fn parse_plain(&self, string: &str) -> Option<ParamValue> {
+        match string.trim().to_ascii_lowercase().as_str() {
+            "on" | "true" | "1" => Some(1.0),
+            "off" | "false" | "0" => Some(0.0),
+            _ => None,
+        }
+    }
+}
+
+/// Collection of parameters, indexed both by position and by ID
+#[derive(Default)]
+pub struct ParameterContainer {
+    parameters: Vec<Box<dyn Parameter>>,
+    id_to_index: HashMap<ParamID, usize>,
+}
+impl ParameterContainer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds a parameter, fails with [`EInterface::InvalidArgument`] if its ID is already in use
+    pub fn add_parameter<P: Parameter + 'static>(
+        &mut self,
+        parameter: P,
+    ) -> Result<&dyn Parameter, EInterface> {
+        let id = parameter.id();
+        if self.id_to_index.contains_key(&id) {
+            return Err(EInterface::InvalidArgument);
+        }
+        self.id_to_index.insert(id, self.parameters.len());
+        self.parameters.push(Box::new(parameter));
+        Ok(self.parameters.last().unwrap().as_ref())
+    }
+    pub fn get_parameter(&self, id: ParamID) -> Option<&dyn Parameter> {
+        let index = *self.id_to_index.get(&id)?;
+        self.get_parameter_by_index(index)
+    }
+    pub fn get_parameter_by_index(&self, index: usize) -> Option<&dyn Parameter> {
+        self.parameters
+            .get(index)
+            .map(|parameter| parameter.as_ref())
+    }
+    pub fn get_parameter_count(&self) -> usize {
+        self.parameters.len()
+    }
+    pub fn remove_all(&mut self) {
+        self.parameters.clear();
+        self.id_to_index.clear();
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Parameter> {
+        self.parameters.iter().map(|parameter| parameter.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_parameter_conversions() {
+        let gain = RangeParameter::new(0, "Gain", "dB", -60.0, 12.0, 0.0).with_precision(1);
+        assert!(gain.info().has_flag(ParameterFlags::CanAutomate));
+        let normalized = gain.normalized();
+        assert!((gain.to_plain(normalized) - 0.0).abs() < 1e-9);
+        assert_eq!(gain.value_to_string(1.0), "12.0");
+        assert_eq!(gain.value_from_string("-60 dB"), Some(0.0));
+
+        let octave = RangeParameter::new(1, "Octave", "", -2.0, 2.0, 0.0).with_step_count(4);
+        assert_eq!(octave.normalized(), 0.5);
+        assert_eq!(octave.value_to_string(1.0), "2");
+        assert_eq!(octave.to_plain(0.3), -1.0);
+    }
+
+    #[test]
+    fn list_and_bool_parameters() {
+        let mode = StringListParameter::new(2, "Mode").with_strings(["Low", "Band", "High"]);
+        assert_eq!(mode.info().step_count, 2);
+        assert_eq!(mode.value_from_string("High"), Some(1.0));
+        assert_eq!(mode.value_to_string(0.5), "Band");
+
+        let bypass = BoolParameter::new(3, "Bypass", false)
+            .with_flags(ParameterFlags::CanAutomate | ParameterFlags::IsBypass);
+        assert!(bypass.set_normalized(1.0));
+        assert!(bypass.value());
+        assert_eq!(bypass.value_to_string(bypass.normalized()), "On");
+    }
+
+    #[test]
+    fn container_rejects_duplicate_ids() {
+        let mut container = ParameterContainer::new();
+        container
+            .add_parameter(BoolParameter::new(7, "Mute", false))
+            .unwrap();
+        assert!(container
+            .add_parameter(BoolParameter::new(7, "Solo", false))
+            .is_err());
+        assert_eq!(container.get_parameter(7).unwrap().info().title(), "Mute");
+    }
+}
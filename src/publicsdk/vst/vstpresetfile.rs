@@ -0,0 +1,627 @@
+//! VST 3 preset files, see `public.sdk/source/vst/vstpresetfile.h`
+//!
+//! A `.vstpreset` file starts with a header holding the class ID of the plug-in
+//! and the offset of a chunk list, followed by the chunk data and the list:
+//!
+//! ```text
+//! 'VST3'  version  class ID (32 ASCII hex digits)  offset of the chunk list
+//! chunk data ...
+//! 'List'  entry count  (id  offset  size) ...
+//! ```
+//!
+//! All numbers are little-endian, offsets are relative to the start of the file.
+#![allow(non_upper_case_globals)]
+
+use crate::{
+    plugininterfaces::{
+        base::{ibstream::IBStream, EInterface, FUID},
+        vst::{
+            ivstcomponent::IComponent, ivsteditcontroller::IEditController,
+            ivstunits::ProgramListID,
+        },
+    },
+    publicsdk::common::{
+        fstreamer::{StreamError, StreamReader, StreamWriter},
+        memorystream::MemoryStream,
+    },
+};
+use std::fmt;
+
+/// Identifier of a chunk
+pub type ChunkID = [u8; 4];
+
+/// Version of the preset format
+pub const kFormatVersion: i32 = 1;
+/// Size of the class ID in the header
+pub const kClassIDSize: usize = 32;
+/// Size of the header
+pub const kHeaderSize: i64 = 4 + 4 + kClassIDSize as i64 + 8;
+/// Size of an entry of the chunk list
+const kListEntrySize: i64 = 4 + 8 + 8;
+
+/// Chunks defined by the SDK
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChunkType {
+    /// File header, `VST3`
+    Header,
+    /// State of the component, `Comp`
+    ComponentState,
+    /// State of the edit controller, `Cont`
+    ControllerState,
+    /// Data of a program of a program list, `Prog`
+    ProgramData,
+    /// Meta info XML, `Info`
+    MetaInfo,
+    /// Chunk list, `List`
+    ChunkList,
+}
+impl ChunkType {
+    pub const fn id(self) -> ChunkID {
+        match self {
+            ChunkType::Header => *b"VST3",
+            ChunkType::ComponentState => *b"Comp",
+            ChunkType::ControllerState => *b"Cont",
+            ChunkType::ProgramData => *b"Prog",
+            ChunkType::MetaInfo => *b"Info",
+            ChunkType::ChunkList => *b"List",
+        }
+    }
+}
+
+/// Error while reading, writing or applying a preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresetError {
+    /// The stream doesn't start with a valid preset header
+    InvalidHeader,
+    /// The chunk list is missing or points outside the file
+    InvalidChunkList,
+    /// The preset doesn't contain a required chunk
+    MissingChunk(ChunkID),
+    /// The stream failed or ended early
+    Stream(StreamError),
+    /// The component or controller rejected a state
+    Interface(EInterface),
+}
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PresetError::InvalidHeader => write!(f, "Invalid preset file header"),
+            PresetError::InvalidChunkList => write!(f, "Invalid preset chunk list"),
+            PresetError::MissingChunk(id) => {
+                write!(f, "Missing preset chunk {}", String::from_utf8_lossy(id))
+            }
+            PresetError::Stream(error) => error.fmt(f),
+            PresetError::Interface(error) => error.fmt(f),
+        }
+    }
+}
+impl std::error::Error for PresetError {}
+impl From<StreamError> for PresetError {
+    fn from(error: StreamError) -> Self {
+        PresetError::Stream(error)
+    }
+}
+impl From<EInterface> for PresetError {
+    fn from(error: EInterface) -> Self {
+        PresetError::Interface(error)
+    }
+}
+
+/// Formats a class ID as 32 uppercase hex digits, as stored in preset files
+pub fn class_id_to_string(class_id: &FUID) -> String {
+    format!(
+        "{:08X}{:08X}{:08X}{:08X}",
+        class_id.get_long_1(),
+        class_id.get_long_2(),
+        class_id.get_long_3(),
+        class_id.get_long_4()
+    )
+}
+
+/// Parses 32 hex digits written by [`class_id_to_string`]
+pub fn class_id_from_string(string: &str) -> Option<FUID> {
+    if string.len() != kClassIDSize || !string.is_ascii() {
+        return None;
+    }
+    let mut longs = [0; 4];
+    for (long, digits) in longs.iter_mut().zip(string.as_bytes().chunks(8)) {
+        *long = u32::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+    Some(FUID::from(longs))
+}
+
+/// Chunk of a preset
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PresetChunk {
+    pub id: ChunkID,
+    pub data: Vec<u8>,
+}
+
+/// Content of a `.vstpreset` file
+///
+/// Chunks are kept in file order, unknown chunks are preserved when the preset is
+/// written again.
+#[derive(Clone)]
+pub struct VstPreset {
+    class_id: FUID,
+    chunks: Vec<PresetChunk>,
+}
+impl VstPreset {
+    /// Empty preset of the plug-in class `class_id`
+    pub fn new(class_id: FUID) -> Self {
+        Self {
+            class_id,
+            chunks: Vec::new(),
+        }
+    }
+    pub fn class_id(&self) -> FUID {
+        self.class_id
+    }
+    pub fn chunks(&self) -> &[PresetChunk] {
+        &self.chunks
+    }
+    pub fn chunk(&self, id: ChunkID) -> Option<&[u8]> {
+        self.chunks
+            .iter()
+            .find(|chunk| chunk.id == id)
+            .map(|chunk| chunk.data.as_slice())
+    }
+    /// Replaces the chunk `id`, or appends it if the preset doesn't contain it
+    pub fn set_chunk(&mut self, id: ChunkID, data: Vec<u8>) {
+        match self.chunks.iter_mut().find(|chunk| chunk.id == id) {
+            Some(chunk) => chunk.data = data,
+            None => self.chunks.push(PresetChunk { id, data }),
+        }
+    }
+    pub fn remove_chunk(&mut self, id: ChunkID) -> Option<Vec<u8>> {
+        let index = self.chunks.iter().position(|chunk| chunk.id == id)?;
+        Some(self.chunks.remove(index).data)
+    }
+    pub fn component_state(&self) -> Option<&[u8]> {
+        self.chunk(ChunkType::ComponentState.id())
+    }
+    pub fn set_component_state(&mut self, state: Vec<u8>) {
+        self.set_chunk(ChunkType::ComponentState.id(), state);
+    }
+    pub fn controller_state(&self) -> Option<&[u8]> {
+        self.chunk(ChunkType::ControllerState.id())
+    }
+    pub fn set_controller_state(&mut self, state: Vec<u8>) {
+        self.set_chunk(ChunkType::ControllerState.id(), state);
+    }
+    /// Program list and program data of a `Prog` chunk
+    pub fn program_data(&self) -> Option<(ProgramListID, &[u8])> {
+        let data = self.chunk(ChunkType::ProgramData.id())?;
+        let (list_id, data) = data.split_first_chunk::<4>()?;
+        Some((ProgramListID::from_le_bytes(*list_id), data))
+    }
+    pub fn set_program_data(&mut self, list_id: ProgramListID, data: &[u8]) {
+        let chunk = list_id.to_le_bytes().iter().chain(data).copied().collect();
+        self.set_chunk(ChunkType::ProgramData.id(), chunk);
+    }
+    /// Meta info XML of the `Info` chunk
+    pub fn meta_info(&self) -> Option<&[u8]> {
+        self.chunk(ChunkType::MetaInfo.id())
+    }
+    pub fn set_meta_info(&mut self, xml: Vec<u8>) {
+        self.set_chunk(ChunkType::MetaInfo.id(), xml);
+    }
+    /// Reads a preset starting at the current position of `stream`
+    pub fn read(stream: &IBStream) -> Result<Self, PresetError> {
+        let mut reader = StreamReader::new(stream);
+        let base = reader.position();
+        let mut id = [0; 4];
+        reader.read_raw(&mut id)?;
+        if id != ChunkType::Header.id() {
+            return Err(PresetError::InvalidHeader);
+        }
+        // Newer versions only add chunks
+        let _version = reader.read_i32()?;
+        let mut class_id = [0; kClassIDSize];
+        reader.read_raw(&mut class_id)?;
+        let class_id = std::str::from_utf8(&class_id)
+            .ok()
+            .and_then(class_id_from_string)
+            .ok_or(PresetError::InvalidHeader)?;
+        let list_offset = reader.read_i64()?;
+        if list_offset < kHeaderSize {
+            return Err(PresetError::InvalidChunkList);
+        }
+
+        let list_position = base
+            .checked_add(list_offset)
+            .ok_or(PresetError::InvalidChunkList)?;
+        reader.seek(list_position)?;
+        reader.read_raw(&mut id)?;
+        let count = reader.read_i32()?;
+        if id != ChunkType::ChunkList.id() || count < 0 {
+            return Err(PresetError::InvalidChunkList);
+        }
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let mut id = [0; 4];
+            reader.read_raw(&mut id)?;
+            let offset = reader.read_i64()?;
+            let size = reader.read_i64()?;
+            let position = base
+                .checked_add(offset)
+                .filter(|position| {
+                    offset >= kHeaderSize && size >= 0 && position.checked_add(size).is_some()
+                })
+                .ok_or(PresetError::InvalidChunkList)?;
+            entries.push((id, position, size));
+        }
+
+        let mut preset = Self::new(class_id);
+        for (id, position, size) in entries {
+            reader.seek(position)?;
+            let data = reader.read_block(size as usize)?;
+            preset.chunks.push(PresetChunk { id, data });
+        }
+        Ok(preset)
+    }
+    /// Writes the preset at the current position of `stream`
+    pub fn write(&self, stream: &IBStream) -> Result<(), PresetError> {
+        let mut writer = StreamWriter::new(stream);
+        writer.write_raw(&ChunkType::Header.id())?;
+        writer.write_i32(kFormatVersion)?;
+        writer.write_raw(class_id_to_string(&self.class_id).as_bytes())?;
+        writer.write_i64(kHeaderSize + self.data_size())?;
+        for chunk in &self.chunks {
+            writer.write_raw(&chunk.data)?;
+        }
+        writer.write_raw(&ChunkType::ChunkList.id())?;
+        writer.write_i32(self.chunks.len() as i32)?;
+        let mut offset = kHeaderSize;
+        for chunk in &self.chunks {
+            writer.write_raw(&chunk.id)?;
+            writer.write_i64(offset)?;
+            writer.write_i64(chunk.data.len() as i64)?;
+            offset += chunk.data.len() as i64;
+        }
+        Ok(())
+    }
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, PresetError> {
+        Self::read(&MemoryStream::from_vec(bytes).as_interface())
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let memory = MemoryStream::new();
+        // Writing to memory only fails if the preset exceeds the stream size
+        let _ = self.write(&memory.as_interface());
+        memory.into_inner()
+    }
+    fn data_size(&self) -> i64 {
+        self.chunks
+            .iter()
+            .map(|chunk| chunk.data.len() as i64)
+            .sum()
+    }
+    /// Size of the file written by [`VstPreset::write`]
+    pub fn file_size(&self) -> i64 {
+        kHeaderSize + self.data_size() + 8 + kListEntrySize * self.chunks.len() as i64
+    }
+    /// Stores the states of a component and its controller
+    pub fn from_component(
+        class_id: FUID,
+        component: &IComponent,
+        controller: Option<&IEditController>,
+    ) -> Result<Self, PresetError> {
+        let mut preset = Self::new(class_id);
+        let state = MemoryStream::new();
+        component.get_state(&state.as_interface())?;
+        preset.set_component_state(state.into_inner());
+        if let Some(controller) = controller {
+            let state = MemoryStream::new();
+            controller.get_state(&state.as_interface())?;
+            preset.set_controller_state(state.into_inner());
+        }
+        Ok(preset)
+    }
+    /// Loads the preset into a component and its controller
+    ///
+    /// The component state is applied to the component, then passed to the controller
+    /// with `setComponentState`, then the controller state is applied if present.
+    pub fn apply(
+        &self,
+        component: &IComponent,
+        controller: Option<&IEditController>,
+    ) -> Result<(), PresetError> {
+        let state = self
+            .component_state()
+            .ok_or(PresetError::MissingChunk(ChunkType::ComponentState.id()))?;
+        component.set_state(&MemoryStream::from_vec(state.to_vec()).as_interface())?;
+        let Some(controller) = controller else {
+            return Ok(());
+        };
+        controller.set_component_state(&MemoryStream::from_vec(state.to_vec()).as_interface())?;
+        if let Some(state) = self.controller_state() {
+            controller.set_state(&MemoryStream::from_vec(state.to_vec()).as_interface())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugininterfaces::{
+        base::{ipluginbase::IPluginBaseImpl, pluginreexports::*, InterfaceRef, TUID},
+        vst::{
+            ivstcomponent::{
+                BusDirection, BusInfo, IComponentImpl, IComponentVtable, IoMode, MediaType,
+                RoutingInfo,
+            },
+            ivsteditcontroller::{
+                IComponentHandler, IEditControllerImpl, IEditControllerVtable, ParameterInfo,
+            },
+            vsttypes::{ParamID, ParamValue, String128, TChar},
+        },
+    };
+    use std::{
+        cell::RefCell,
+        ffi::CStr,
+        io::{Read, Seek, SeekFrom, Write},
+        os::raw::c_void,
+    };
+
+    static COMPONENT_VTABLE: IComponentVtable = IComponentVtable::new::<Plugin, 0>();
+    static CONTROLLER_VTABLE: IEditControllerVtable = IEditControllerVtable::new::<Plugin, -1>();
+
+    /// Component and controller recording the states they receive
+    #[repr(C)]
+    struct Plugin {
+        component_vtable: &'static IComponentVtable,
+        controller_vtable: &'static IEditControllerVtable,
+        calls: RefCell<Vec<(&'static str, Vec<u8>)>>,
+    }
+    impl Plugin {
+        fn new() -> Self {
+            Self {
+                component_vtable: &COMPONENT_VTABLE,
+                controller_vtable: &CONTROLLER_VTABLE,
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+        fn component(&self) -> InterfaceRef<'_, IComponent> {
+            unsafe { InterfaceRef::from_raw(self as *const Self as *mut c_void).unwrap() }
+        }
+        fn controller(&self) -> InterfaceRef<'_, IEditController> {
+            unsafe {
+                InterfaceRef::from_raw(&self.controller_vtable as *const _ as *mut c_void).unwrap()
+            }
+        }
+        fn record(&self, call: &'static str, state: &IBStream) -> Result<(), EInterface> {
+            let mut data = Vec::new();
+            state
+                .io()
+                .read_to_end(&mut data)
+                .map_err(|_| EInterface::ResultFalse)?;
+            self.calls.borrow_mut().push((call, data));
+            Ok(())
+        }
+    }
+    impl FUnknownImpl for Plugin {
+        type Impl = Self;
+
+        fn get_impl(&self) -> &Self::Impl {
+            self
+        }
+        unsafe fn query_interface(
+            &self,
+            _iid: *const FUID,
+            _interface: *mut *mut c_void,
+        ) -> Result<(), EInterface> {
+            Err(EInterface::BadQuery)
+        }
+        fn add_ref(&self) -> u32 {
+            1
+        }
+        unsafe fn release(_self: *mut Self) -> u32 {
+            1
+        }
+
+        const INNER_OFFSET_IN_POINTERS: usize = 0;
+    }
+    impl IPluginBaseImpl for Plugin {
+        unsafe fn initialize(&self, _context: *mut c_void) -> Result<(), EInterface> {
+            Ok(())
+        }
+        unsafe fn terminate(&self) {}
+
+        const INNER_OFFSET_IN_POINTERS: usize = 0;
+    }
+    impl IComponentImpl for Plugin {
+        unsafe fn get_controller_class_id(&self) -> Result<TUID, EInterface> {
+            Err(EInterface::NotImplemented)
+        }
+        unsafe fn set_io_mode(&self, _mode: IoMode) -> Result<(), EInterface> {
+            Ok(())
+        }
+        unsafe fn get_bus_count(&self, _type_: MediaType, _dir: BusDirection) -> i32 {
+            0
+        }
+        unsafe fn get_bus_info(
+            &self,
+            _type_: MediaType,
+            _dir: BusDirection,
+            _index: i32,
+        ) -> Result<BusInfo, EInterface> {
+            Err(EInterface::InvalidArgument)
+        }
+        unsafe fn get_routing_info(
+            &self,
+            _in_info: &RoutingInfo,
+        ) -> Result<RoutingInfo, EInterface> {
+            Err(EInterface::NotImplemented)
+        }
+        unsafe fn activate_bus(
+            &self,
+            _type_: MediaType,
+            _dir: BusDirection,
+            _index: i32,
+            _state: bool,
+        ) -> Result<(), EInterface> {
+            Ok(())
+        }
+        unsafe fn set_active(&self, _state: bool) -> Result<(), EInterface> {
+            Ok(())
+        }
+        unsafe fn set_state(&self, state: &IBStream) -> Result<(), EInterface> {
+            self.record("component.setState", state)
+        }
+        unsafe fn get_state(&self, state: &IBStream) -> Result<(), EInterface> {
+            state
+                .io()
+                .write_all(b"component")
+                .map_err(|_| EInterface::ResultFalse)
+        }
+    }
+    impl IEditControllerImpl for Plugin {
+        unsafe fn set_component_state(&self, state: &IBStream) -> Result<(), EInterface> {
+            self.record("controller.setComponentState", state)
+        }
+        unsafe fn set_state(&self, state: &IBStream) -> Result<(), EInterface> {
+            self.record("controller.setState", state)
+        }
+        unsafe fn get_state(&self, state: &IBStream) -> Result<(), EInterface> {
+            state
+                .io()
+                .write_all(b"controller")
+                .map_err(|_| EInterface::ResultFalse)
+        }
+        unsafe fn get_parameter_count(&self) -> i32 {
+            0
+        }
+        unsafe fn get_parameter_info(&self, _index: i32) -> Result<ParameterInfo, EInterface> {
+            Err(EInterface::InvalidArgument)
+        }
+        unsafe fn get_param_string_by_value(
+            &self,
+            _id: ParamID,
+            _value: ParamValue,
+        ) -> Result<String128, EInterface> {
+            Err(EInterface::ResultFalse)
+        }
+        unsafe fn get_param_value_by_string(
+            &self,
+            _id: ParamID,
+            _string: *const TChar,
+        ) -> Result<ParamValue, EInterface> {
+            Err(EInterface::ResultFalse)
+        }
+        unsafe fn normalized_param_to_plain(&self, _id: ParamID, value: ParamValue) -> ParamValue {
+            value
+        }
+        unsafe fn plain_param_to_normalized(&self, _id: ParamID, value: ParamValue) -> ParamValue {
+            value
+        }
+        unsafe fn get_param_normalized(&self, _id: ParamID) -> ParamValue {
+            0.0
+        }
+        unsafe fn set_param_normalized(
+            &self,
+            _id: ParamID,
+            _value: ParamValue,
+        ) -> Result<(), EInterface> {
+            Err(EInterface::ResultFalse)
+        }
+        unsafe fn set_component_handler(
+            &self,
+            _handler: Option<&IComponentHandler>,
+        ) -> Result<(), EInterface> {
+            Ok(())
+        }
+        unsafe fn create_view(&self, _name: &CStr) -> Option<FUnknown> {
+            None
+        }
+    }
+
+    #[test]
+    fn round_trip_and_load_order() {
+        let class_id = FUID::from([0x0123_4567, 0x89AB_CDEF, 0xFEDC_BA98, 0x7654_3210]);
+        assert_eq!(
+            class_id_to_string(&class_id),
+            "0123456789ABCDEFFEDCBA9876543210"
+        );
+        let plugin = Plugin::new();
+        let mut preset =
+            VstPreset::from_component(class_id, &plugin.component(), Some(&plugin.controller()))
+                .unwrap();
+        preset.set_program_data(7, b"program");
+        preset.set_meta_info(b"<MetaInfo/>".to_vec());
+
+        let stream = MemoryStream::new();
+        preset.write(&stream.as_interface()).unwrap();
+        assert_eq!(stream.len() as i64, preset.file_size());
+        assert_eq!(&stream.data()[..8], b"VST3\x01\0\0\0");
+        stream.set_position(0);
+        let read = VstPreset::read(&stream.as_interface()).unwrap();
+        assert!(read.class_id() == class_id);
+        assert_eq!(read.chunks(), preset.chunks());
+        assert_eq!(read.program_data(), Some((7, &b"program"[..])));
+        assert_eq!(read.to_bytes(), stream.into_inner());
+
+        read.apply(&plugin.component(), Some(&plugin.controller()))
+            .unwrap();
+        let calls = plugin.calls.borrow();
+        assert_eq!(
+            *calls,
+            [
+                ("component.setState", b"component".to_vec()),
+                ("controller.setComponentState", b"component".to_vec()),
+                ("controller.setState", b"controller".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert_eq!(
+            VstPreset::from_bytes(b"VST2".to_vec()).err(),
+            Some(PresetError::InvalidHeader)
+        );
+        let mut bytes = VstPreset::new(FUID::from([1, 2, 3, 4])).to_bytes();
+        bytes.truncate(kHeaderSize as usize + 2);
+        assert!(matches!(
+            VstPreset::from_bytes(bytes),
+            Err(PresetError::Stream(StreamError::UnexpectedEnd {
+                offset: 48,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn rejects_overflowing_offsets() {
+        let stream = MemoryStream::new();
+        stream.as_interface().io().write_all(&[0; 8]).unwrap();
+        VstPreset::new(FUID::from([1, 2, 3, 4]))
+            .write(&stream.as_interface())
+            .unwrap();
+        let mut bytes = stream.into_inner();
+        let list_offset = 8 + 4 + 4 + kClassIDSize;
+        bytes[list_offset..list_offset + 8].copy_from_slice(&i64::MAX.to_le_bytes());
+        let stream = MemoryStream::from_vec(bytes);
+        stream.as_interface().io().seek(SeekFrom::Start(8)).unwrap();
+        assert_eq!(
+            VstPreset::read(&stream.as_interface()).err(),
+            Some(PresetError::InvalidChunkList)
+        );
+
+        let mut preset = VstPreset::new(FUID::from([1, 2, 3, 4]));
+        preset.set_component_state(vec![1]);
+        let stream = MemoryStream::new();
+        stream.as_interface().io().write_all(&[0; 8]).unwrap();
+        preset.write(&stream.as_interface()).unwrap();
+        let mut bytes = stream.into_inner();
+        // Offset of the only chunk list entry, behind 'List' and the count
+        let entry_offset = bytes.len() - 16;
+        bytes[entry_offset..entry_offset + 8].copy_from_slice(&(i64::MAX - 4).to_le_bytes());
+        let stream = MemoryStream::from_vec(bytes);
+        stream.as_interface().io().seek(SeekFrom::Start(8)).unwrap();
+        assert_eq!(
+            VstPreset::read(&stream.as_interface()).err(),
+            Some(PresetError::InvalidChunkList)
+        );
+    }
+}
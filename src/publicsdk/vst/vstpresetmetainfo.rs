@@ -0,0 +1,522 @@
+//! Meta information of preset files, the XML of the `Info` chunk
+//!
+//! The meta information describes a preset without loading it into the plug-in:
+//!
+//! ```xml
+//! <?xml version="1.0" encoding="utf-8"?>
+//! <MetaInfo>
+//!     <Attribute id="MediaType" value="VstPreset" type="string" flags="writeProtected"/>
+//!     <Attribute id="PlugInCategory" value="Instrument|Synth" type="string" flags="writeProtected"/>
+//!     <Attribute id="MusicalCharacter" value="Warm|Soft" type="string" flags=""/>
+//! </MetaInfo>
+//! ```
+//!
+//! Multiple values of an attribute, e.g. the tags of the character, are separated by `|`.
+use super::vstpresetfile::VstPreset;
+use crate::plugininterfaces::vst::vstpresetkeys::PresetAttributes;
+use std::{ffi::CStr, fmt};
+
+/// Id of the media type attribute
+pub const MEDIA_TYPE: &CStr = c"MediaType";
+/// Media type of preset files
+pub const VST_PRESET_MEDIA_TYPE: &str = "VstPreset";
+/// Separator of multiple values of an attribute
+pub const VALUE_SEPARATOR: char = '|';
+
+/// Error while reading meta information
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetaInfoError {
+    /// The XML isn't valid UTF-8
+    InvalidUtf8,
+    /// The XML is malformed at the given byte offset
+    Syntax(usize),
+    /// The root element isn't `MetaInfo`
+    MissingRoot,
+}
+impl fmt::Display for MetaInfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetaInfoError::InvalidUtf8 => write!(f, "Meta info isn't valid UTF-8"),
+            MetaInfoError::Syntax(offset) => write!(f, "Malformed meta info XML at {offset}"),
+            MetaInfoError::MissingRoot => write!(f, "Missing MetaInfo element"),
+        }
+    }
+}
+impl std::error::Error for MetaInfoError {}
+
+/// Attribute of the meta information which isn't part of the typed model
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetaAttribute {
+    pub id: String,
+    pub value: String,
+    /// Type of the value, e.g. `string`
+    pub type_: String,
+    /// Flags of the attribute, e.g. `writeProtected`
+    pub flags: String,
+}
+
+/// Meta information of a preset, see [`PresetAttributes`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PresetMetaInfo {
+    /// Media type, [`VST_PRESET_MEDIA_TYPE`] for preset files
+    pub media_type: Option<String>,
+    /// [`PresetAttributes::PLUGIN_NAME`]
+    pub plugin_name: Option<String>,
+    /// [`PresetAttributes::PLUGIN_CATEGORY`], e.g. `Instrument|Synth`
+    pub plugin_category: Option<String>,
+    /// [`PresetAttributes::INSTRUMENT`]
+    pub instrument: Option<String>,
+    /// [`PresetAttributes::STYLE`]
+    pub style: Option<String>,
+    /// [`PresetAttributes::CHARACTER`], e.g. `Warm|Soft`
+    pub character: Option<String>,
+    /// [`PresetAttributes::STATE_TYPE`]
+    pub state_type: Option<String>,
+    /// [`PresetAttributes::FILE_PATH_STRING_TYPE`]
+    pub file_path: Option<String>,
+    /// [`PresetAttributes::NAME`]
+    pub name: Option<String>,
+    /// [`PresetAttributes::FILE_NAME`]
+    pub file_name: Option<String>,
+    /// Other attributes, kept in file order
+    pub other: Vec<MetaAttribute>,
+}
+impl PresetMetaInfo {
+    /// Meta information of a preset of the plug-in `plugin_name` in category `plugin_category`
+    pub fn new(plugin_name: &str, plugin_category: &str) -> Self {
+        Self {
+            media_type: Some(VST_PRESET_MEDIA_TYPE.to_owned()),
+            plugin_name: Some(plugin_name.to_owned()),
+            plugin_category: Some(plugin_category.to_owned()),
+            ..Default::default()
+        }
+    }
+    /// Typed fields by attribute id, in the order they are written
+    fn fields(&self) -> [(&'static CStr, &Option<String>); 10] {
+        [
+            (MEDIA_TYPE, &self.media_type),
+            (PresetAttributes::PLUGIN_NAME, &self.plugin_name),
+            (PresetAttributes::PLUGIN_CATEGORY, &self.plugin_category),
+            (PresetAttributes::INSTRUMENT, &self.instrument),
+            (PresetAttributes::STYLE, &self.style),
+            (PresetAttributes::CHARACTER, &self.character),
+            (PresetAttributes::STATE_TYPE, &self.state_type),
+            (PresetAttributes::FILE_PATH_STRING_TYPE, &self.file_path),
+            (PresetAttributes::NAME, &self.name),
+            (PresetAttributes::FILE_NAME, &self.file_name),
+        ]
+    }
+    fn field_mut(&mut self, id: &str) -> Option<&mut Option<String>> {
+        let fields = [
+            (MEDIA_TYPE, &mut self.media_type),
+            (PresetAttributes::PLUGIN_NAME, &mut self.plugin_name),
+            (PresetAttributes::PLUGIN_CATEGORY, &mut self.plugin_category),
+            (PresetAttributes::INSTRUMENT, &mut self.instrument),
+            (PresetAttributes::STYLE, &mut self.style),
+            (PresetAttributes::CHARACTER, &mut self.character),
+            (PresetAttributes::STATE_TYPE, &mut self.state_type),
+            (PresetAttributes::FILE_PATH_STRING_TYPE, &mut self.file_path),
+            (PresetAttributes::NAME, &mut self.name),
+            (PresetAttributes::FILE_NAME, &mut self.file_name),
+        ];
+        fields
+            .into_iter()
+            .find(|(key, _)| key.to_bytes() == id.as_bytes())
+            .map(|(_, field)| field)
+    }
+    /// Value of the attribute `id`, typed or other
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.fields()
+            .into_iter()
+            .find(|(key, _)| key.to_bytes() == id.as_bytes())
+            .map(|(_, value)| value.as_deref())
+            .unwrap_or_else(|| {
+                self.other
+                    .iter()
+                    .find(|attribute| attribute.id == id)
+                    .map(|attribute| attribute.value.as_str())
+            })
+    }
+    /// Sets the attribute `id`, typed or other
+    pub fn set(&mut self, id: &str, value: &str) {
+        if let Some(field) = self.field_mut(id) {
+            *field = Some(value.to_owned());
+        } else if let Some(attribute) = self.other.iter_mut().find(|other| other.id == id) {
+            attribute.value = value.to_owned();
+        } else {
+            self.other.push(MetaAttribute {
+                id: id.to_owned(),
+                value: value.to_owned(),
+                type_: "string".to_owned(),
+                flags: String::new(),
+            });
+        }
+    }
+    /// Category tags, e.g. `["Instrument", "Synth"]`
+    pub fn category_tags(&self) -> Vec<&str> {
+        split_values(&self.plugin_category)
+    }
+    /// Character tags, e.g. `["Warm", "Soft"]`
+    pub fn character_tags(&self) -> Vec<&str> {
+        split_values(&self.character)
+    }
+    /// Reads the XML of an `Info` chunk
+    pub fn from_xml(xml: &[u8]) -> Result<Self, MetaInfoError> {
+        let text = std::str::from_utf8(xml).map_err(|_| MetaInfoError::InvalidUtf8)?;
+        let mut parser = Parser { text, pos: 0 };
+        // Optional byte order mark
+        parser.eat("\u{FEFF}");
+        parser.skip_misc()?;
+        let root = parser.element(0)?;
+        parser.skip_misc()?;
+        if parser.pos != text.len() {
+            return Err(MetaInfoError::Syntax(parser.pos));
+        }
+        if root.name != "MetaInfo" {
+            return Err(MetaInfoError::MissingRoot);
+        }
+        let mut info = Self::default();
+        for child in root
+            .children
+            .iter()
+            .filter(|child| child.name == "Attribute")
+        {
+            let Some(id) = child.attribute("id") else {
+                continue;
+            };
+            let value = child.attribute("value").unwrap_or_default().to_owned();
+            match info.field_mut(id) {
+                Some(field) => *field = Some(value),
+                None => info.other.push(MetaAttribute {
+                    id: id.to_owned(),
+                    value,
+                    type_: child.attribute("type").unwrap_or_default().to_owned(),
+                    flags: child.attribute("flags").unwrap_or_default().to_owned(),
+                }),
+            }
+        }
+        Ok(info)
+    }
+    /// Writes the XML of an `Info` chunk
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<MetaInfo>\n");
+        let typed = self.fields().into_iter().filter_map(|(id, value)| {
+            let id = id.to_str().unwrap();
+            let flags = if is_write_protected(id) {
+                "writeProtected"
+            } else {
+                ""
+            };
+            value.as_deref().map(|value| (id, value, "string", flags))
+        });
+        let other = self.other.iter().map(|attribute| {
+            (
+                attribute.id.as_str(),
+                attribute.value.as_str(),
+                attribute.type_.as_str(),
+                attribute.flags.as_str(),
+            )
+        });
+        for (id, value, type_, flags) in typed.chain(other) {
+            xml.push_str(&format!(
+                "\t<Attribute id=\"{}\" value=\"{}\" type=\"{}\" flags=\"{}\"/>\n",
+                escape(id),
+                escape(value),
+                escape(type_),
+                escape(flags)
+            ));
+        }
+        xml.push_str("</MetaInfo>\n");
+        xml
+    }
+    /// Reads the `Info` chunk of `preset`, `None` if it has none
+    pub fn from_preset(preset: &VstPreset) -> Option<Result<Self, MetaInfoError>> {
+        preset.meta_info().map(Self::from_xml)
+    }
+    /// Stores the meta information as the `Info` chunk of `preset`
+    pub fn store(&self, preset: &mut VstPreset) {
+        preset.set_meta_info(self.to_xml().into_bytes());
+    }
+}
+
+/// Attributes describing the plug-in, which preset browsers shouldn't let users edit
+fn is_write_protected(id: &str) -> bool {
+    [
+        MEDIA_TYPE,
+        PresetAttributes::PLUGIN_NAME,
+        PresetAttributes::PLUGIN_CATEGORY,
+    ]
+    .iter()
+    .any(|key| key.to_bytes() == id.as_bytes())
+}
+
+fn split_values(value: &Option<String>) -> Vec<&str> {
+    value
+        .as_deref()
+        .map(|value| {
+            value
+                .split(VALUE_SEPARATOR)
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\t' => escaped.push_str("&#9;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+}
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Maximum nesting depth of elements, the meta info only uses two levels
+const MAX_DEPTH: usize = 64;
+
+/// Minimal XML parser for elements, attributes, comments and processing instructions.
+/// Text content is skipped.
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.text[self.pos..]
+    }
+    fn error(&self) -> MetaInfoError {
+        MetaInfoError::Syntax(self.pos)
+    }
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+    fn expect(&mut self, token: &str) -> Result<(), MetaInfoError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+    fn skip_past(&mut self, end: &str) -> Result<(), MetaInfoError> {
+        let index = self.rest().find(end).ok_or(self.error())?;
+        self.pos += index + end.len();
+        Ok(())
+    }
+    /// Skips whitespace, comments, processing instructions and the doctype
+    fn skip_misc(&mut self) -> Result<(), MetaInfoError> {
+        loop {
+            self.skip_whitespace();
+            if self.eat("<?") {
+                self.skip_past("?>")?;
+            } else if self.eat("<!--") {
+                self.skip_past("-->")?;
+            } else if self.eat("<!DOCTYPE") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+    fn name(&mut self) -> Result<&str, MetaInfoError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error());
+        }
+        self.pos += len;
+        Ok(&self.text[self.pos - len..self.pos])
+    }
+    fn attribute_value(&mut self) -> Result<String, MetaInfoError> {
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.error()),
+        };
+        self.pos += 1;
+        let start = self.pos;
+        let len = self.rest().find(quote).ok_or(self.error())?;
+        self.pos += len + 1;
+        unescape(&self.text[start..start + len]).ok_or(MetaInfoError::Syntax(start))
+    }
+    /// Parses an element nested in `depth` others, failing beyond [`MAX_DEPTH`]
+    fn element(&mut self, depth: usize) -> Result<Element, MetaInfoError> {
+        if depth >= MAX_DEPTH {
+            return Err(self.error());
+        }
+        self.expect("<")?;
+        let name = self.name()?.to_owned();
+        let mut element = Element {
+            name,
+            attributes: Vec::new(),
+            children: Vec::new(),
+        };
+        loop {
+            self.skip_whitespace();
+            if self.eat("/>") {
+                return Ok(element);
+            }
+            if self.eat(">") {
+                break;
+            }
+            let key = self.name()?.to_owned();
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let value = self.attribute_value()?;
+            element.attributes.push((key, value));
+        }
+        loop {
+            self.skip_misc()?;
+            if self.eat("</") {
+                if self.name()? != element.name {
+                    return Err(self.error());
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            }
+            if self.rest().starts_with('<') {
+                element.children.push(self.element(depth + 1)?);
+            } else {
+                // Text content
+                let len = self.rest().find('<').ok_or(self.error())?;
+                self.pos += len;
+            }
+        }
+    }
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find('&') {
+        unescaped.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+        let end = rest.find(';')?;
+        let entity = &rest[..end];
+        let c = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()?
+                } else {
+                    entity.strip_prefix('#')?.parse().ok()?
+                };
+                char::from_u32(code)?
+            }
+        };
+        unescaped.push(c);
+        rest = &rest[end + 1..];
+    }
+    unescaped.push_str(rest);
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_sdk_meta_info() {
+        let xml = br#"<?xml version="1.0" encoding="utf-8"?>
+<!-- written by a host -->
+<MetaInfo>
+	<Attribute id="MediaType" value="VstPreset" type="string" flags="writeProtected"/>
+	<Attribute id="PlugInCategory" value="Instrument|Synth" type="string" flags="writeProtected"/>
+	<Attribute id="PlugInName" value="Pad &amp; Lead" type="string" flags="writeProtected"></Attribute>
+	<Attribute id='MusicalCharacter' value='Warm | Soft' type='string' flags=''/>
+	<Attribute id="Rating" value="4" type="int" flags=""/>
+</MetaInfo>
+"#;
+        let info = PresetMetaInfo::from_xml(xml).unwrap();
+        assert_eq!(info.media_type.as_deref(), Some(VST_PRESET_MEDIA_TYPE));
+        assert_eq!(info.plugin_name.as_deref(), Some("Pad & Lead"));
+        assert_eq!(info.category_tags(), ["Instrument", "Synth"]);
+        assert_eq!(info.character_tags(), ["Warm", "Soft"]);
+        assert_eq!(info.get("Rating"), Some("4"));
+        assert_eq!(info.other[0].type_, "int");
+
+        let written = info.to_xml();
+        assert!(written.contains(r#"value="Pad &amp; Lead" type="string" flags="writeProtected""#));
+        assert_eq!(PresetMetaInfo::from_xml(written.as_bytes()), Ok(info));
+    }
+
+    #[test]
+    fn stores_in_preset() {
+        let mut info = PresetMetaInfo::new("Synth", "Instrument|Synth");
+        info.set("MusicalStyle", "Ambient");
+        info.set("Author", "<me>");
+        let mut preset = VstPreset::new(crate::plugininterfaces::base::FUID::from([1, 2, 3, 4]));
+        info.store(&mut preset);
+        let read = PresetMetaInfo::from_preset(&preset).unwrap().unwrap();
+        assert_eq!(read.style.as_deref(), Some("Ambient"));
+        assert_eq!(read.get("Author"), Some("<me>"));
+        assert_eq!(read, info);
+
+        assert_eq!(
+            PresetMetaInfo::from_xml(b"<Info/>"),
+            Err(MetaInfoError::MissingRoot)
+        );
+        assert_eq!(
+            PresetMetaInfo::from_xml(b"<MetaInfo><Attribute id=\"a\"></MetaInfo>"),
+            Err(MetaInfoError::Syntax(38))
+        );
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let depth = 300_000;
+        let xml = format!(
+            "<MetaInfo>{}{}</MetaInfo>",
+            "<a>".repeat(depth),
+            "</a>".repeat(depth)
+        );
+        assert_eq!(
+            PresetMetaInfo::from_xml(xml.as_bytes()),
+            Err(MetaInfoError::Syntax(10 + 3 * (MAX_DEPTH - 1)))
+        );
+        let xml = format!(
+            "<MetaInfo>{}{}</MetaInfo>",
+            "<a>".repeat(62),
+            "</a>".repeat(62)
+        );
+        assert!(PresetMetaInfo::from_xml(xml.as_bytes()).is_ok());
+    }
+}
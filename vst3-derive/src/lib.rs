@@ -0,0 +1,421 @@
+//! Derive macros for the `vst3` crate
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Expr, ExprArray, ExprLit, ExprRange,
+    Fields, Ident, Lit, LitInt, LitStr, RangeLimits,
+};
+
+/// Derives `vst3::publicsdk::vst::params::Params` for a struct of `AtomicParam` fields.
+///
+/// Every field annotated with `#[param(...)]` becomes a parameter, other fields are
+/// initialized with `Default::default()`. The derive also implements `Default`.
+///
+/// Field attributes:
+/// - `id = <expr>`: parameter ID, required and unique within the struct
+/// - `title = "..."`, `short_title = "..."`: titles, the title defaults to the field name
+/// - `unit = "..."`: unit label
+/// - `range = <min>..=<max>`: plain range, defaults to `0.0..=1.0`
+/// - `steps = <n>`: number of steps for a stepped range
+/// - `list = ["a", "b", ...]`: string list parameter
+/// - `bool`: on/off parameter
+/// - `default = <expr>`: default plain value, list index or bool
+/// - `precision = <n>`: digits after the decimal point when formatting
+/// - `unit_id = <expr>`: unit the parameter belongs to
+/// - `automate`, `read_only`, `wrap_around`, `hidden`, `program_change`, `bypass`: flags
+///
+/// The container attribute `#[params(version = <n>)]` sets the state version written by `get_state`.
+#[proc_macro_derive(Params, attributes(param, params))]
+pub fn derive_params(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_params(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+enum Kind {
+    Range { min: Box<Expr>, max: Box<Expr> },
+    List(Vec<LitStr>),
+    Bool,
+}
+
+struct ParamAttributes {
+    id: Expr,
+    title: Option<LitStr>,
+    short_title: Option<LitStr>,
+    unit: Option<LitStr>,
+    range: Option<(Expr, Expr)>,
+    steps: Option<Expr>,
+    list: Option<Vec<LitStr>>,
+    is_bool: bool,
+    default: Option<Expr>,
+    precision: Option<Expr>,
+    unit_id: Option<Expr>,
+    flags: Vec<Ident>,
+}
+
+struct ParamField {
+    ident: Ident,
+    kind: Kind,
+    attributes: ParamAttributes,
+}
+
+const FLAGS: &[(&str, &str)] = &[
+    ("automate", "CanAutomate"),
+    ("read_only", "IsReadOnly"),
+    ("wrap_around", "IsWrapAround"),
+    ("hidden", "IsHidden"),
+    ("program_change", "IsProgramChange"),
+    ("bypass", "IsBypass"),
+];
+
+fn expand_params(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "Params can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            input.span(),
+            "Params can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut version: Option<Expr> = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("params"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("version") {
+                version = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown params attribute, expected `version`"))
+            }
+        })?;
+    }
+    let version = version.unwrap_or_else(|| syn::parse_quote!(1));
+
+    let mut params = Vec::new();
+    let mut other_fields = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.clone().unwrap();
+        let Some(attr) = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("param"))
+        else {
+            other_fields.push(ident);
+            continue;
+        };
+        let attributes = parse_param_attributes(attr)?;
+        let kind = match (&attributes.range, &attributes.list, attributes.is_bool) {
+            (_, Some(_), true) | (Some(_), _, true) | (Some(_), Some(_), _) => {
+                return Err(syn::Error::new(
+                    attr.span(),
+                    "`range`, `list` and `bool` are mutually exclusive",
+                ))
+            }
+            (_, Some(list), false) => {
+                if list.is_empty() {
+                    return Err(syn::Error::new(attr.span(), "`list` must not be empty"));
+                }
+                Kind::List(list.clone())
+            }
+            (None, None, true) => Kind::Bool,
+            (Some((min, max)), None, false) => Kind::Range {
+                min: Box::new(min.clone()),
+                max: Box::new(max.clone()),
+            },
+            (None, None, false) => Kind::Range {
+                min: syn::parse_quote!(0.0),
+                max: syn::parse_quote!(1.0),
+            },
+        };
+        if attributes.steps.is_some() && !matches!(kind, Kind::Range { .. }) {
+            return Err(syn::Error::new(
+                attr.span(),
+                "`steps` can only be used with range parameters",
+            ));
+        }
+        params.push(ParamField {
+            ident,
+            kind,
+            attributes,
+        });
+    }
+
+    check_literal_ids(&params)?;
+
+    let krate = quote!(::vst3);
+    let module = quote!(#krate::publicsdk::vst::params);
+    let flags_path = quote!(#krate::plugininterfaces::vst::ivsteditcontroller::ParameterFlags);
+
+    let descriptors = params.iter().map(|param| {
+        let attributes = &param.attributes;
+        let id = &attributes.id;
+        let title = attributes
+            .title
+            .as_ref()
+            .map(LitStr::value)
+            .unwrap_or_else(|| title_from_ident(&param.ident));
+        let short_title = attributes
+            .short_title
+            .as_ref()
+            .map(LitStr::value)
+            .unwrap_or_default();
+        let units = attributes
+            .unit
+            .as_ref()
+            .map(LitStr::value)
+            .unwrap_or_default();
+        let (kind, step_count, default_plain) = match &param.kind {
+            Kind::Range { min, max } => {
+                let step_count = match &attributes.steps {
+                    Some(steps) => quote!((#steps) as i32),
+                    None => quote!(0),
+                };
+                let default_plain = match &attributes.default {
+                    Some(default) => quote!((#default) as f64),
+                    None => quote!((#min) as f64),
+                };
+                (
+                    quote!(#module::ParamKind::Range {
+                        min: (#min) as f64,
+                        max: (#max) as f64,
+                    }),
+                    step_count,
+                    default_plain,
+                )
+            }
+            Kind::List(strings) => {
+                let step_count = strings.len() as i32 - 1;
+                let default_plain = match &attributes.default {
+                    Some(default) => quote!((#default) as f64),
+                    None => quote!(0.0),
+                };
+                (
+                    quote!(#module::ParamKind::List(&[#(#strings),*])),
+                    quote!(#step_count),
+                    default_plain,
+                )
+            }
+            Kind::Bool => {
+                let default_plain = match &attributes.default {
+                    Some(default) => quote!(if #default { 1.0 } else { 0.0 }),
+                    None => quote!(0.0),
+                };
+                (quote!(#module::ParamKind::Bool), quote!(1), default_plain)
+            }
+        };
+        let mut flags: Vec<TokenStream2> = attributes
+            .flags
+            .iter()
+            .map(|flag| {
+                let variant = FLAGS
+                    .iter()
+                    .find(|(name, _)| flag == name)
+                    .map(|(_, variant)| Ident::new(variant, flag.span()))
+                    .unwrap();
+                quote!(#flags_path::#variant as i32)
+            })
+            .collect();
+        if matches!(param.kind, Kind::List(_)) {
+            flags.push(quote!(#flags_path::IsList as i32));
+        }
+        let flags = if flags.is_empty() {
+            quote!(#flags_path::NoFlags as i32)
+        } else {
+            quote!(#(#flags)|*)
+        };
+        let unit_id = match &attributes.unit_id {
+            Some(unit_id) => quote!(#unit_id),
+            None => quote!(#krate::plugininterfaces::vst::vsttypes::kRootUnitId),
+        };
+        let precision = match &attributes.precision {
+            Some(precision) => quote!(#precision),
+            None => quote!(4),
+        };
+        quote_spanned! {param.ident.span()=>
+            #module::ParamDescriptor {
+                id: #id,
+                title: #title,
+                short_title: #short_title,
+                units: #units,
+                kind: #kind,
+                step_count: #step_count,
+                default_plain: #default_plain,
+                flags: #flags,
+                unit_id: #unit_id,
+                precision: #precision,
+            }
+        }
+    });
+
+    let lookups = params.iter().map(|param| {
+        let ident = &param.ident;
+        let id = &param.attributes.id;
+        quote!(if id == #id {
+            return Some(&self.#ident);
+        })
+    });
+    let initializers = params.iter().enumerate().map(|(index, param)| {
+        let ident = &param.ident;
+        quote!(#ident: #module::AtomicParam::new(
+            &<Self as #module::Params>::DESCRIPTORS[#index]
+        ))
+    });
+    let ids = params.iter().map(|param| &param.attributes.id);
+    let count = params.len();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #module::Params for #name #ty_generics #where_clause {
+            const STATE_VERSION: u32 = #version;
+            const DESCRIPTORS: &'static [#module::ParamDescriptor] = &[#(#descriptors),*];
+
+            fn param(
+                &self,
+                id: #krate::plugininterfaces::vst::vsttypes::ParamID,
+            ) -> Option<&#module::AtomicParam> {
+                #(#lookups)*
+                None
+            }
+        }
+        impl #impl_generics ::core::default::Default for #name #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #(#initializers,)*
+                    #(#other_fields: ::core::default::Default::default(),)*
+                }
+            }
+        }
+        const _: () = {
+            let ids: [#krate::plugininterfaces::vst::vsttypes::ParamID; #count] = [#(#ids),*];
+            let mut i = 0;
+            while i < ids.len() {
+                let mut j = i + 1;
+                while j < ids.len() {
+                    if ids[i] == ids[j] {
+                        panic!(concat!("duplicate parameter ID in ", stringify!(#name)));
+                    }
+                    j += 1;
+                }
+                i += 1;
+            }
+        };
+    })
+}
+
+fn parse_param_attributes(attr: &syn::Attribute) -> syn::Result<ParamAttributes> {
+    let mut id = None;
+    let mut attributes = ParamAttributes {
+        id: syn::parse_quote!(0),
+        title: None,
+        short_title: None,
+        unit: None,
+        range: None,
+        steps: None,
+        list: None,
+        is_bool: false,
+        default: None,
+        precision: None,
+        unit_id: None,
+        flags: Vec::new(),
+    };
+    attr.parse_nested_meta(|meta| {
+        let path = &meta.path;
+        if path.is_ident("id") {
+            id = Some(meta.value()?.parse()?);
+        } else if path.is_ident("title") {
+            attributes.title = Some(meta.value()?.parse()?);
+        } else if path.is_ident("short_title") {
+            attributes.short_title = Some(meta.value()?.parse()?);
+        } else if path.is_ident("unit") {
+            attributes.unit = Some(meta.value()?.parse()?);
+        } else if path.is_ident("range") {
+            let range: ExprRange = meta.value()?.parse()?;
+            let (Some(min), Some(max), RangeLimits::Closed(_)) =
+                (range.start, range.end, range.limits)
+            else {
+                return Err(meta.error("`range` expects an inclusive range like `0.0..=1.0`"));
+            };
+            attributes.range = Some((*min, *max));
+        } else if path.is_ident("steps") {
+            attributes.steps = Some(meta.value()?.parse()?);
+        } else if path.is_ident("list") {
+            let array: ExprArray = meta.value()?.parse()?;
+            let strings = array
+                .elems
+                .iter()
+                .map(|elem| match elem {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(string),
+                        ..
+                    }) => Ok(string.clone()),
+                    _ => Err(syn::Error::new(elem.span(), "expected a string literal")),
+                })
+                .collect::<syn::Result<_>>()?;
+            attributes.list = Some(strings);
+        } else if path.is_ident("bool") {
+            attributes.is_bool = true;
+        } else if path.is_ident("default") {
+            attributes.default = Some(meta.value()?.parse()?);
+        } else if path.is_ident("precision") {
+            attributes.precision = Some(meta.value()?.parse()?);
+        } else if path.is_ident("unit_id") {
+            attributes.unit_id = Some(meta.value()?.parse()?);
+        } else if let Some((name, _)) = FLAGS.iter().find(|(name, _)| path.is_ident(name)) {
+            attributes.flags.push(Ident::new(name, path.span()));
+        } else {
+            return Err(meta.error("unknown param attribute"));
+        }
+        Ok(())
+    })?;
+    attributes.id = id.ok_or_else(|| syn::Error::new(attr.span(), "missing `id = ...`"))?;
+    Ok(attributes)
+}
+
+/// Reports duplicated literal IDs at the offending attribute,
+/// IDs given as constants are checked by the generated const assertion.
+fn check_literal_ids(params: &[ParamField]) -> syn::Result<()> {
+    let mut seen: Vec<(u64, Span)> = Vec::new();
+    for param in params {
+        let Expr::Lit(ExprLit {
+            lit: Lit::Int(id), ..
+        }) = &param.attributes.id
+        else {
+            continue;
+        };
+        let value = LitInt::base10_parse::<u64>(id)?;
+        if let Some((_, first)) = seen.iter().find(|(seen, _)| *seen == value) {
+            let mut error = syn::Error::new(id.span(), format!("duplicate parameter ID {value}"));
+            error.combine(syn::Error::new(*first, "first used here"));
+            return Err(error);
+        }
+        seen.push((value, id.span()));
+    }
+    Ok(())
+}
+
+fn title_from_ident(ident: &Ident) -> String {
+    ident
+        .to_string()
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}